@@ -0,0 +1,763 @@
+//! The version arithmetic behind a bump. Nothing here touches the filesystem, git, or a
+//! process, so it can be reused wherever a manifest's contents are already in hand
+
+use semver::{BuildMetadata, Prerelease, Version};
+
+/// The prerelease `--next-dev` leaves the manifest on. A patch release from it finishes
+/// that version instead of skipping past it
+pub const DEV_PRE_ID: &str = "dev";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum VersionBump {
+    #[cfg_attr(feature = "cli", value(alias = "pre"))]
+    Prepatch,
+    #[cfg_attr(feature = "cli", value(alias = "fix"))]
+    Patch,
+    Preminor,
+    #[cfg_attr(feature = "cli", value(alias = "feature"))]
+    Minor,
+    Premajor,
+    #[cfg_attr(feature = "cli", value(alias = "breaking"))]
+    Major,
+    Skip,
+}
+
+macro_rules! _vext_def_field {
+    ($name:literal, $set:ident, $get:ident, $reset:ident, $inc:ident) => {
+        fn $set(self, version: u64) -> Self;
+        fn $get(&self) -> u64;
+        fn $reset(self) -> Self {
+            self.$set(0)
+        }
+        fn $inc(self, by: u64) -> anyhow::Result<Self> {
+            let v = self.$get().checked_add(by).ok_or(anyhow::anyhow!(
+                "Cannot increment the {} version past {}",
+                $name,
+                u64::MAX
+            ))?;
+            Ok(self.$set(v))
+        }
+    };
+}
+trait VersionExt: Sized {
+    _vext_def_field!("major", set_major, get_major, _reset_major, inc_major);
+    _vext_def_field!("minor", set_minor, get_minor, reset_minor, inc_minor);
+    _vext_def_field!("patch", set_patch, get_patch, reset_patch, inc_patch);
+
+    fn set_pre(self, id: &str, version: u64) -> anyhow::Result<Self>;
+    fn get_pre(&self, id: &str) -> Option<u64>;
+    fn reset_pre(self) -> Self;
+    fn inc_pre(self, id: &str) -> anyhow::Result<Self> {
+        let v = self
+            .get_pre(id)
+            .map_or(Some(0), |v| v.checked_add(1))
+            .ok_or(anyhow::anyhow!(
+                "Cannot increment the prerelease version past {}",
+                u64::MAX
+            ))?;
+        self.set_pre(id, v)
+    }
+
+    /// Increment the trailing numeric identifier of whatever prerelease is
+    /// present (`pre` → `pre.0`, `alpha.1.2` → `alpha.1.3`), keeping the rest
+    fn inc_pre_preserving(self, id: &str) -> anyhow::Result<Self>;
+
+    /// Replace the trailing numeric identifier of the prerelease with `counter`
+    fn set_pre_counter(self, counter: u64) -> anyhow::Result<Self>;
+
+    /// Set the prerelease to `<id>.<stamp>`, or `<id>.<stamp>.N` counting up from 1 when
+    /// the version already carries that stamp
+    fn set_pre_stamp(self, id: &str, stamp: &str) -> anyhow::Result<Self>;
+}
+
+macro_rules! _vext_impl_field {
+    ($field:ident, $set:ident, $get:ident) => {
+        fn $set(mut self, version: u64) -> Self {
+            self.$field = version;
+            self
+        }
+
+        fn $get(&self) -> u64 {
+            self.$field
+        }
+    };
+}
+
+impl VersionExt for Version {
+    _vext_impl_field!(major, set_major, get_major);
+    _vext_impl_field!(minor, set_minor, get_minor);
+    _vext_impl_field!(patch, set_patch, get_patch);
+
+    fn set_pre(mut self, id: &str, version: u64) -> anyhow::Result<Self> {
+        self.pre = Prerelease::new(&format!("{id}.{version}"))
+            .map_err(|e| anyhow::anyhow!("`{id}` isn't a valid prerelease identifier: {e}"))?;
+        Ok(self)
+    }
+
+    fn reset_pre(mut self) -> Self {
+        self.pre = Prerelease::EMPTY;
+        self
+    }
+
+    fn get_pre(&self, id: &str) -> Option<u64> {
+        extract_pre_version(&self.pre, id)
+    }
+
+    fn inc_pre_preserving(mut self, id: &str) -> anyhow::Result<Self> {
+        if self.pre.is_empty() {
+            return self.inc_pre(id);
+        }
+
+        let mut identifiers = self.pre.split('.').map(str::to_string).collect::<Vec<_>>();
+        match identifiers.last().and_then(|last| last.parse::<u64>().ok()) {
+            Some(counter) => {
+                let counter = counter.checked_add(1).ok_or(anyhow::anyhow!(
+                    "Cannot increment the prerelease version past {}",
+                    u64::MAX
+                ))?;
+                *identifiers.last_mut().expect("checked above") = counter.to_string();
+            }
+            None => identifiers.push("0".to_string()),
+        }
+
+        self.pre = Prerelease::new(&identifiers.join("."))?;
+        Ok(self)
+    }
+
+    fn set_pre_counter(mut self, counter: u64) -> anyhow::Result<Self> {
+        let mut identifiers = self.pre.split('.').collect::<Vec<_>>();
+        identifiers.pop();
+        let counter = counter.to_string();
+        identifiers.push(&counter);
+        self.pre = Prerelease::new(&identifiers.join("."))?;
+        Ok(self)
+    }
+
+    fn set_pre_stamp(mut self, id: &str, stamp: &str) -> anyhow::Result<Self> {
+        let base = format!("{id}.{stamp}");
+        let pre = match self.pre.as_str() {
+            current if current == base => format!("{base}.1"),
+            current => match current
+                .strip_prefix(&base)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .and_then(|same_day| same_day.parse::<u64>().ok())
+            {
+                Some(counter) => format!("{base}.{}", counter.saturating_add(1)),
+                None => base,
+            },
+        };
+        self.pre = Prerelease::new(&pre).map_err(|_| {
+            anyhow::anyhow!(
+                "`{pre}` isn't a valid prerelease (numeric parts can't have leading zeros, so use a format like %Y%m%d rather than %m%d)"
+            )
+        })?;
+        Ok(self)
+    }
+}
+
+/// What happens to `+build` metadata when a version is bumped
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum BuildPolicy {
+    /// Drop it
+    #[default]
+    Clear,
+    /// Leave it as is
+    Keep,
+    /// Swap it for the `--build` identifiers
+    Replace,
+    /// Add the `--build` identifiers that aren't already present
+    Append,
+}
+
+#[derive(Clone, Debug)]
+pub struct BumpOptions {
+    /// Identifier for new prereleases, `alpha` gives `1.2.4-alpha.0`
+    pub pre_id: String,
+    /// Treat any prerelease (not just `<pre_id>.N`) as a prerelease, and bump its
+    /// trailing counter in place rather than restarting at `<pre_id>.0`
+    pub preserve_prerelease: bool,
+    pub build_policy: BuildPolicy,
+    /// Dot-separated identifiers used by `Replace` and `Append`
+    pub build: Option<String>,
+    /// Added to the major/minor/patch component being bumped
+    pub increment_by: u64,
+    /// Prerelease counter to use instead of incrementing the current one
+    pub pre_counter: Option<u64>,
+    /// Date stamp numbering prereleases instead of a counter, `20240601` gives
+    /// `1.2.4-alpha.20240601`
+    pub pre_stamp: Option<String>,
+}
+
+impl Default for BumpOptions {
+    fn default() -> Self {
+        Self {
+            pre_id: "alpha".to_string(),
+            preserve_prerelease: false,
+            build_policy: BuildPolicy::Clear,
+            build: None,
+            increment_by: 1,
+            pre_counter: None,
+            pre_stamp: None,
+        }
+    }
+}
+
+impl BumpOptions {
+    fn apply_build(&self, existing: &BuildMetadata) -> anyhow::Result<BuildMetadata> {
+        let new = self.build.as_deref().unwrap_or_default();
+        let build = match self.build_policy {
+            BuildPolicy::Clear => return Ok(BuildMetadata::EMPTY),
+            BuildPolicy::Keep => return Ok(existing.clone()),
+            BuildPolicy::Replace => new.to_string(),
+            BuildPolicy::Append => {
+                let mut identifiers = existing
+                    .split('.')
+                    .filter(|id| !id.is_empty())
+                    .collect::<Vec<_>>();
+                for id in new.split('.').filter(|id| !id.is_empty()) {
+                    if !identifiers.contains(&id) {
+                        identifiers.push(id);
+                    }
+                }
+                identifiers.join(".")
+            }
+        };
+
+        BuildMetadata::new(&build)
+            .map_err(|e| anyhow::anyhow!("Invalid build metadata `{build}`: {e}"))
+    }
+}
+
+impl VersionBump {
+    #[must_use]
+    pub fn is_pre(self) -> bool {
+        match self {
+            VersionBump::Prepatch | VersionBump::Preminor | VersionBump::Premajor => true,
+            VersionBump::Patch | VersionBump::Minor | VersionBump::Major | VersionBump::Skip => {
+                false
+            }
+        }
+    }
+
+    /// The same bump limited to the patch component, for 0.0.x crates
+    #[must_use]
+    pub fn patch_only(self) -> Self {
+        match self {
+            VersionBump::Preminor | VersionBump::Premajor => VersionBump::Prepatch,
+            VersionBump::Minor | VersionBump::Major => VersionBump::Patch,
+            VersionBump::Prepatch | VersionBump::Patch | VersionBump::Skip => self,
+        }
+    }
+
+    /// The bump that best describes going from `old` to `new` (for `--to`)
+    #[must_use]
+    pub fn between(old: &Version, new: &Version) -> Self {
+        let pre = !new.pre.is_empty();
+        match (new.major != old.major, new.minor != old.minor, pre) {
+            (true, _, true) => VersionBump::Premajor,
+            (true, _, false) => VersionBump::Major,
+            (false, true, true) => VersionBump::Preminor,
+            (false, true, false) => VersionBump::Minor,
+            (false, false, true) => VersionBump::Prepatch,
+            (false, false, false) => VersionBump::Patch,
+        }
+    }
+
+    pub fn apply(self, mut version: Version, options: &BumpOptions) -> anyhow::Result<Version> {
+        if matches!(self, VersionBump::Skip) {
+            return Ok(version);
+        }
+
+        version.build = options.apply_build(&version.build)?;
+
+        let has_pre = if options.preserve_prerelease {
+            !version.pre.is_empty()
+        } else if options.pre_stamp.is_some() {
+            // `alpha.20240601.1` is still an alpha
+            version
+                .pre
+                .as_str()
+                .starts_with(&format!("{}.", options.pre_id))
+        } else {
+            version.get_pre(&options.pre_id).is_some()
+        };
+
+        let dev = version.get_pre(DEV_PRE_ID).is_some();
+
+        if let Some(stamp) = options.pre_stamp.as_deref().filter(|_| self.is_pre()) {
+            version = version.set_pre_stamp(&options.pre_id, stamp)?;
+        } else if self.is_pre() {
+            version = if options.preserve_prerelease {
+                version.inc_pre_preserving(&options.pre_id)?
+            } else {
+                version.inc_pre(&options.pre_id)?
+            };
+            if let Some(counter) = options.pre_counter {
+                version = version.set_pre_counter(counter)?;
+            }
+        } else {
+            version = version.reset_pre();
+        }
+
+        Ok(match self {
+            VersionBump::Patch | VersionBump::Prepatch if !has_pre && !dev => {
+                version.inc_patch(options.increment_by)?
+            }
+            VersionBump::Patch => version.reset_pre(),
+
+            VersionBump::Minor | VersionBump::Preminor if !has_pre => {
+                version.inc_minor(options.increment_by)?.reset_patch()
+            }
+            VersionBump::Minor => version.reset_patch(),
+
+            VersionBump::Premajor if !has_pre => version
+                .inc_major(options.increment_by)?
+                .reset_minor()
+                .reset_patch(),
+            // Finishing a premajor series: `2.0.0-alpha.4` becomes `2.0.0`
+            VersionBump::Major if has_pre && version.minor == 0 && version.patch == 0 => version,
+            VersionBump::Major => version
+                .inc_major(options.increment_by)?
+                .reset_minor()
+                .reset_patch()
+                .reset_pre(),
+
+            _ => version,
+        })
+    }
+
+    #[must_use]
+    pub fn name(self) -> String {
+        match self {
+            VersionBump::Prepatch => "prepatch",
+            VersionBump::Patch => "patch",
+            VersionBump::Preminor => "preminor",
+            VersionBump::Minor => "minor",
+            VersionBump::Premajor => "premajor",
+            VersionBump::Major => "major",
+            VersionBump::Skip => "skip",
+        }
+        .to_string()
+    }
+}
+
+/// Extract the numeric part from an "-<id>.X" prerelease identifier
+/// Returns Some(X) if the prerelease is in the format "<id>.X", None otherwise
+fn extract_pre_version(prerelease: &Prerelease, id: &str) -> Option<u64> {
+    let pre_str = prerelease.as_str();
+    if let Some(suffix) = pre_str
+        .strip_prefix(id)
+        .and_then(|rest| rest.strip_prefix('.'))
+    {
+        suffix.parse::<u64>().ok()
+    } else {
+        None
+    }
+}
+
+/// The prerelease of `pre` without its trailing counter, and the counter
+fn split_counter(pre: &Prerelease) -> Option<(&str, u64)> {
+    let (prefix, counter) = pre.as_str().rsplit_once('.')?;
+    Some((prefix, counter.parse().ok()?))
+}
+
+/// `version` with its prerelease counter moved past every one in `tagged` for the same
+/// version and prerelease (`1.3.0-alpha.0` becomes `1.3.0-alpha.3` when `1.3.0-alpha.2` is
+/// tagged), so prereleases cut on different branches don't collide
+pub fn past_tagged(version: Version, tagged: &[Version]) -> anyhow::Result<Version> {
+    let Some((prefix, counter)) = split_counter(&version.pre) else {
+        return Ok(version);
+    };
+    let highest = tagged
+        .iter()
+        .filter(|tag| {
+            (tag.major, tag.minor, tag.patch) == (version.major, version.minor, version.patch)
+        })
+        .filter_map(|tag| split_counter(&tag.pre))
+        .filter(|(tag_prefix, _)| *tag_prefix == prefix)
+        .map(|(_, counter)| counter)
+        .max();
+    match highest {
+        Some(highest) if highest >= counter => {
+            let next = highest.checked_add(1).ok_or(anyhow::anyhow!(
+                "Cannot increment the prerelease version past {}",
+                u64::MAX
+            ))?;
+            version.set_pre_counter(next)
+        }
+        _ => Ok(version),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! do_test {
+        ($name:ident, $current:literal, $bump:ident, $expected:literal) => {
+            #[test]
+            fn $name() {
+                let version = Version::parse($current).unwrap();
+                let result = VersionBump::$bump
+                    .apply(version, &BumpOptions::default())
+                    .unwrap();
+                assert_eq!(result.to_string(), $expected);
+            }
+        };
+    }
+
+    do_test!(major_bump, "1.2.3", Major, "2.0.0");
+    do_test!(minor_bump, "1.2.3", Minor, "1.3.0");
+    do_test!(patch_bump, "1.2.3", Patch, "1.2.4");
+
+    do_test!(preminor_first_time, "1.2.3", Preminor, "1.3.0-alpha.0");
+    do_test!(
+        preminor_increment_existing,
+        "1.3.0-alpha.2",
+        Preminor,
+        "1.3.0-alpha.3"
+    );
+    do_test!(premajor_first_time, "1.4.2", Premajor, "2.0.0-alpha.0");
+    do_test!(
+        premajor_increment_existing,
+        "2.0.0-alpha.3",
+        Premajor,
+        "2.0.0-alpha.4"
+    );
+    do_test!(major_finishes_premajor, "2.0.0-alpha.4", Major, "2.0.0");
+    do_test!(prepatch_first_time, "1.2.3", Prepatch, "1.2.4-alpha.0");
+    do_test!(
+        prepatch_increment_existing,
+        "1.2.4-alpha.1",
+        Prepatch,
+        "1.2.4-alpha.2"
+    );
+
+    do_test!(patch_clears_pre, "1.2.3-alpha.0", Patch, "1.2.3");
+    do_test!(minor_clears_pre, "1.2.3-alpha.0", Minor, "1.2.0");
+    do_test!(major_clears_pre, "1.2.3-alpha.0", Major, "2.0.0");
+
+    #[test]
+    fn test_realistic() {
+        macro_rules! apply_and_assert {
+            ($v:ident, $bump:ident, $expected:literal) => {
+                println!("{}", $v);
+                let $v = VersionBump::$bump
+                    .apply($v, &BumpOptions::default())
+                    .unwrap();
+                println!("{}", $v);
+                assert_eq!($v.to_string(), $expected);
+            };
+        }
+
+        let version = Version::parse("0.1.0").unwrap();
+        apply_and_assert!(version, Prepatch, "0.1.1-alpha.0");
+        apply_and_assert!(version, Prepatch, "0.1.1-alpha.1");
+        apply_and_assert!(version, Prepatch, "0.1.1-alpha.2");
+        apply_and_assert!(version, Prepatch, "0.1.1-alpha.3");
+        apply_and_assert!(version, Prepatch, "0.1.1-alpha.4");
+        apply_and_assert!(version, Patch, "0.1.1");
+        apply_and_assert!(version, Preminor, "0.2.0-alpha.0");
+        apply_and_assert!(version, Minor, "0.2.0");
+        apply_and_assert!(version, Major, "1.0.0");
+        apply_and_assert!(version, Premajor, "2.0.0-alpha.0");
+        apply_and_assert!(version, Premajor, "2.0.0-alpha.1");
+        apply_and_assert!(version, Major, "2.0.0");
+    }
+
+    #[test]
+    fn test_non_alpha_prerelease_treated_as_no_prerelease() {
+        let version = Version::parse("1.2.3-beta.1").unwrap();
+        let result = VersionBump::Preminor
+            .apply(version, &BumpOptions::default())
+            .unwrap();
+        assert_eq!(result.to_string(), "1.3.0-alpha.0");
+    }
+
+    #[test]
+    fn test_dev_prerelease() {
+        let bump = |bump: VersionBump| {
+            bump.apply(
+                Version::parse("1.3.1-dev.0").unwrap(),
+                &BumpOptions::default(),
+            )
+            .unwrap()
+            .to_string()
+        };
+        assert_eq!(bump(VersionBump::Patch), "1.3.1");
+        assert_eq!(bump(VersionBump::Prepatch), "1.3.1-alpha.0");
+        assert_eq!(bump(VersionBump::Minor), "1.4.0");
+        assert_eq!(bump(VersionBump::Preminor), "1.4.0-alpha.0");
+    }
+
+    #[test]
+    fn test_preserve_prerelease() {
+        macro_rules! assert_preserved {
+            ($current:literal, $bump:ident, $expected:literal) => {
+                let options = BumpOptions {
+                    preserve_prerelease: true,
+                    ..BumpOptions::default()
+                };
+                let version = Version::parse($current).unwrap();
+                let result = VersionBump::$bump.apply(version, &options).unwrap();
+                assert_eq!(result.to_string(), $expected);
+            };
+        }
+
+        assert_preserved!("1.2.3-pre", Prepatch, "1.2.3-pre.0");
+        assert_preserved!("1.2.3-alpha.1.2+meta", Prepatch, "1.2.3-alpha.1.3");
+        assert_preserved!("1.3.0-rc.1", Preminor, "1.3.0-rc.2");
+        assert_preserved!("1.2.3-pre", Patch, "1.2.3");
+        assert_preserved!("1.2.3", Prepatch, "1.2.4-alpha.0");
+    }
+
+    #[test]
+    fn test_past_tagged() {
+        let v = |version: &str| Version::parse(version).unwrap();
+        let tagged = [
+            v("1.3.0-alpha.0"),
+            v("1.3.0-alpha.2"),
+            v("1.3.0-rc.5"),
+            v("1.2.0-alpha.9"),
+        ];
+        assert_eq!(
+            past_tagged(v("1.3.0-alpha.0"), &tagged).unwrap(),
+            v("1.3.0-alpha.3")
+        );
+        assert_eq!(
+            past_tagged(v("1.3.0-alpha.4"), &tagged).unwrap(),
+            v("1.3.0-alpha.4")
+        );
+        assert_eq!(
+            past_tagged(v("1.3.0-beta.0"), &tagged).unwrap(),
+            v("1.3.0-beta.0")
+        );
+        assert_eq!(past_tagged(v("1.3.0"), &tagged).unwrap(), v("1.3.0"));
+    }
+
+    #[test]
+    fn test_pre_counter() {
+        let options = BumpOptions {
+            pre_counter: Some(7),
+            ..BumpOptions::default()
+        };
+        let bump = |version: &str, bump: VersionBump| {
+            bump.apply(Version::parse(version).unwrap(), &options)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(bump("1.2.3", VersionBump::Prepatch), "1.2.4-alpha.7");
+        assert_eq!(
+            bump("1.2.4-alpha.3", VersionBump::Prepatch),
+            "1.2.4-alpha.7"
+        );
+        assert_eq!(bump("1.2.4-alpha.3", VersionBump::Patch), "1.2.4");
+    }
+
+    #[test]
+    fn test_pre_id() {
+        let bump = |version: &str, bump: VersionBump, pre_id: &str| {
+            let options = BumpOptions {
+                pre_id: pre_id.to_string(),
+                ..BumpOptions::default()
+            };
+            bump.apply(Version::parse(version).unwrap(), &options)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(bump("1.2.3", VersionBump::Prepatch, "beta"), "1.2.4-beta.0");
+        assert_eq!(bump("1.2.3", VersionBump::Preminor, "rc"), "1.3.0-rc.0");
+        assert_eq!(
+            bump("1.2.4-beta.0", VersionBump::Prepatch, "beta"),
+            "1.2.4-beta.1"
+        );
+        assert_eq!(
+            bump("1.3.0-rc.4", VersionBump::Preminor, "rc"),
+            "1.3.0-rc.5"
+        );
+        assert_eq!(bump("1.3.0-rc.4", VersionBump::Minor, "rc"), "1.3.0");
+        // Another identifier's counter isn't carried over
+        assert_eq!(
+            bump("1.2.4-beta.3", VersionBump::Prepatch, "rc"),
+            "1.2.5-rc.0"
+        );
+        assert_eq!(
+            extract_pre_version(&Prerelease::new("beta.3").unwrap(), "beta"),
+            Some(3)
+        );
+        assert_eq!(
+            extract_pre_version(&Prerelease::new("beta.3").unwrap(), "rc"),
+            None
+        );
+
+        for pre_id in ["rc!", ""] {
+            let options = BumpOptions {
+                pre_id: pre_id.to_string(),
+                ..BumpOptions::default()
+            };
+            assert!(
+                VersionBump::Prepatch
+                    .apply(Version::new(1, 2, 3), &options)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_pre_stamp() {
+        let bump = |version: &str, bump: VersionBump, stamp: &str| {
+            let options = BumpOptions {
+                pre_id: "nightly".to_string(),
+                pre_stamp: Some(stamp.to_string()),
+                ..BumpOptions::default()
+            };
+            bump.apply(Version::parse(version).unwrap(), &options)
+                .map(|version| version.to_string())
+        };
+        let nightly = |version, stamp| bump(version, VersionBump::Preminor, stamp).unwrap();
+
+        assert_eq!(nightly("1.2.3", "20240601"), "1.3.0-nightly.20240601");
+        assert_eq!(
+            nightly("1.3.0-nightly.20240601", "20240601"),
+            "1.3.0-nightly.20240601.1"
+        );
+        assert_eq!(
+            nightly("1.3.0-nightly.20240601.1", "20240601"),
+            "1.3.0-nightly.20240601.2"
+        );
+        assert_eq!(
+            nightly("1.3.0-nightly.20240601.2", "20240602"),
+            "1.3.0-nightly.20240602"
+        );
+        assert!(
+            Version::parse("1.3.0-nightly.20240601.2").unwrap()
+                < Version::parse("1.3.0-nightly.20240602").unwrap()
+        );
+        assert_eq!(
+            bump("1.3.0-nightly.20240601", VersionBump::Minor, "20240601").unwrap(),
+            "1.3.0"
+        );
+        assert!(bump("1.2.3", VersionBump::Prepatch, "0601").is_err());
+    }
+
+    #[test]
+    fn test_build_policy() {
+        macro_rules! assert_build {
+            ($policy:ident, $build:expr, $expected:literal) => {
+                let options = BumpOptions {
+                    build_policy: BuildPolicy::$policy,
+                    build: $build.map(str::to_string),
+                    ..BumpOptions::default()
+                };
+                let version = Version::parse("1.2.3+ci.41.linux").unwrap();
+                let result = VersionBump::Patch.apply(version, &options).unwrap();
+                assert_eq!(result.to_string(), $expected);
+            };
+        }
+
+        assert_build!(Clear, Some("ci.42"), "1.2.4");
+        assert_build!(Keep, None::<&str>, "1.2.4+ci.41.linux");
+        assert_build!(Replace, Some("ci.42"), "1.2.4+ci.42");
+        assert_build!(Append, Some("ci.42.linux"), "1.2.4+ci.41.linux.42");
+        assert_build!(Append, None::<&str>, "1.2.4+ci.41.linux");
+
+        let options = BumpOptions {
+            build_policy: BuildPolicy::Replace,
+            build: Some("not valid!".to_string()),
+            ..BumpOptions::default()
+        };
+        assert!(
+            VersionBump::Patch
+                .apply(Version::new(1, 2, 3), &options)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_overflow_is_an_error() {
+        let version = Version::new(u64::MAX, 0, 0);
+        assert!(
+            VersionBump::Major
+                .apply(version, &BumpOptions::default())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_increment_by() {
+        let options = BumpOptions {
+            increment_by: 2,
+            ..BumpOptions::default()
+        };
+        let bumped = |bump: VersionBump, version: &str| {
+            bump.apply(Version::parse(version).unwrap(), &options)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(bumped(VersionBump::Minor, "1.2.3"), "1.4.0");
+        assert_eq!(bumped(VersionBump::Major, "1.2.3"), "3.0.0");
+        assert_eq!(bumped(VersionBump::Prepatch, "1.2.3"), "1.2.5-alpha.0");
+        assert_eq!(
+            bumped(VersionBump::Prepatch, "1.2.5-alpha.0"),
+            "1.2.5-alpha.1"
+        );
+        assert!(
+            VersionBump::Patch
+                .apply(Version::new(1, 2, u64::MAX - 1), &options)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_patch_only() {
+        let version = Version::parse("0.0.3").unwrap();
+        let options = BumpOptions::default();
+        let bumped = |bump: VersionBump| {
+            bump.patch_only()
+                .apply(version.clone(), &options)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(bumped(VersionBump::Major), "0.0.4");
+        assert_eq!(bumped(VersionBump::Minor), "0.0.4");
+        assert_eq!(bumped(VersionBump::Preminor), "0.0.4-alpha.0");
+        assert_eq!(bumped(VersionBump::Premajor), "0.0.4-alpha.0");
+        assert_eq!(bumped(VersionBump::Patch), "0.0.4");
+    }
+
+    #[test]
+    fn test_between() {
+        let v = |s| Version::parse(s).unwrap();
+        let cases = [
+            ("1.2.3", "2.0.0", VersionBump::Major),
+            ("1.2.3", "2.0.0-alpha.0", VersionBump::Premajor),
+            ("1.2.3", "1.4.0", VersionBump::Minor),
+            ("1.2.3", "1.3.0-rc.1", VersionBump::Preminor),
+            ("1.2.3", "1.2.9", VersionBump::Patch),
+            ("1.2.3-rc.1", "1.2.3-rc.2", VersionBump::Prepatch),
+        ];
+        for (old, new, expected) in cases {
+            assert_eq!(
+                VersionBump::between(&v(old), &v(new)),
+                expected,
+                "{old} → {new}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_pre_version() {
+        let pre1 = semver::Prerelease::new("alpha.0").unwrap();
+        let pre2 = semver::Prerelease::new("alpha.42").unwrap();
+        let pre3 = semver::Prerelease::new("beta.1").unwrap();
+        let pre4 = semver::Prerelease::new("alpha").unwrap();
+
+        assert_eq!(extract_pre_version(&pre1, "alpha"), Some(0));
+        assert_eq!(extract_pre_version(&pre2, "alpha"), Some(42));
+        assert_eq!(extract_pre_version(&pre3, "alpha"), None);
+        assert_eq!(extract_pre_version(&pre4, "alpha"), None);
+        assert_eq!(extract_pre_version(&pre3, "beta"), Some(1));
+    }
+}
@@ -0,0 +1,3037 @@
+//! The `version` command line: argument parsing and every subcommand, on top of the bump
+//! core at the crate root
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use cargo_manifest::Manifest;
+use clap::builder::{PossibleValue, TypedValueParser};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{Parser, Subcommand, ValueEnum};
+use colored::{Color, Colorize};
+use config::Config;
+use i18n::Message;
+use output::Reason;
+use progress::Progress;
+use semver::Version;
+
+use crate::bump::{self, BuildPolicy, BumpOptions, VersionBump};
+
+mod adopt;
+mod badge;
+mod bitbucket;
+mod changelog;
+mod check;
+mod clock;
+mod compare;
+mod config;
+mod crate_file;
+mod dist;
+mod env;
+mod explain;
+mod files;
+mod forge;
+mod freeze;
+mod git;
+mod gitea;
+mod github;
+mod go;
+mod graduate;
+mod hooks;
+mod http;
+mod i18n;
+mod infer;
+mod lockfile;
+mod log;
+mod manifest;
+mod migrate;
+mod notes;
+mod output;
+mod packaging;
+mod partial;
+mod plugin;
+mod progress;
+mod promote;
+mod provenance;
+mod reconcile;
+mod registry;
+mod release;
+mod rename;
+mod report;
+mod roadmap;
+mod scan;
+mod schema;
+mod serve;
+mod set;
+mod snapshot;
+mod status;
+mod submodule;
+mod sync;
+mod tier;
+mod upgrade;
+mod upstream;
+mod verify;
+mod versioning;
+mod workspace;
+
+/// Parses a [`VersionBump`] by name or alias, in any case, suggesting the nearest one
+/// (aliases included, which clap's own suggestions skip) on a typo
+#[derive(Clone)]
+pub struct BumpParser;
+
+impl BumpParser {
+    /// The name or alias `value` is most likely a typo of
+    fn suggestion(value: &str) -> Option<String> {
+        let value = value.to_ascii_lowercase();
+        VersionBump::value_variants()
+            .iter()
+            .filter_map(ValueEnum::to_possible_value)
+            .flat_map(|possible| {
+                possible
+                    .get_name_and_aliases()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .map(|name| (explain::edit_distance(&value, &name), name))
+            .filter(|(distance, name)| *distance <= (name.len() / 3).max(1))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, name)| name)
+    }
+}
+
+impl TypedValueParser for BumpParser {
+    type Value = VersionBump;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<VersionBump, clap::Error> {
+        let value = value.to_string_lossy();
+        if let Ok(bump) = VersionBump::from_str(&value, true) {
+            return Ok(bump);
+        }
+        let mut error = clap::Error::new(ErrorKind::InvalidValue).with_cmd(cmd);
+        if let Some(arg) = arg {
+            error.insert(
+                ContextKind::InvalidArg,
+                ContextValue::String(arg.to_string()),
+            );
+        }
+        error.insert(
+            ContextKind::InvalidValue,
+            ContextValue::String(value.to_string()),
+        );
+        error.insert(
+            ContextKind::ValidValue,
+            ContextValue::Strings(
+                self.possible_values()
+                    .into_iter()
+                    .flatten()
+                    .map(|possible| possible.get_name().to_string())
+                    .collect(),
+            ),
+        );
+        if let Some(suggestion) = Self::suggestion(&value) {
+            error.insert(
+                ContextKind::SuggestedValue,
+                ContextValue::String(suggestion),
+            );
+        }
+        Err(error)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            VersionBump::value_variants()
+                .iter()
+                .filter_map(ValueEnum::to_possible_value),
+        ))
+    }
+}
+
+#[derive(Parser)]
+#[allow(clippy::struct_excessive_bools)]
+#[clap(version, name = "Workspace Version Upgrade Utility")]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// If no subcommand is provided, treat the first argument as a version bump
+    #[arg(
+        value_parser = BumpParser,
+        help = "If not provided, configured to read from git, will attempt to infer the bump from the git commit message, else `prepatch`. `breaking`, `feature`, `fix`, and `pre` stand for major, minor, patch, and prepatch"
+    )]
+    pub version_bump: Option<VersionBump>,
+
+    // Git-based version inference flag
+    #[arg(long, help = "Infer version bump from git commit messages")]
+    pub from_git: bool,
+
+    // Expect a workspace instead of a regular project
+    #[arg(
+        long,
+        global = true,
+        help = "Expect to find a workspace rather than a normal project"
+    )]
+    pub workspace: bool,
+
+    #[arg(
+        long,
+        requires = "from_git",
+        help = "When --from-git infers nothing and falls back to `prepatch`, or infers `skip`, say what was scanned and why nothing else matched"
+    )]
+    pub explain_skip: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Commit message file, or a directory of them (e.g. exported PR descriptions); repeat for more, and the most significant marker across them all wins"
+    )]
+    pub message_file: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Pull request description to read a `release: <bump>` front-matter or fenced directive from (with --from-git)"
+    )]
+    pub pr_description: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Branch being merged, matched against `branch_bumps` (feature/* minor, fix/* patch, breaking/* major by default) when there's no marker (with --from-git)"
+    )]
+    pub branch: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Path to manifest file",
+        default_value = "Cargo.toml"
+    )]
+    pub path: PathBuf,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = manifest::Format::Cargo,
+        conflicts_with = "workspace",
+        help = "What --path holds: a Cargo manifest, just a version string (e.g. a VERSION file), or a TOML file with the version under --version-key"
+    )]
+    pub format: manifest::Format,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "KEY",
+        default_value = "version",
+        help = "Dotted key of the version with --format toml, e.g. `release.version` for `version` under `[release]`"
+    )]
+    pub version_key: String,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        help = "Language for human-readable output (default: from LC_ALL, LC_MESSAGES, or LANG)"
+    )]
+    pub lang: Option<i18n::Lang>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Path to config file [default: .version.toml beside the manifest, if present]"
+    )]
+    pub config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Rewrite a config written for an older config_version in the current schema, keeping its comments"
+    )]
+    pub write_migrated: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Apply a named profile from the config"
+    )]
+    pub profile: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "REGEX",
+        help = "Regex whose first capture group is the exact version to rewrite; must match once"
+    )]
+    pub anchor: Option<regex::Regex>,
+
+    #[arg(
+        short,
+        long,
+        global = true,
+        value_name = "NAME",
+        conflicts_with = "workspace",
+        help = "Package to use when the manifest declares several [[package]] tables"
+    )]
+    pub package: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Allow a manifest in a build directory, a vendored dependency, or a git submodule"
+    )]
+    pub force_path: bool,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        requires = "workspace",
+        conflicts_with = "to",
+        help = "Only bump the workspace members whose directories match, each from its own version (repeatable)"
+    )]
+    pub paths: Vec<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "DATE",
+        visible_alias = "anchor-date",
+        env = "SOURCE_DATE_EPOCH",
+        value_parser = clock::parse_date,
+        help = "Take \"now\" from this date (unix seconds or YYYY-MM-DD) for changelog and other rendered dates, for reproducible runs; freezes, release intervals, and rc soaks still go by the system clock"
+    )]
+    pub date: Option<u64>,
+
+    #[arg(long, global = true, help = "Suppress all output except errors")]
+    pub quiet: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Print the result as JSON (implies --quiet)"
+    )]
+    pub json: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "VERSION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "v1",
+        conflicts_with = "json",
+        help = "Print the bump result as tab-separated lines in a format that never changes within a version, e.g. --porcelain=v1 (implies --quiet)"
+    )]
+    pub porcelain: Option<output::Porcelain>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "porcelain", "paths"],
+        help = "Print just the new version on stdout (the rest of the output is on stderr either way)"
+    )]
+    pub version_only_stdout: bool,
+
+    #[arg(
+        long,
+        help = "Append released/version/previous_version/bump to the GitHub Actions step outputs in $GITHUB_OUTPUT"
+    )]
+    pub github_output: bool,
+
+    #[arg(
+        long,
+        help = "When the bump is skipped, still run the `check` rules and fail if any are broken"
+    )]
+    pub check_on_skip: bool,
+
+    #[arg(
+        long,
+        requires = "github_token",
+        help = "When the bump is skipped, set a `version` commit status on HEAD saying so, to tell a skipped release from one that never ran"
+    )]
+    pub skip_status: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "TAG",
+        help = "Previous release tag to collect commits from, even one on another branch [default: the nearest tag reachable from HEAD]"
+    )]
+    pub since: Option<String>,
+
+    #[arg(long, help = "Prepend the commits since the last tag to the changelog")]
+    pub changelog: bool,
+
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        help = "Changelog path relative to the manifest, `{name}` and `{dir}` expand per member [default: CHANGELOG.md, or {dir}/CHANGELOG.md with --per-crate-changelog]"
+    )]
+    pub changelog_path: Option<String>,
+
+    #[arg(
+        long,
+        requires_all = ["changelog", "workspace"],
+        help = "Write one changelog per workspace member, scoped to commits under its directory"
+    )]
+    pub per_crate_changelog: bool,
+
+    #[arg(
+        long,
+        env = "GITHUB_TOKEN",
+        hide_env_values = true,
+        visible_alias = "forge-token",
+        help = "Token for GitHub, Gitea, or Bitbucket, used to look up the pull request description and labels when the commit message has no marker"
+    )]
+    pub github_token: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "OWNER/REPO",
+        env = "GITHUB_REPOSITORY",
+        visible_alias = "forge-repo",
+        help = "Repository to look up pull requests in, on the config's `forge` or GitHub [default: parsed from the origin remote]"
+    )]
+    pub github_repo: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write a cargo-dist `dist-manifest.json` snippet announcing the new version"
+    )]
+    pub dist_manifest: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "ID",
+        help = "Identifier for new prereleases, e.g. `beta` for 1.2.4-beta.0 [default: pre_id from the config, else alpha]"
+    )]
+    pub pre_id: Option<String>,
+
+    #[arg(
+        long,
+        help = "Keep prereleases that aren't `<pre-id>.N` (e.g. `rc.1`, `pre`) and bump their trailing counter in place"
+    )]
+    pub preserve_prerelease: bool,
+
+    #[arg(
+        long,
+        help = "Number prereleases by the commits since the last stable tag (like `git describe`) instead of incrementing [default: pre_distance from the config]"
+    )]
+    pub pre_distance: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["pre_distance", "pre_date"],
+        help = "Number prereleases past every tag of the same prerelease on any branch, here or on origin, so two branches cutting 1.3.0 prereleases don't both mint `alpha.0` [default: pre_lineage from the config]"
+    )]
+    pub pre_lineage: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "pre_distance",
+        help = "Number prereleases by today's date (`1.3.0-nightly.20240601`, then `.1`, `.2` for more the same day) in the config's `pre_date` format [default: %Y%m%d]"
+    )]
+    pub pre_date: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "LEVEL",
+        help = "Check the registry for the current version, and warn or error if it was never published or was yanked"
+    )]
+    pub registry_check: Option<CheckLevel>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        default_value = registry::CRATES_IO_INDEX,
+        help = "Sparse index used by --registry-check and verify-package"
+    )]
+    pub registry_index: String,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        default_value_t = 3,
+        help = "Retry a registry, GitHub, or download request up to N times after a connection error, timeout, 429, or 5xx, honoring Retry-After"
+    )]
+    pub http_retries: u32,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        default_value_t = 30,
+        help = "Give up on each attempt at a network request after SECS seconds"
+    )]
+    pub http_timeout: u64,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "POLICY",
+        default_value_t = BuildPolicy::Clear,
+        help = "What to do with existing `+build` metadata"
+    )]
+    pub build_metadata: BuildPolicy,
+
+    #[arg(
+        long,
+        conflicts_with = "build_metadata",
+        help = "Keep existing `+build` metadata (same as --build-metadata keep)"
+    )]
+    pub no_build_metadata_clear: bool,
+
+    #[arg(
+        long,
+        value_name = "IDS",
+        help = "Dot-separated build identifiers for --build-metadata replace/append, e.g. ci.1234"
+    )]
+    pub build: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "VERSION",
+        conflicts_with_all = ["version_bump", "from_git"],
+        help = "Bump straight to this version, checking it's newer, untagged, and (with --registry-check) unpublished"
+    )]
+    pub to: Option<Version>,
+
+    #[arg(
+        long,
+        value_parser = BumpParser,
+        value_name = "BUMP",
+        help = "Fail unless the requested or inferred bump is this one, e.g. to check a PR's label against its commits"
+    )]
+    pub assert_bump: Option<VersionBump>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "to",
+        help = "Add N to the bumped major/minor/patch component instead of 1"
+    )]
+    pub increment_by: u64,
+
+    #[arg(
+        long,
+        help = "Refuse a stable release unless the current version is an rc of it, tagged at least `rc_soak_days` ago"
+    )]
+    pub require_rc: bool,
+
+    #[arg(
+        long,
+        help = "After rewriting the manifests, run `cargo check --workspace` (or the config's `verify_build`) and undo the bump if it fails"
+    )]
+    pub verify_build: bool,
+
+    #[arg(
+        long,
+        env = "VERSION_RELEASE_APPROVED",
+        value_parser = clap::builder::BoolishValueParser::new(),
+        help = "Sign off on a stable release, required when the config sets `require_approval`"
+    )]
+    pub approve: bool,
+
+    #[arg(
+        long,
+        help = "Release even if the last release tag is newer than the config's `min_release_interval`"
+    )]
+    pub ignore_release_interval: bool,
+
+    #[arg(
+        long,
+        help = "Bump even if the new version moves further than the config's `max_jump` allows"
+    )]
+    pub allow_large_jump: bool,
+
+    #[arg(
+        long,
+        value_name = "REASON",
+        help = "Release during one of the config's `freeze` windows, recording why in the release commit"
+    )]
+    pub override_freeze: Option<String>,
+
+    #[arg(
+        long,
+        help = "After the release, update the Homebrew formulas / PKGBUILDs listed under [[packaging]]"
+    )]
+    pub packaging: bool,
+
+    #[arg(long, help = "Commit the bumped files")]
+    pub commit: bool,
+
+    #[arg(
+        long,
+        requires = "commit",
+        help = "Attach the --json release result to the release commit as a git note (refs/notes/version)"
+    )]
+    pub notes: bool,
+
+    #[arg(
+        long,
+        requires = "commit",
+        help = "Tag the release commit using the tag format"
+    )]
+    pub tag: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "tag",
+        help = "A release artifact to list with its sha256 in the tag message (repeatable)"
+    )]
+    pub artifact: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        requires = "tag",
+        help = "Start a new product line: tag the release with the next `{epoch}` after the latest tag's"
+    )]
+    pub epoch_bump: bool,
+
+    #[arg(
+        long,
+        requires = "commit",
+        help = "Push the release commit and tags to origin"
+    )]
+    pub push: bool,
+
+    #[arg(
+        long,
+        requires = "tag",
+        requires = "push",
+        requires = "github_token",
+        help = "Once pushed, create a release on GitHub or Gitea for each tag, with the tag message as its notes"
+    )]
+    pub forge_release: bool,
+
+    #[arg(
+        long,
+        value_name = "BRANCH",
+        requires = "push",
+        requires = "github_token",
+        help = "Once pushed, open a pull request from the current branch into BRANCH"
+    )]
+    pub open_pr: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "LABEL",
+        requires = "open_pr",
+        help = "Label the pull request --open-pr opens, where the forge has labels (repeatable)"
+    )]
+    pub pr_label: Vec<String>,
+
+    #[arg(
+        long,
+        requires = "commit",
+        help = "After a stable release, move the manifest on to the next patch's `-dev.0` prerelease in a second commit [default: next_dev from the config]"
+    )]
+    pub next_dev: bool,
+
+    #[arg(
+        long,
+        help = "On a stable bump, record the commit released from and the CI run URL under [package.metadata.release] [default: release_metadata from the config]"
+    )]
+    pub release_metadata: bool,
+}
+
+impl Args {
+    fn is_quiet(&self) -> bool {
+        self.quiet || self.json || self.porcelain.is_some()
+    }
+
+    /// The clock date-dependent features read: `--date`/`SOURCE_DATE_EPOCH`, else the system's
+    fn clock(&self) -> clock::Clock {
+        clock::Clock::new(self.date)
+    }
+
+    fn bump_options(&self, config: &Config) -> anyhow::Result<BumpOptions> {
+        let pre_stamp = match &config.pre_date {
+            Some(format) => Some(format.as_str()),
+            None if self.pre_date => Some("%Y%m%d"),
+            None => None,
+        }
+        .map(|format| self.clock().format(format));
+        if pre_stamp.is_some() && (self.pre_distance || config.pre_distance) {
+            anyhow::bail!("Prereleases can be numbered by date (pre_date) or distance, not both");
+        }
+        if (self.pre_lineage || config.pre_lineage)
+            && (pre_stamp.is_some() || self.pre_distance || config.pre_distance)
+        {
+            anyhow::bail!(
+                "pre_lineage numbers prereleases past their tags, so it can't be combined with pre_date or pre_distance"
+            );
+        }
+        Ok(BumpOptions {
+            pre_id: match &self.pre_id {
+                Some(pre_id) => config::valid_pre_id(pre_id)?,
+                None => config.pre_id()?,
+            }
+            .to_string(),
+            preserve_prerelease: self.preserve_prerelease,
+            build_policy: if self.no_build_metadata_clear {
+                BuildPolicy::Keep
+            } else {
+                self.build_metadata
+            },
+            build: self.build.clone(),
+            increment_by: self.increment_by,
+            pre_counter: if self.pre_distance || config.pre_distance {
+                Some(distance_from_stable(manifest_dir(&self.path), config)?)
+            } else {
+                None
+            },
+            pre_stamp,
+        })
+    }
+
+    /// Turn on whatever the profile enables (flags given on the command line still apply)
+    fn apply_profile(&mut self, profile: &config::Profile) {
+        self.changelog |= profile.changelog.unwrap_or(false);
+        self.commit |= profile.commit.unwrap_or(false);
+        self.tag |= profile.tag.unwrap_or(false);
+        self.push |= profile.push.unwrap_or(false);
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check that HEAD is exactly the release tag, the tag matches the manifest
+    /// version, and the tag is reachable from the default branch
+    VerifyTag {
+        #[arg(help = "Tag being released, e.g. v1.2.3")]
+        tag: String,
+
+        #[arg(
+            long,
+            value_name = "BRANCH",
+            help = "Branch the tag must be reachable from [default: origin/HEAD, else main]"
+        )]
+        default_branch: Option<String>,
+    },
+
+    /// Check the published package against the release: its manifest version, that
+    /// `Cargo.toml.orig` is the tagged manifest, that it was packaged from the tagged commit,
+    /// and that its changelog has the release's section
+    VerifyPackage {
+        #[arg(help = "Version that was published [default: the manifest version]")]
+        version: Option<Version>,
+
+        #[arg(
+            long = "crate",
+            value_name = "FILE",
+            help = "Check this .crate file (e.g. from `cargo package`) instead of downloading it from the --registry-index registry"
+        )]
+        crate_file: Option<PathBuf>,
+    },
+
+    /// Release a 0.x crate as 1.0.0: the major bump with a changelog section opening with
+    /// the config's `graduation_note`, and (with --workspace) every member's requirement on
+    /// it moved to 1.0
+    Graduate,
+
+    /// Move a prerelease to the next channel (alpha → beta → rc → the release, or the
+    /// config's `channels`) without changing its major, minor, or patch: 1.3.0-alpha.4 →
+    /// 1.3.0-beta.0
+    Promote {
+        #[arg(
+            value_name = "CHANNEL",
+            help = "The channel to move to, or `release` for the stable version [default: the next channel]"
+        )]
+        channel: Option<String>,
+    },
+
+    /// Write this version into the manifest(s), sync targets, and lockfile, e.g. one computed
+    /// by another CI step, without the release checks; it may be lower than the current version
+    Set {
+        #[arg(value_name = "VERSION")]
+        version: Version,
+    },
+
+    /// List every version found in release tags (sorted by semver) with its date and bump
+    Log,
+
+    /// Print the release notes for a version (the commits since the release before it) as
+    /// Markdown, HTML, Slack mrkdwn, or plain text
+    Notes {
+        #[arg(help = "Version to describe [default: the manifest version]")]
+        version: Option<Version>,
+
+        #[arg(
+            long = "as",
+            value_name = "STYLE",
+            value_enum,
+            default_value_t = notes::Style::Markdown,
+            help = "What to render the notes as"
+        )]
+        style: notes::Style,
+    },
+
+    /// Onboard a project with no version yet: add one to every manifest lacking it, seed
+    /// the changelog, and commit and tag the result
+    Adopt {
+        #[arg(
+            long,
+            value_name = "VERSION",
+            default_value = "0.1.0",
+            help = "Version to start from"
+        )]
+        initial: Version,
+    },
+
+    /// Compare the manifest version with the release tags and the registry, explain any
+    /// drift, and propose (or apply) the fix: a retag, a manifest reset, or a catch-up release
+    Reconcile {
+        #[arg(
+            long,
+            help = "Compare with the tags only, without looking up the registry"
+        )]
+        offline: bool,
+
+        #[arg(long, help = "Commit and tag the proposed fix (nothing is pushed)")]
+        apply: bool,
+    },
+
+    /// Validate the config: unknown keys (with the likely intended one), values that don't
+    /// parse, and settings that conflict
+    ExplainConfig {
+        #[arg(long, help = "Fail on warnings too, not just errors")]
+        strict: bool,
+    },
+
+    /// Enforce the policy rules enabled in the config (e.g. `stable_default_branch`)
+    Check {
+        #[arg(
+            long,
+            value_name = "BRANCH",
+            help = "Branch being checked, for detached CI checkouts [default: the current branch]"
+        )]
+        branch: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "BRANCH",
+            help = "The default branch [default: origin/HEAD, else main]"
+        )]
+        default_branch: Option<String>,
+    },
+
+    /// Show the current version, the last release tag, commits since, and the pending bump
+    Status {
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Warn if the last release tag is older than this [default: stale_after from the config]"
+        )]
+        stale_after: Option<u64>,
+    },
+
+    /// Write a self-contained HTML page of the pending release (per-crate bumps,
+    /// changelog, contributors, and timing), e.g. as a CI artifact for release reviews
+    Report {
+        #[arg(long, value_name = "DIR", help = "Directory to write index.html to")]
+        html: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Show the crates of a saved `--json` result (e.g. from the release job) instead of the pending bumps"
+        )]
+        result: Option<PathBuf>,
+    },
+
+    /// Print the versions a sequence of hypothetical bumps would lead to, without writing
+    /// anything
+    Roadmap {
+        #[arg(
+            long,
+            value_parser = BumpParser,
+            value_delimiter = ',',
+            required = true,
+            help = "Bumps to apply in order, e.g. minor,patch,major"
+        )]
+        steps: Vec<VersionBump>,
+    },
+
+    /// Print `export VERSION=… VERSION_MAJOR=…` lines (also MINOR, PATCH, and PRE) for
+    /// `eval "$(version env)"` in shell scripts and Makefiles
+    Env,
+
+    /// Print a unique, sortable version for HEAD (e.g. `1.3.0-snapshot.4+gabc1234`) for
+    /// per-commit artifacts, leaving the manifest alone unless asked
+    Snapshot {
+        #[arg(long, default_value = "snapshot", help = "Prerelease identifier")]
+        id: String,
+
+        #[arg(
+            long,
+            help = "Number by the HEAD commit's timestamp instead of the commits since the last tag"
+        )]
+        timestamp: bool,
+
+        #[arg(
+            long,
+            value_name = "VERSION",
+            help = "Version to build on, e.g. 0.0.0 for 0.0.0-canary.<timestamp> [default: the next release]"
+        )]
+        base: Option<Version>,
+
+        #[arg(long, help = "Write the snapshot version to the manifest")]
+        write: bool,
+    },
+
+    /// List every package manifest under a directory (for repos that aren't
+    /// cargo workspaces), optionally applying the same bump to all of them
+    Scan {
+        #[arg(default_value = ".", help = "Directory to search")]
+        dir: PathBuf,
+
+        #[arg(
+            long,
+            value_parser = BumpParser,
+            help = "Bump to apply to every package found"
+        )]
+        bump: Option<VersionBump>,
+    },
+
+    /// Compare two versions by semver precedence, exiting 0 if A is less than B, 1 if they're
+    /// equal, 2 if A is greater, or 3 if either isn't a version
+    Compare {
+        #[arg(help = "Version to compare, e.g. 1.2.3 or v1.2.3-rc.1")]
+        a: String,
+
+        #[arg(help = "Version to compare it with")]
+        b: String,
+
+        #[arg(long, value_enum, help = "Also print the relation of A to B")]
+        print: Option<compare::Print>,
+    },
+
+    /// Print the JSON Schema of a `--json` output (all of them, keyed by name, by default)
+    Schema {
+        #[arg(value_enum, help = "Output to describe")]
+        document: Option<schema::Document>,
+    },
+
+    /// Convert a workspace between fixed and independent versioning by rewriting the
+    /// member manifests, and record the mode in the config
+    MigrateMode {
+        #[arg(value_enum, help = "Versioning to convert to")]
+        mode: versioning::Versioning,
+    },
+
+    /// Translate another release tool's settings into a config file
+    Migrate {
+        #[arg(long, value_enum, help = "Tool to read settings from")]
+        from: migrate::Source,
+
+        #[arg(long, help = "Overwrite an existing config file")]
+        force: bool,
+    },
+
+    /// Answer `POST /bump` requests over HTTP from one long-running process, returning the
+    /// `--json` result
+    Serve {
+        #[arg(
+            long,
+            value_name = "ADDR",
+            default_value = "127.0.0.1:8080",
+            help = "Address to listen on"
+        )]
+        listen: String,
+
+        #[arg(
+            long,
+            env = "VERSION_SERVE_TOKEN",
+            hide_env_values = true,
+            help = "Require `Authorization: Bearer <TOKEN>` on every request (needed to listen beyond loopback)"
+        )]
+        token: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "DIR",
+            default_value = ".",
+            help = "Only bump manifests under DIR"
+        )]
+        root: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CheckLevel {
+    Warn,
+    Error,
+}
+
+impl VersionBump {
+    fn description(self) -> &'static str {
+        match self {
+            VersionBump::Major => i18n::tr(Message::MajorRelease),
+            VersionBump::Minor => i18n::tr(Message::MinorRelease),
+            VersionBump::Patch => i18n::tr(Message::PatchRelease),
+            VersionBump::Premajor => i18n::tr(Message::PremajorRelease),
+            VersionBump::Preminor => i18n::tr(Message::PreminorRelease),
+            VersionBump::Prepatch => i18n::tr(Message::PrepatchRelease),
+            VersionBump::Skip => i18n::tr(Message::SkipRelease),
+        }
+    }
+
+    fn emoji(self) -> &'static str {
+        match self {
+            VersionBump::Major => "🚀",
+            VersionBump::Minor => "✨",
+            VersionBump::Patch => "🔧",
+            VersionBump::Premajor => "⚗️",
+            VersionBump::Preminor => "🧪",
+            VersionBump::Prepatch => "🔬",
+            VersionBump::Skip => "⏭️",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            VersionBump::Major => Color::Red,
+            VersionBump::Minor => Color::Blue,
+            VersionBump::Patch => Color::Green,
+            VersionBump::Premajor | VersionBump::Preminor | VersionBump::Prepatch => Color::Yellow,
+            VersionBump::Skip => Color::White,
+        }
+    }
+}
+
+fn extract_version(args: &Args, manifest: &Manifest) -> anyhow::Result<Version> {
+    let version_field = if args.workspace {
+        manifest
+            .workspace
+            .as_ref()
+            .ok_or(anyhow::anyhow!("Expected to find a workspace"))?
+            .package
+            .as_ref()
+            .ok_or(anyhow::anyhow!(
+                "Expected to find a package section in the workspace"
+            ))?
+            .version
+            .as_ref()
+            .ok_or(anyhow::anyhow!(
+                "Expected to find a package version in the package section"
+            ))?
+            .clone()
+    } else {
+        manifest
+            .package
+            .as_ref()
+            .ok_or(anyhow::anyhow!(
+                "Expected to find a package section in the manifest"
+            ))?
+            .version
+            .as_ref()
+            .ok_or(anyhow::anyhow!(
+                "Expected to find a package version in the package section"
+            ))?
+            .clone()
+            .as_local()
+            .ok_or(anyhow::anyhow!(
+                "The package version is inherited from a workspace (use --workspace)"
+            ))?
+    };
+
+    Ok(semver::Version::parse(&version_field)?)
+}
+
+/// Parse the command line and run it
+pub fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    i18n::init(args.lang);
+    http::init(http::Settings {
+        retries: args.http_retries,
+        timeout: std::time::Duration::from_secs(args.http_timeout),
+    });
+
+    if let Some(Command::Serve {
+        listen,
+        token,
+        root,
+    }) = &args.command
+    {
+        return serve::serve(listen, token.as_deref(), root);
+    }
+    execute(args)
+}
+
+/// Everything a single invocation does, also run for each `version serve` request
+fn execute(mut args: Args) -> anyhow::Result<()> {
+    git::set_quiet(args.is_quiet());
+    if let Some(Command::Scan { dir, bump }) = &args.command {
+        return run_scan(&args, dir, *bump);
+    }
+    if let Some(Command::Compare { a, b, print }) = &args.command {
+        compare::compare(a, b, *print);
+    }
+    if let Some(Command::Schema { document }) = &args.command {
+        return schema::print(*document);
+    }
+
+    if !args.force_path
+        && let Some(reason) = manifest::unsafe_location(&args.path)
+    {
+        anyhow::bail!("Refusing to continue: {reason} (pass --force-path if that's intended)");
+    }
+    // Before loading, so a change made while the manifest is read is caught too
+    files::guard(&args.path);
+    let text = files::read(&args.path)?;
+    let manifest = parse_manifest(&args, &text.content)?;
+
+    if let Some(Command::Migrate { from, force }) = &args.command {
+        return run_migrate(&args, &manifest, *from, *force);
+    }
+
+    if args.write_migrated {
+        write_migrated(&args)?;
+    }
+
+    if let Some(Command::ExplainConfig { strict }) = &args.command {
+        return explain::explain_config(&args, &manifest, *strict);
+    }
+
+    explain::check_on_startup(&args)?;
+    let mut config = Config::load(args.config.as_deref(), manifest_dir(&args.path))?;
+    if let Some(name) = args.profile.clone() {
+        let profile = config.apply_profile(&name)?;
+        args.apply_profile(&profile);
+    }
+    if config.tag_format().contains("{epoch}") || args.epoch_bump {
+        let tags = git::merged_tags(manifest_dir(&args.path)).unwrap_or_default();
+        config.resolve_epoch(&tags, args.epoch_bump)?;
+    }
+    if (args.tag || args.push) && !args.commit {
+        anyhow::bail!("--tag and --push (or a profile enabling them) require --commit");
+    }
+    if let Some(missing) = args.artifact.iter().find(|path| !path.is_file()) {
+        anyhow::bail!("--artifact {} is not a file", missing.display());
+    }
+    if !args.artifact.is_empty()
+        && let Some(template) = &config.tag_message
+        && !template.contains("{artifacts}")
+    {
+        anyhow::bail!("--artifact needs `{{artifacts}}` in tag_message to list the checksums");
+    }
+    if let Some(Command::MigrateMode { mode }) = &args.command {
+        return versioning::migrate(&args, &manifest, &config, *mode);
+    }
+    if args.workspace
+        && let Some(versioning) = config.versioning
+        && args.command.is_none()
+    {
+        versioning::check(&release_members(&args, &manifest, &config)?, versioning)?;
+    }
+    if !args.paths.is_empty() && args.command.is_none() {
+        return partial::bump_paths(&args, &manifest, &config);
+    }
+    if let Some(Command::Adopt { initial }) = &args.command {
+        return adopt::adopt(&args, &manifest, &config, initial);
+    }
+    let version = extract_version(&args, &manifest)?;
+    if graduate::active(&args) {
+        graduate::prepare(&mut args, &version)?;
+    }
+    if promote::active(&args) {
+        promote::prepare(&mut args, &config, &version)?;
+    }
+    set::prepare(&mut args)?;
+
+    run(&args, &text, &manifest, &config, &version)
+}
+
+/// The manifest `--path` points at in `--format`, from the `content` read from it
+fn parse_manifest(args: &Args, content: &str) -> anyhow::Result<Manifest> {
+    match args.format {
+        manifest::Format::Cargo => {
+            manifest::parse_package(&args.path, content, args.package.as_deref())
+        }
+        manifest::Format::Raw => manifest::parse_raw(&args.path, content),
+        manifest::Format::Toml => manifest::parse_toml(&args.path, content, &args.version_key),
+    }
+}
+
+/// Run the subcommand (or the bump) once the manifest (as read and as parsed), config, and
+/// version are loaded
+fn run(
+    args: &Args,
+    text: &files::Text,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+) -> anyhow::Result<()> {
+    match &args.command {
+        Some(Command::VerifyTag {
+            tag,
+            default_branch,
+        }) => verify::verify_tag(args, config, version, tag, default_branch.as_deref()),
+        Some(Command::VerifyPackage {
+            version: published,
+            crate_file,
+        }) => verify::verify_package(
+            args,
+            manifest,
+            config,
+            published.as_ref().unwrap_or(version),
+            crate_file.as_deref(),
+        ),
+        Some(Command::Check {
+            branch,
+            default_branch,
+        }) => check::check(
+            args,
+            manifest,
+            config,
+            version,
+            branch.as_deref(),
+            default_branch.as_deref(),
+        ),
+        Some(Command::Log) => log::log(args, config),
+        Some(Command::Notes {
+            version: wanted,
+            style,
+        }) => notes::notes(args, manifest, config, version, wanted.as_ref(), *style),
+        Some(Command::Reconcile { offline, apply }) => {
+            reconcile::reconcile(args, text, manifest, config, version, *offline, *apply)
+        }
+        Some(Command::Status { stale_after }) => {
+            status::status(args, config, version, *stale_after)
+        }
+        Some(Command::Report { html, result }) => {
+            report::report(args, manifest, config, version, html, result.as_deref())
+        }
+        Some(Command::Roadmap { steps }) => {
+            // Every step is hypothetical, so none of them has a commit distance
+            let options = BumpOptions {
+                pre_counter: None,
+                ..args.bump_options(config)?
+            };
+            roadmap::roadmap(version, steps, &options, args.json)
+        }
+        Some(Command::Env) => {
+            print!("{}", env::render(version));
+            Ok(())
+        }
+        Some(Command::Snapshot {
+            id,
+            timestamp,
+            base,
+            write,
+        }) => snapshot::snapshot(args, config, version, id, *timestamp, base.as_ref(), *write),
+        Some(
+            Command::Migrate { .. }
+            | Command::MigrateMode { .. }
+            | Command::Scan { .. }
+            | Command::Schema { .. }
+            | Command::Compare { .. }
+            | Command::Serve { .. }
+            | Command::Adopt { .. }
+            | Command::ExplainConfig { .. },
+        ) => {
+            unreachable!("handled before reading the version")
+        }
+        Some(Command::Set { .. }) => set::write(args, text, manifest, config, version),
+        // `graduate` and `promote` are bumps to a set version, set up by their `prepare`
+        Some(Command::Graduate | Command::Promote { .. }) | None => {
+            bump(args, text, manifest, config, version)
+        }
+    }
+}
+
+fn bump(
+    args: &Args,
+    text: &files::Text,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+) -> anyhow::Result<()> {
+    let old_version = version.to_string();
+
+    if let Some(annotation) = check_frozen(args, text, manifest, config)? {
+        let names = crate_names(args, manifest, config)?.join(", ");
+        let line = format!(
+            "{} {}",
+            "❄".cyan().bold(),
+            i18n::format(
+                Message::Frozen,
+                &[("names", &names), ("annotation", annotation)]
+            )
+        );
+        return report_skip(
+            args,
+            manifest,
+            config,
+            version,
+            &line,
+            &Reason::Frozen { annotation },
+        );
+    }
+
+    let (version_bump, reason) = choose_bump(args, config, version);
+    let tiers = crate_tiers(args, manifest, config)?;
+    let version_bump = cap_for_tier(args, &tiers, version_bump, &reason);
+    check_assert_bump(args, version_bump, &reason)?;
+
+    if matches!(version_bump, VersionBump::Skip) {
+        let line = format!(
+            "{} {}",
+            version_bump.emoji(),
+            version_bump.description().color(version_bump.color())
+        );
+        return report_skip(args, manifest, config, version, &line, &reason);
+    }
+
+    if let Some(level) = args.registry_check {
+        check_registry(args, manifest, config, version, level)?;
+    }
+
+    let new_version = match &args.to {
+        Some(target) => {
+            check_target(args, manifest, config, version, target)?;
+            target.clone()
+        }
+        None => next_version(args, manifest, config, version, version_bump)?,
+    };
+
+    let names = crate_names(args, manifest, config)?;
+    let name = names.first().map_or("", String::as_str);
+    let freeze_override = check_release(args, config, name, version, &new_version)?;
+    let result = bump_result(
+        args,
+        manifest,
+        config,
+        version,
+        &new_version,
+        version_bump,
+        &reason,
+    )?;
+    let root = manifest_dir(&args.path);
+    plugin::run(&config.plugins, root, &result, args.is_quiet())?;
+
+    if !args.is_quiet() {
+        print_bump(args, version_bump, &tiers, &old_version, &new_version);
+        report_renames(&result);
+    }
+
+    // An aborting hook puts back everything the bump wrote
+    let backup = if config.hooks.is_empty() {
+        None
+    } else {
+        Some(files::Backup::new(version_files(args, manifest, config)?)?)
+    };
+    let mut written = write_version(args, text, manifest, config, &old_version, &new_version)?;
+    if let Err(err) = hooks::run(&config.hooks, root, version, &new_version, args.is_quiet()) {
+        if let Some(backup) = backup {
+            backup.restore()?;
+        }
+        anyhow::bail!("{err}; rolled back the version bump");
+    }
+
+    if args.changelog {
+        written.extend(write_changelogs(
+            args,
+            manifest,
+            config,
+            &new_version.to_string(),
+        )?);
+    }
+
+    written.extend(write_announcements(args, manifest, config, &new_version)?);
+
+    if args.commit {
+        let note = args.notes.then_some(&result);
+        commit_release(
+            args,
+            manifest,
+            config,
+            &new_version,
+            &written,
+            note,
+            freeze_override,
+        )?;
+        if args.next_dev || config.next_dev {
+            start_next_dev(args, manifest, config, &new_version)?;
+        }
+    }
+
+    if args.packaging {
+        update_packaging(args, manifest, config, version, &new_version)?;
+    }
+
+    print_result(args, &result, &new_version)
+}
+
+/// The files announcing the release to other tools: `--dist-manifest` and the `[badge]`
+fn write_announcements(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    new_version: &Version,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    if let Some(path) = &args.dist_manifest {
+        let names = crate_names(args, manifest, config)?;
+        let tag = config.tag(names.first().map_or("", String::as_str), new_version);
+        dist::write(path, new_version, tag, names)?;
+        report_updated(args, path);
+        written.push(path.clone());
+    }
+    if let Some(badge) = &config.badge
+        && let Some(path) = badge::write(manifest_dir(&args.path), badge, new_version)?
+    {
+        report_updated(args, &path);
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Crates renamed since their last release, whose old name `[[sync]]` dependency targets
+/// are moved off
+fn report_renames(result: &output::Output) {
+    for krate in &result.crates {
+        if let Some(from) = &krate.renamed_from {
+            eprintln!(
+                "{} {} was released as {from}; requirements on the old name are pointed at the new one",
+                "ℹ".blue().bold(),
+                krate.name.bold()
+            );
+        }
+    }
+}
+
+/// The "Version bump: old → new (description)" line
+fn print_bump(
+    args: &Args,
+    version_bump: VersionBump,
+    tiers: &[(String, Option<tier::Tier>)],
+    old_version: &str,
+    new_version: &Version,
+) {
+    let description = if args.increment_by > 1 {
+        format!("({}, +{})", version_bump.description(), args.increment_by)
+    } else {
+        format!("({})", version_bump.description())
+    };
+    // Each tier present, most restricted last
+    let mut labels = tiers
+        .iter()
+        .filter_map(|(_, tier)| *tier)
+        .collect::<Vec<_>>();
+    labels.sort();
+    labels.dedup();
+    let labels = labels.iter().fold(String::new(), |labels, tier| {
+        labels + " [" + tier.name() + "]"
+    });
+    eprintln!(
+        "{} {} {} {} {} {}{}",
+        version_bump.emoji(),
+        i18n::tr(Message::VersionBump).bold().blue(),
+        old_version.cyan(),
+        "→".bright_white(),
+        new_version.to_string().bright_green().bold(),
+        description.color(version_bump.color()),
+        labels.dimmed()
+    );
+    if !new_version.build.is_empty() {
+        let new_version = new_version.to_string();
+        eprintln!(
+            "  {} {}  {} {}",
+            i18n::tr(Message::TagSafe).dimmed(),
+            output::tag_safe(&new_version),
+            i18n::tr(Message::RegistrySafe).dimmed(),
+            output::registry_safe(&new_version)
+        );
+    }
+}
+
+/// Print why nothing was bumped, with the side effects asked for on a skip (and the
+/// unchanged versions with `--json` or `--version-only-stdout`)
+fn report_skip(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    line: &str,
+    reason: &Reason,
+) -> anyhow::Result<()> {
+    if !args.is_quiet() {
+        eprintln!("{line}");
+    }
+    if args.check_on_skip {
+        check::check_skipped(args, manifest, config, version)?;
+    }
+    if args.skip_status {
+        mark_skipped(args, config)?;
+    }
+    let result = bump_result(
+        args,
+        manifest,
+        config,
+        version,
+        version,
+        VersionBump::Skip,
+        reason,
+    )?;
+    print_result(args, &result, version)
+}
+
+/// `--skip-status`: a successful `version` status on HEAD, so a pipeline can tell that the
+/// release step ran and chose not to release
+fn mark_skipped(args: &Args, config: &Config) -> anyhow::Result<()> {
+    let token = args.github_token.as_deref().unwrap_or_default();
+    let repo = forge_repo(args, config).ok_or(anyhow::anyhow!(
+        "--skip-status needs --github-repo (or a GitHub, Gitea, or Bitbucket origin remote)"
+    ))?;
+    let sha = git::git(manifest_dir(&args.path), &["rev-parse", "HEAD"])?;
+    forge::commit_status(token, &repo, &sha, "version", "No release for this commit")?;
+    if !args.is_quiet() {
+        eprintln!(
+            "{} Marked {} as skipped",
+            "✓".green().bold(),
+            &sha[..sha.len().min(7)]
+        );
+    }
+    Ok(())
+}
+
+/// The bump result on stdout and in the CI outputs, as requested
+fn print_result(args: &Args, result: &output::Output, version: &Version) -> anyhow::Result<()> {
+    print_output(args, result)?;
+    if args.version_only_stdout {
+        println!("{version}");
+    }
+    if args.github_output {
+        result.write_github_output()?;
+    }
+    Ok(())
+}
+
+/// The bump result on stdout, with `--json` or `--porcelain`
+fn print_output(args: &Args, result: &output::Output) -> anyhow::Result<()> {
+    if args.json {
+        result.print()?;
+    }
+    if let Some(format) = args.porcelain {
+        print!("{}", result.porcelain(format));
+    }
+    Ok(())
+}
+
+/// `version` after `bump`, numbered past its tagged prereleases with `--pre-lineage`
+fn next_version(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    bump: VersionBump,
+) -> anyhow::Result<Version> {
+    let next = bump.apply(version.clone(), &args.bump_options(config)?)?;
+    let Some(tags) = lineage_tags(args, config)? else {
+        return Ok(next);
+    };
+    let names = crate_names(args, manifest, config)?;
+    past_lineage(
+        args,
+        config,
+        &tags,
+        names.first().map_or("", String::as_str),
+        &next,
+    )
+}
+
+/// With `--pre-lineage`, every tag a prerelease counter has to be numbered past
+fn lineage_tags(args: &Args, config: &Config) -> anyhow::Result<Option<Vec<String>>> {
+    if !(args.pre_lineage || config.pre_lineage) {
+        return Ok(None);
+    }
+    Ok(Some(git::all_tags(manifest_dir(&args.path))?))
+}
+
+/// `version`, numbered past the prereleases of `name` already in `tags`
+fn past_lineage(
+    args: &Args,
+    config: &Config,
+    tags: &[String],
+    name: &str,
+    version: &Version,
+) -> anyhow::Result<Version> {
+    let tagged = tags
+        .iter()
+        .filter_map(|tag| config.parse_tag(tag))
+        .filter(|(tag_name, _)| tag_name.is_empty() || tag_name == name)
+        .map(|(_, version)| version)
+        .collect::<Vec<_>>();
+    let next = bump::past_tagged(version.clone(), &tagged)?;
+    if next != *version && !args.is_quiet() {
+        eprintln!(
+            "{} {version} collides with a prerelease already tagged (on another branch or on origin); using {next}",
+            "ℹ".blue().bold()
+        );
+    }
+    Ok(next)
+}
+
+/// `--github-repo`, else the repository the origin remote points at, on the config's
+/// `forge` or the one the remote's host runs
+fn forge_repo(args: &Args, config: &Config) -> Option<forge::Repo> {
+    let remote = git::git(manifest_dir(&args.path), &["remote", "get-url", "origin"]).ok();
+    forge::repo(config.forge, args.github_repo.as_deref(), remote.as_deref())
+}
+
+/// The freeze annotation on the package being bumped, if any. Frozen workspace members are
+/// reported and left out of the release, unless they inherit the version that's about to move
+fn check_frozen(
+    args: &Args,
+    text: &files::Text,
+    manifest: &Manifest,
+    config: &Config,
+) -> anyhow::Result<Option<&'static str>> {
+    if !args.workspace {
+        return Ok(manifest::frozen(&text.content));
+    }
+
+    for member in workspace::members(manifest_dir(&args.path), manifest, &config.ignore)? {
+        let Some(annotation) = member.frozen else {
+            continue;
+        };
+        if config.exclude.contains(&member.name) {
+            continue;
+        }
+        if member.inherits_version {
+            anyhow::bail!(
+                "{} is frozen ({annotation}) but inherits the workspace version; give it its own version or remove the annotation",
+                member.name
+            );
+        }
+        if !args.is_quiet() {
+            eprintln!(
+                "{} {}",
+                "❄".cyan().bold(),
+                i18n::format(
+                    Message::Frozen,
+                    &[("names", &member.name), ("annotation", annotation)]
+                )
+            );
+        }
+    }
+
+    Ok(None)
+}
+
+/// The bump requested or inferred for `version`, after applying the config's policies
+fn choose_bump(args: &Args, config: &Config, version: &Version) -> (VersionBump, Reason) {
+    let (version_bump, reason) = args
+        .to
+        .as_ref()
+        .map(|target| VersionBump::between(version, target))
+        .or(args.version_bump)
+        .map(|bump| (bump, Reason::Argument))
+        .or_else(|| infer::infer_version_bump(args, config))
+        .unwrap_or((VersionBump::Prepatch, Reason::Default));
+    if args.explain_skip && !args.is_quiet() {
+        explain_inference(args, config, version_bump, &reason);
+    }
+
+    let experimental = version.major == 0 && version.minor == 0;
+    if config.experimental_patch_only && experimental && !matches!(reason, Reason::Argument) {
+        let clamped = version_bump.patch_only();
+        if clamped != version_bump && !args.is_quiet() {
+            eprintln!(
+                "{} {version} is 0.0.x, applying the {} bump as {}",
+                "ℹ".blue().bold(),
+                version_bump.name(),
+                clamped.name()
+            );
+        }
+        return (clamped, reason);
+    }
+
+    (version_bump, reason)
+}
+
+/// The crates being released, each with its stability tier
+fn crate_tiers(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+) -> anyhow::Result<Vec<(String, Option<tier::Tier>)>> {
+    if args.workspace {
+        return Ok(release_members(args, manifest, config)?
+            .into_iter()
+            .map(|member| {
+                let tier = tier::resolve(config, &member.name, member.tier);
+                (member.name, tier)
+            })
+            .collect());
+    }
+    let declared = match args.format {
+        manifest::Format::Cargo => tier::in_metadata(
+            manifest
+                .package
+                .as_ref()
+                .and_then(|package| package.metadata.as_ref()),
+        )?,
+        manifest::Format::Raw | manifest::Format::Toml => None,
+    };
+    Ok(crate_names(args, manifest, config)?
+        .into_iter()
+        .map(|name| {
+            let tier = tier::resolve(config, &name, declared);
+            (name, tier)
+        })
+        .collect())
+}
+
+/// An inferred bump held to what the most restricted tier among `tiers` allows. Bumps
+/// given on the command line are taken as they are
+fn cap_for_tier(
+    args: &Args,
+    tiers: &[(String, Option<tier::Tier>)],
+    bump: VersionBump,
+    reason: &Reason,
+) -> VersionBump {
+    let Some((name, tier)) = tiers
+        .iter()
+        .filter_map(|(name, tier)| Some((name, (*tier)?)))
+        .max_by_key(|(_, tier)| *tier)
+    else {
+        return bump;
+    };
+    let capped = tier.cap(bump);
+    if matches!(reason, Reason::Argument) || capped == bump {
+        return bump;
+    }
+    if !args.is_quiet() {
+        eprintln!(
+            "{} {name} is a {} crate, applying the {} bump as {}",
+            "ℹ".blue().bold(),
+            tier.name(),
+            bump.name(),
+            capped.name()
+        );
+    }
+    capped
+}
+
+/// `--explain-skip`: why inference settled on the default bump, or on skipping
+fn explain_inference(args: &Args, config: &Config, bump: VersionBump, reason: &Reason) {
+    match reason {
+        Reason::Default => {
+            eprintln!(
+                "{} Nothing decided the bump, so it's {}:",
+                "ℹ".blue().bold(),
+                bump.name()
+            );
+            for line in infer::explain(args, config) {
+                eprintln!("  {} {line}", "-".dimmed());
+            }
+        }
+        _ if bump == VersionBump::Skip => {
+            eprintln!("{} Skipping: {reason}", "ℹ".blue().bold());
+        }
+        _ => {}
+    }
+}
+
+/// `--assert-bump`: the bump must be the one CI expects
+fn check_assert_bump(args: &Args, bump: VersionBump, reason: &Reason) -> anyhow::Result<()> {
+    match args.assert_bump {
+        Some(expected) if expected != bump => anyhow::bail!(
+            "Expected a {} bump, but got {} ({reason})",
+            expected.name(),
+            bump.name()
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// A missing or yanked base version usually means the last release pipeline
+/// failed partway, so bumping past it would paper over the problem
+fn check_registry(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    level: CheckLevel,
+) -> anyhow::Result<()> {
+    let names = publishable_names(args, manifest, config)?;
+    let mut progress = Progress::new("Checking registry", names.len(), args.is_quiet());
+    let mut problems = Vec::new();
+    for name in names {
+        progress.inc(&name);
+        match registry::published(&args.registry_index, &name, version)? {
+            registry::Published::Yes => {}
+            registry::Published::Yanked => problems.push(format!("{name} {version} was yanked")),
+            registry::Published::Never => {
+                problems.push(format!("{name} {version} was never published"));
+            }
+        }
+    }
+
+    drop(progress);
+    report_problems(problems, level)
+}
+
+/// Safety checks for `--to`: the target must be newer, untagged, and not already on the registry
+fn check_target(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    target: &Version,
+) -> anyhow::Result<()> {
+    if target <= version {
+        anyhow::bail!("--to {target} must be greater than the current version {version}");
+    }
+
+    let root = manifest_dir(&args.path);
+    let names = crate_names(args, manifest, config)?;
+    for tag in release_tags(config, &names, target) {
+        if git::tag_exists(root, &tag) {
+            anyhow::bail!("Tag {tag} already exists");
+        }
+    }
+
+    let Some(level) = args.registry_check else {
+        return Ok(());
+    };
+    let names = publishable_names(args, manifest, config)?;
+    let mut progress = Progress::new("Checking registry", names.len(), args.is_quiet());
+    let mut problems = Vec::new();
+    for name in names {
+        progress.inc(&name);
+        match registry::published(&args.registry_index, &name, target)? {
+            registry::Published::Yes => {
+                problems.push(format!("{name} {target} is already published"));
+            }
+            registry::Published::Yanked => {
+                problems.push(format!("{name} {target} was published and yanked"));
+            }
+            registry::Published::Never => {}
+        }
+    }
+
+    drop(progress);
+    report_problems(problems, level)
+}
+
+/// The gates every release of `name` from `version` to `new_version` goes through, the
+/// package's or a `--paths` member's. Returns the `--override-freeze` reason if it's
+/// released during a freeze
+fn check_release<'a>(
+    args: &'a Args,
+    config: &Config,
+    name: &str,
+    version: &Version,
+    new_version: &Version,
+) -> anyhow::Result<Option<&'a str>> {
+    if args.require_rc {
+        check_rc_soak(args, config, name, version, new_version)?;
+    }
+    check_jump(args, config, version, new_version)?;
+    check_approval(args, config, new_version)?;
+    let freeze_override = check_freeze(args, config, new_version)?;
+    submodule::check(manifest_dir(&args.path), &config.submodules, new_version)?;
+    check_release_interval(args, config, new_version)?;
+    if let Some(upstream) = &config.upstream {
+        upstream.check(&args.registry_index, new_version)?;
+    }
+    Ok(freeze_override)
+}
+
+/// `require_approval`: prereleases go through freely, stable releases need `--approve`
+fn check_approval(args: &Args, config: &Config, new_version: &Version) -> anyhow::Result<()> {
+    if config.require_approval && new_version.pre.is_empty() && !args.approve {
+        anyhow::bail!(
+            "{new_version} is a stable release, which needs sign-off: pass --approve or set VERSION_RELEASE_APPROVED=1"
+        );
+    }
+    Ok(())
+}
+
+/// `max_jump`: refuse a bump that moves a component further than configured, which is
+/// usually a typo in `--to` or `--increment-by`
+fn check_jump(
+    args: &Args,
+    config: &Config,
+    version: &Version,
+    new_version: &Version,
+) -> anyhow::Result<()> {
+    match &config.max_jump {
+        Some(max_jump) if !args.allow_large_jump => {
+            match max_jump.violation(version, new_version) {
+                Some(problem) => Err(anyhow::anyhow!(problem)),
+                None => Ok(()),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `freeze`: refuse a release during a freeze window unless `--override-freeze` says why,
+/// returning that reason for the release commit's `Freeze-Override:` trailer
+fn check_freeze<'a>(
+    args: &'a Args,
+    config: &Config,
+    new_version: &Version,
+) -> anyhow::Result<Option<&'a str>> {
+    let Some(freeze) = freeze::blocking(&config.freeze, clock::system_now(), new_version)? else {
+        return Ok(None);
+    };
+    match args.override_freeze.as_deref().map(str::trim) {
+        None => anyhow::bail!(
+            "Releases are frozen during \"{}\" (UTC); pass --override-freeze <REASON> to release {new_version} anyway",
+            freeze.window
+        ),
+        Some("") => {
+            anyhow::bail!("--override-freeze needs a reason for releasing during the freeze")
+        }
+        Some(reason) => {
+            if !args.is_quiet() {
+                eprintln!(
+                    "{} Releasing during the freeze \"{}\": {reason}",
+                    "❄".cyan().bold(),
+                    freeze.window
+                );
+            }
+            Ok(Some(reason))
+        }
+    }
+}
+
+/// `min_release_interval`: a stable release can't follow the last release tag too closely,
+/// which is usually automation looping
+fn check_release_interval(
+    args: &Args,
+    config: &Config,
+    new_version: &Version,
+) -> anyhow::Result<()> {
+    let Some(interval) = config.min_release_interval()? else {
+        return Ok(());
+    };
+    if !new_version.pre.is_empty() || args.ignore_release_interval {
+        return Ok(());
+    }
+    let Some(tag) = previous_tag(args) else {
+        return Ok(());
+    };
+
+    let root = manifest_dir(&args.path);
+    let age = clock::system_now().saturating_sub(git::tag_timestamp(root, &tag)?);
+    if age < interval {
+        anyhow::bail!(
+            "{tag} was released {} minutes ago, under min_release_interval ({}); pass --ignore-release-interval to release anyway",
+            age / 60,
+            config.min_release_interval.as_deref().unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+/// `--require-rc`: a stable `new_version` of `name` must come from a soaked rc of the same
+/// version
+fn check_rc_soak(
+    args: &Args,
+    config: &Config,
+    name: &str,
+    version: &Version,
+    new_version: &Version,
+) -> anyhow::Result<()> {
+    if !new_version.pre.is_empty() {
+        return Ok(());
+    }
+
+    if !is_rc_of(version, new_version) {
+        anyhow::bail!(
+            "--require-rc: {new_version} needs a release candidate first (current version is {version})"
+        );
+    }
+
+    let tag = config.tag(name, version);
+    let root = manifest_dir(&args.path);
+    if !git::tag_exists(root, &tag) {
+        anyhow::bail!("--require-rc: {version} was never tagged ({tag} not found)");
+    }
+
+    let soaked = clock::system_now().saturating_sub(git::commit_timestamp(root, &tag)?)
+        / changelog::SECONDS_PER_DAY;
+    let required = config.rc_soak_days.unwrap_or(0);
+    if soaked < required {
+        anyhow::bail!(
+            "--require-rc: {version} has soaked for {soaked} of the required {required} days"
+        );
+    }
+
+    Ok(())
+}
+
+/// Whether `version` is an `rc` prerelease of `release`
+fn is_rc_of(version: &Version, release: &Version) -> bool {
+    let is_rc = version.pre.as_str() == "rc" || version.pre.as_str().starts_with("rc.");
+    is_rc
+        && (version.major, version.minor, version.patch)
+            == (release.major, release.minor, release.patch)
+}
+
+/// Crates that can be published, minus `exclude`d workspace members
+fn publishable_names(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+) -> anyhow::Result<Vec<String>> {
+    Ok(if args.workspace {
+        release_members(args, manifest, config)?
+            .into_iter()
+            .filter(|member| member.publish)
+            .map(|member| member.name)
+            .collect()
+    } else {
+        manifest
+            .package
+            .iter()
+            .filter(|package| workspace::is_publishable(package.publish.as_ref()))
+            .map(|package| package.name.clone())
+            .collect()
+    })
+}
+
+/// Bail with every problem at `error`, otherwise print them as warnings
+fn report_problems(problems: Vec<String>, level: CheckLevel) -> anyhow::Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    match level {
+        CheckLevel::Error => anyhow::bail!("{}", problems.join(", ")),
+        CheckLevel::Warn => {
+            for problem in problems {
+                eprintln!("{} {problem}", "⚠".yellow().bold());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Commit everything that was written, then tag and push as requested. A release during a
+/// freeze records the `freeze_override` reason in the commit
+fn commit_release(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    new_version: &Version,
+    written: &[PathBuf],
+    note: Option<&output::Output>,
+    freeze_override: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut tags = Vec::new();
+    if args.tag {
+        tags = release_tags(config, &crate_names(args, manifest, config)?, new_version);
+        let root = manifest_dir(&args.path);
+        for tag in go::tags(root, &config.sync, new_version, args.is_quiet())? {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+    }
+
+    let names = crate_names(args, manifest, config)?;
+    let first = names.first().map_or("", String::as_str);
+    let tags = tags
+        .into_iter()
+        .map(|tag| {
+            let name = config
+                .parse_tag(&tag)
+                .map(|(name, _)| name)
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| first.to_string());
+            let message = tag_message(args, config, &tag, &name, new_version)?;
+            Ok((tag, message))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let message = with_freeze_override(format!("Release {new_version}"), freeze_override);
+    let note = note.map(serde_json::to_string_pretty).transpose()?;
+    release::commit_and_tag(
+        manifest_dir(&args.path),
+        written,
+        &message,
+        &tags,
+        note.as_deref(),
+        args.push,
+    )?;
+
+    if !args.is_quiet() {
+        report_committed(&message, &tags);
+        if note.is_some() {
+            eprintln!(
+                "{} {}",
+                "✓".green().bold(),
+                i18n::format(
+                    Message::Noted,
+                    &[("ref", &release::NOTES_REF.bold().to_string())]
+                )
+            );
+        }
+        if args.push {
+            eprintln!("{} {}", "✓".green().bold(), i18n::tr(Message::Pushed));
+        }
+    }
+
+    if args.push {
+        publish(args, config, new_version, &message, &tags)?;
+    }
+    Ok(())
+}
+
+/// `message` with a `Freeze-Override:` trailer giving the reason, if there is one
+fn with_freeze_override(message: String, freeze_override: Option<&str>) -> String {
+    match freeze_override {
+        Some(reason) => format!("{message}\n\nFreeze-Override: {reason}"),
+        None => message,
+    }
+}
+
+/// `--forge-release` and `--open-pr`, once the release is pushed
+fn publish(
+    args: &Args,
+    config: &Config,
+    new_version: &Version,
+    message: &str,
+    tags: &[(String, String)],
+) -> anyhow::Result<()> {
+    if !args.forge_release && args.open_pr.is_none() {
+        return Ok(());
+    }
+    let token = args.github_token.as_deref().unwrap_or_default();
+    let repo = forge_repo(args, config).ok_or(anyhow::anyhow!(
+        "--forge-release and --open-pr need --github-repo (or a GitHub, Gitea, or Bitbucket origin remote)"
+    ))?;
+
+    if args.forge_release {
+        for (tag, notes) in tags {
+            let prerelease = !new_version.pre.is_empty();
+            let created = forge::create_release(token, &repo, tag, notes, prerelease)?;
+            if args.is_quiet() {
+                continue;
+            }
+            match created {
+                Some(url) => eprintln!("{} Created the {tag} release: {url}", "✓".green().bold()),
+                None => eprintln!(
+                    "{} {} has no releases, so the {tag} tag stands for it",
+                    "ℹ".blue().bold(),
+                    repo.kind.name()
+                ),
+            }
+        }
+    }
+
+    if let Some(base) = &args.open_pr {
+        let head = git::current_branch(manifest_dir(&args.path)).ok_or(anyhow::anyhow!(
+            "--open-pr needs a branch to open the pull request from, but HEAD is detached"
+        ))?;
+        if !args.pr_label.is_empty() && !repo.kind.has_labels() && !args.is_quiet() {
+            eprintln!(
+                "{} {} has no labels, so --pr-label is left off",
+                "⚠".yellow().bold(),
+                repo.kind.name()
+            );
+        }
+        let request = forge::NewPullRequest {
+            title: message.lines().next().unwrap_or(message).to_string(),
+            body: tags
+                .first()
+                .map(|(_, notes)| notes.clone())
+                .unwrap_or_default(),
+            head,
+            base: base.clone(),
+            labels: args.pr_label.clone(),
+        };
+        let opened = forge::open_pull_request(token, &repo, &request)?;
+        if !args.is_quiet() {
+            eprintln!(
+                "{} Opened pull request #{}: {}",
+                "✓".green().bold(),
+                opened.number,
+                opened.url
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `--next-dev`: once `released` is committed, commit the manifest at the next patch's
+/// `-dev.0`, so the branch never sits on a released version. Prereleases are left alone
+fn start_next_dev(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    released: &Version,
+) -> anyhow::Result<()> {
+    if !released.pre.is_empty() {
+        if !args.is_quiet() {
+            eprintln!(
+                "{} {released} is a prerelease, so the manifest stays on it",
+                "ℹ".blue().bold()
+            );
+        }
+        return Ok(());
+    }
+
+    let patch = released
+        .patch
+        .checked_add(1)
+        .ok_or_else(|| anyhow::anyhow!("Cannot increment the patch version past {}", u64::MAX))?;
+    let mut dev = Version::new(released.major, released.minor, patch);
+    dev.pre = semver::Prerelease::new(&format!("{}.0", bump::DEV_PRE_ID))?;
+    // The release rewrote the manifest since it was read
+    let text = files::read(&args.path)?;
+    let written = write_version(args, &text, manifest, config, &released.to_string(), &dev)?;
+    let message = format!("Start {dev} development");
+    release::commit_and_tag(
+        manifest_dir(&args.path),
+        &written,
+        &message,
+        &[],
+        None,
+        args.push,
+    )?;
+    if !args.is_quiet() {
+        report_committed(&message, &[]);
+    }
+    Ok(())
+}
+
+/// Rewrite the manifest, from its `text` as read, and the `[[sync]]` targets, returning
+/// the files written. With `--verify-build`, the build check runs next and every edit is
+/// undone if it fails
+fn write_version(
+    args: &Args,
+    text: &files::Text,
+    manifest: &Manifest,
+    config: &Config,
+    old_version: &str,
+    new_version: &Version,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let root = manifest_dir(&args.path);
+    let backup = if args.verify_build {
+        Some(files::Backup::new(version_files(args, manifest, config)?)?)
+    } else {
+        None
+    };
+
+    let file_content = rewrite_manifest(args, &text.content, old_version, new_version)?;
+    files::write(&args.path, text.encode(&file_content)?)?;
+    report_updated(args, &args.path);
+    let mut written = vec![args.path.clone()];
+
+    let renames = if args.format == manifest::Format::Cargo
+        && config
+            .sync
+            .iter()
+            .any(|target| target.format() == Some(sync::Format::Dependency))
+    {
+        rename::renames(args, manifest, config)?
+    } else {
+        Vec::new()
+    };
+    for target in &config.sync {
+        let path = target.apply(root, old_version, &new_version.to_string(), &renames)?;
+        report_updated(args, &path);
+        written.push(path);
+    }
+    if graduate::active(args) {
+        let names = lock_bumps(args, manifest, config, old_version, new_version)?
+            .into_iter()
+            .map(|bump| bump.name)
+            .collect::<Vec<_>>();
+        for path in graduate::retarget_dependents(args, manifest, config, &names, new_version)? {
+            if !written.contains(&path) {
+                written.push(path);
+            }
+        }
+    }
+    if args.format == manifest::Format::Cargo && args.anchor.is_none() {
+        let lockfile = lockfile(root);
+        if lockfile::write(
+            &lockfile,
+            &lock_bumps(args, manifest, config, old_version, new_version)?,
+        )? {
+            report_updated(args, &lockfile);
+            written.push(lockfile);
+        }
+    }
+
+    if let Some(backup) = backup {
+        verify_build(args, config, &backup, &old_version.parse()?, new_version)?;
+        // cargo may have refreshed the lockfile's entries for the bumped crates
+        let lockfile = lockfile(root);
+        if lockfile.is_file() && !written.contains(&lockfile) {
+            written.push(lockfile);
+        }
+    }
+
+    if (args.release_metadata || config.release_metadata) && new_version.pre.is_empty() {
+        let mut manifests = vec![args.path.clone()];
+        if args.workspace {
+            manifests.extend(
+                release_members(args, manifest, config)?
+                    .into_iter()
+                    .map(|member| member.dir.join("Cargo.toml")),
+            );
+        }
+        for path in provenance::write(root, &manifests)? {
+            report_updated(args, &path);
+            if !written.contains(&path) {
+                written.push(path);
+            }
+        }
+    }
+
+    Ok(written)
+}
+
+const DEFAULT_VERIFY_BUILD: &str = "cargo check --workspace";
+
+/// Every file [`write_version`] may rewrite, to back up before it does
+fn version_files(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let root = manifest_dir(&args.path);
+    let mut paths = vec![args.path.clone(), lockfile(root)];
+    paths.extend(config.sync.iter().map(|target| root.join(&target.path)));
+    if graduate::active(args) {
+        paths.extend(graduate::dependent_manifests(args, manifest, config)?);
+    }
+    if args.workspace && (args.release_metadata || config.release_metadata) {
+        paths.extend(
+            release_members(args, manifest, config)?
+                .into_iter()
+                .map(|member| member.dir.join("Cargo.toml")),
+        );
+    }
+    Ok(paths)
+}
+
+/// `--verify-build`: run the build check on the versions just written, putting back
+/// everything in `backup` if it fails
+fn verify_build(
+    args: &Args,
+    config: &Config,
+    backup: &files::Backup,
+    old_version: &Version,
+    new_version: &Version,
+) -> anyhow::Result<()> {
+    let command = config
+        .verify_build
+        .as_deref()
+        .unwrap_or(DEFAULT_VERIFY_BUILD);
+    if let Err(err) =
+        hooks::run_command(command, manifest_dir(&args.path), old_version, new_version)
+    {
+        backup.restore()?;
+        anyhow::bail!("{err}; rolled back the version bump");
+    }
+    if !args.is_quiet() {
+        eprintln!(
+            "{} {}",
+            "✓".green().bold(),
+            i18n::format(Message::VerifiedBuild, &[("command", command)])
+        );
+    }
+    Ok(())
+}
+
+/// The `Cargo.lock` entries moving with the bump: the package's, or with `--workspace`
+/// every released member sharing the workspace version
+fn lock_bumps(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    old_version: &str,
+    new_version: &Version,
+) -> anyhow::Result<Vec<lockfile::Bump>> {
+    let bump = |name: String| lockfile::Bump {
+        name,
+        old: old_version.to_string(),
+        new: new_version.to_string(),
+    };
+    if args.workspace {
+        Ok(release_members(args, manifest, config)?
+            .into_iter()
+            .filter(|member| member.inherits_version)
+            .map(|member| bump(member.name))
+            .collect())
+    } else {
+        Ok(manifest
+            .package
+            .iter()
+            .map(|package| bump(package.name.clone()))
+            .collect())
+    }
+}
+
+/// The `Cargo.lock` governing the manifest: the nearest one above it, else beside it
+fn lockfile(root: &Path) -> PathBuf {
+    root.ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lockfile| lockfile.is_file())
+        .unwrap_or_else(|| root.join("Cargo.lock"))
+}
+
+/// `--packaging`: point the `[[packaging]]` formulas / PKGBUILDs at the new release
+fn update_packaging(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    new_version: &Version,
+) -> anyhow::Result<()> {
+    let names = crate_names(args, manifest, config)?;
+    let tag = config.tag(names.first().map_or("", String::as_str), new_version);
+    for package in &config.packaging {
+        let path = package.update(manifest_dir(&args.path), version, new_version, &tag)?;
+        report_updated(args, &path);
+    }
+    Ok(())
+}
+
+/// The annotation for `tag`, from `tag_message`
+fn tag_message(
+    args: &Args,
+    config: &Config,
+    tag: &str,
+    name: &str,
+    version: &Version,
+) -> anyhow::Result<String> {
+    let template = match &config.tag_message {
+        Some(template) => template.as_str(),
+        None if args.artifact.is_empty() => "Release {version}",
+        None => "Release {version}\n\n{artifacts}",
+    };
+    let mut message = template
+        .replace("{version}", &version.to_string())
+        .replace("{name}", name)
+        .replace("{tag}", tag);
+    if message.contains("{artifacts}") {
+        let checksums = release::checksums(&args.artifact)?;
+        message = message.replace("{artifacts}", checksums.trim_end());
+    }
+    // Last, so braces in commit subjects aren't taken for placeholders
+    if message.contains("{changelog}") {
+        let root = manifest_dir(&args.path);
+        let commits = git::commits(root, previous_tag(args).as_deref(), &[])?;
+        let mut section = changelog::section(&version.to_string(), &args.clock().today(), &commits)
+            + &submodule::changelog(root, &config.submodules)?;
+        if graduate::active(args) {
+            section = graduate::announce(&section, config);
+        }
+        message = message.replace("{changelog}", section.trim_end());
+    }
+    Ok(message)
+}
+
+/// One tag per crate when the tag format names the crate, otherwise a single tag
+fn release_tags(config: &Config, names: &[String], version: &Version) -> Vec<String> {
+    if config.tag_format().contains("{name}") {
+        names.iter().map(|name| config.tag(name, version)).collect()
+    } else {
+        vec![config.tag(names.first().map_or("", String::as_str), version)]
+    }
+}
+
+fn run_scan(args: &Args, dir: &Path, bump: Option<VersionBump>) -> anyhow::Result<()> {
+    let config = Config::load(args.config.as_deref(), dir)?;
+    let options = args.bump_options(&config)?;
+    let mut crates = Vec::new();
+
+    let manifests = scan::find_manifests(dir, &config.ignore)?;
+    let mut progress = Progress::new("Scanning", manifests.len(), args.is_quiet());
+    for path in manifests {
+        progress.inc(&path.to_string_lossy());
+        let Some(package) = manifest::load(&path)?.package else {
+            continue;
+        };
+        let text = files::read(&path)?;
+        if let Some(annotation) = manifest::frozen(&text.content) {
+            if !args.is_quiet() {
+                progress.println(&format!(
+                    "{}  {}  {}",
+                    path.display(),
+                    package.name.bold(),
+                    format!("(frozen: {annotation})").dimmed()
+                ));
+            }
+            let version = package
+                .version
+                .as_ref()
+                .and_then(|version| version.clone().as_local())
+                .unwrap_or_default();
+            crates.push(output::CrateResult::new(
+                package.name,
+                version.clone(),
+                version,
+                VersionBump::Skip.name(),
+                vec![Reason::Frozen { annotation }],
+            ));
+            continue;
+        }
+        let Some(version) = package
+            .version
+            .as_ref()
+            .and_then(|version| version.clone().as_local())
+        else {
+            if !args.is_quiet() {
+                progress.println(&format!(
+                    "{}  {}  {}",
+                    path.display(),
+                    package.name.bold(),
+                    "(inherits its workspace version)".dimmed()
+                ));
+            }
+            continue;
+        };
+        let version = Version::parse(&version)
+            .map_err(|e| anyhow::anyhow!("{}: invalid version: {e}", path.display()))?;
+
+        let new_version = match bump {
+            Some(bump) if !matches!(bump, VersionBump::Skip) => {
+                let new_version = bump.apply(version.clone(), &options)?;
+                let span =
+                    manifest::table_span(&text.content, "package").unwrap_or(0..text.content.len());
+                let content = rewrite_span(
+                    &text.content,
+                    span,
+                    "version",
+                    &version.to_string(),
+                    &new_version.to_string(),
+                )?;
+                files::write(&path, text.encode(&content)?)?;
+                new_version
+            }
+            _ => version.clone(),
+        };
+
+        if !args.is_quiet() {
+            if new_version == version {
+                progress.println(&format!(
+                    "{}  {}  {}",
+                    path.display(),
+                    package.name.bold(),
+                    version
+                ));
+            } else {
+                progress.println(&format!(
+                    "{}  {}  {} {} {}",
+                    path.display(),
+                    package.name.bold(),
+                    version.to_string().cyan(),
+                    "→".bright_white(),
+                    new_version.to_string().bright_green().bold()
+                ));
+            }
+        }
+
+        crates.push(output::CrateResult::new(
+            package.name,
+            version.to_string(),
+            new_version.to_string(),
+            bump.unwrap_or(VersionBump::Skip).name(),
+            vec![Reason::Argument],
+        ));
+    }
+
+    drop(progress);
+    print_output(args, &output::Output::new(crates))?;
+
+    Ok(())
+}
+
+/// `--write-migrated`: bring the config file up to the current `config_version`
+fn write_migrated(args: &Args) -> anyhow::Result<()> {
+    let Some(path) = Config::file(args.config.as_deref(), manifest_dir(&args.path)) else {
+        anyhow::bail!("--write-migrated: there's no config file to migrate");
+    };
+    if upgrade::write(&path)? {
+        report_updated(args, &path);
+    } else if !args.is_quiet() {
+        eprintln!(
+            "{} {} is already at config_version {}",
+            "ℹ".blue().bold(),
+            path.display(),
+            upgrade::CONFIG_VERSION
+        );
+    }
+    Ok(())
+}
+
+fn run_migrate(
+    args: &Args,
+    manifest: &Manifest,
+    from: migrate::Source,
+    force: bool,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| config::default_path(root));
+    if path.exists() && !force {
+        anyhow::bail!("{} already exists (use --force)", path.display());
+    }
+
+    let migration = migrate::migrate(from, root, manifest)?;
+    std::fs::write(&path, toml::to_string(&migration.config)?)?;
+    report_updated(args, &path);
+
+    if !args.is_quiet() {
+        for setting in &migration.untranslated {
+            eprintln!("{} Not translated: {setting}", "⚠".yellow().bold());
+        }
+    }
+
+    Ok(())
+}
+
+/// Workspace members, minus those excluded by the config
+fn release_members(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+) -> anyhow::Result<Vec<workspace::Member>> {
+    Ok(
+        workspace::members(manifest_dir(&args.path), manifest, &config.ignore)?
+            .into_iter()
+            .filter(|member| !config.exclude.contains(&member.name) && member.frozen.is_none())
+            .collect(),
+    )
+}
+
+/// Names of the crates being released: the package, or every workspace member
+fn crate_names(args: &Args, manifest: &Manifest, config: &Config) -> anyhow::Result<Vec<String>> {
+    if args.workspace {
+        Ok(release_members(args, manifest, config)?
+            .into_iter()
+            .map(|member| member.name)
+            .collect())
+    } else {
+        Ok(manifest
+            .package
+            .iter()
+            .map(|package| package.name.clone())
+            .collect())
+    }
+}
+
+fn bump_result(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    old_version: &Version,
+    new_version: &Version,
+    version_bump: VersionBump,
+    reason: &Reason,
+) -> anyhow::Result<output::Output> {
+    let renames = rename::renames(args, manifest, config)?;
+    let crates = crate_tiers(args, manifest, config)?
+        .into_iter()
+        .map(|(name, tier)| {
+            let renamed_from = renames
+                .iter()
+                .find(|rename| rename.to == name)
+                .map(|rename| rename.from.clone());
+            output::CrateResult {
+                renamed_from,
+                tier,
+                ..output::CrateResult::new(
+                    name,
+                    old_version.to_string(),
+                    new_version.to_string(),
+                    version_bump.name(),
+                    vec![reason.clone()],
+                )
+            }
+        })
+        .collect();
+
+    Ok(output::Output::new(crates))
+}
+
+/// Rewrite the manifest's version field: the `--anchor` match if given, otherwise the
+/// `version` key of `[workspace.package]`, the `--package` table, or `[package]`
+fn rewrite_manifest(
+    args: &Args,
+    content: &str,
+    old_version: &str,
+    new_version: &Version,
+) -> anyhow::Result<String> {
+    let new_version = new_version.to_string();
+    if args.format == manifest::Format::Raw {
+        return Ok(content.replacen(old_version, &new_version, 1));
+    }
+    if let Some(anchor) = &args.anchor {
+        return sync::replace_capture(content, anchor, old_version, &new_version)
+            .map_err(|e| anyhow::anyhow!("--anchor: {e}"));
+    }
+    if args.format == manifest::Format::Toml {
+        let (span, field) = manifest::key_span(content, &args.version_key);
+        return rewrite_span(content, span, &field, old_version, &new_version);
+    }
+
+    let whole = 0..content.len();
+    let (span, field) = if args.workspace {
+        match manifest::table_span(content, "workspace.package") {
+            Some(span) => (span, "version"),
+            None => (
+                manifest::table_span(content, "workspace").unwrap_or(whole),
+                r"package\.version",
+            ),
+        }
+    } else {
+        let span = match args.package.as_deref() {
+            Some(name) => manifest::package_span(content, name),
+            None => manifest::table_span(content, "package"),
+        };
+        (span.unwrap_or(whole), "version")
+    };
+
+    rewrite_span(content, span, field, old_version, &new_version)
+}
+
+/// [`rewrite_version`] limited to `span` of `content`
+fn rewrite_span(
+    content: &str,
+    span: std::ops::Range<usize>,
+    field: &str,
+    old: &str,
+    new: &str,
+) -> anyhow::Result<String> {
+    Ok(format!(
+        "{}{}{}",
+        &content[..span.start],
+        rewrite_version(&content[span.clone()], field, old, new)?,
+        &content[span.end..]
+    ))
+}
+
+/// Replace `old` in `content` with `new` where the `field = "old"` pattern matches
+fn rewrite_version(content: &str, field: &str, old: &str, new: &str) -> anyhow::Result<String> {
+    let escaped = regex::escape(old);
+    let pattern = regex::Regex::new(&format!(r#"(?m)^(\s*{field}\s*=\s*)(['"]){escaped}['"]"#))?;
+    if !pattern.is_match(content) {
+        anyhow::bail!(
+            "Couldn't find the version {old} in the manifest (pass --anchor to point at it)"
+        );
+    }
+    let content = pattern.replace(content, |caps: &regex::Captures| {
+        let quote = &caps[2];
+        format!("{}{quote}{new}{quote}", &caps[1])
+    });
+    Ok(content.into_owned())
+}
+
+fn report_updated(args: &Args, path: &Path) {
+    if !args.is_quiet() {
+        eprintln!(
+            "{} {}",
+            "✓".green().bold(),
+            i18n::format(
+                Message::Updated,
+                &[("path", &path.display().to_string().bold().to_string())]
+            )
+        );
+    }
+}
+
+/// The "Committed …" and "Tagged …" lines after a release commit
+fn report_committed(message: &str, tags: &[(String, String)]) {
+    eprintln!(
+        "{} {}",
+        "✓".green().bold(),
+        i18n::format(
+            Message::Committed,
+            &[(
+                "message",
+                &message
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .bold()
+                    .to_string()
+            )]
+        )
+    );
+    for (tag, _) in tags {
+        eprintln!(
+            "{} {}",
+            "✓".green().bold(),
+            i18n::format(Message::Tagged, &[("tag", &tag.bold().to_string())])
+        );
+    }
+}
+
+fn manifest_dir(path: &Path) -> &Path {
+    path.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+/// Commits since the newest stable release tag reachable from HEAD
+fn distance_from_stable(root: &Path, config: &Config) -> anyhow::Result<u64> {
+    let stable = git::merged_tags(root)?
+        .into_iter()
+        .filter_map(|tag| Some((config.version_from_tag(&tag)?, tag)))
+        .filter(|(version, _)| version.pre.is_empty())
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, tag)| tag);
+    git::distance(root, stable.as_deref())
+}
+
+/// The tag commits are collected from: `--since`, else the nearest reachable tag
+fn previous_tag(args: &Args) -> Option<String> {
+    args.since
+        .clone()
+        .or_else(|| git::last_tag(manifest_dir(&args.path)))
+}
+
+fn write_changelogs(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    new_version: &str,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let root = manifest_dir(&args.path);
+    let since = previous_tag(args);
+    let date = args.clock().today();
+
+    // (changelog path, commit pathspecs)
+    let targets = if args.per_crate_changelog {
+        let template = args
+            .changelog_path
+            .as_deref()
+            .unwrap_or("{dir}/CHANGELOG.md");
+        release_members(args, manifest, config)?
+            .into_iter()
+            .map(|member| {
+                let dir = member.dir.strip_prefix(root).unwrap_or(&member.dir);
+                let path = template
+                    .replace("{name}", &member.name)
+                    .replace("{dir}", &dir.to_string_lossy());
+                (root.join(path), config.commit_pathspecs(&member.name, dir))
+            })
+            .collect::<Vec<_>>()
+    } else {
+        let path = args.changelog_path.as_deref().unwrap_or("CHANGELOG.md");
+        vec![(root.join(path), Vec::new())]
+    };
+
+    let submodules = submodule::changelog(root, &config.submodules)?;
+    let mut written = Vec::new();
+    for (path, scope) in targets {
+        let commits = git::commits(root, since.as_deref(), &scope)?;
+        let mut section = changelog::section(new_version, &date, &commits) + &submodules;
+        if graduate::active(args) {
+            section = graduate::announce(&section, config);
+        }
+        changelog::prepend(&path, &section)?;
+        report_updated(args, &path);
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    #[test]
+    fn test_bump_parser() {
+        let cmd = clap::Command::new("version");
+        let parse = |value: &str| BumpParser.parse_ref(&cmd, None, OsStr::new(value));
+        assert_eq!(parse("minor").unwrap(), VersionBump::Minor);
+        assert_eq!(parse("MAJOR").unwrap(), VersionBump::Major);
+        assert_eq!(parse("Breaking").unwrap(), VersionBump::Major);
+        assert_eq!(parse("feature").unwrap(), VersionBump::Minor);
+        assert_eq!(parse("fix").unwrap(), VersionBump::Patch);
+        assert_eq!(parse("pre").unwrap(), VersionBump::Prepatch);
+        assert!(parse("featur").is_err());
+
+        assert_eq!(BumpParser::suggestion("featur").as_deref(), Some("feature"));
+        assert_eq!(
+            BumpParser::suggestion("Brekaing").as_deref(),
+            Some("breaking")
+        );
+        assert_eq!(BumpParser::suggestion("minr").as_deref(), Some("minor"));
+        assert_eq!(BumpParser::suggestion("release"), None);
+    }
+
+    #[test]
+    fn test_is_rc_of() {
+        let v = |s| Version::parse(s).unwrap();
+        assert!(is_rc_of(&v("1.3.0-rc.2"), &v("1.3.0")));
+        assert!(is_rc_of(&v("1.3.0-rc"), &v("1.3.0")));
+        assert!(!is_rc_of(&v("1.3.0-beta.1"), &v("1.3.0")));
+        assert!(!is_rc_of(&v("1.3.0-rc.1"), &v("1.4.0")));
+        assert!(!is_rc_of(&v("1.2.9"), &v("1.3.0")));
+    }
+
+    #[test]
+    fn test_with_freeze_override() {
+        assert_eq!(
+            with_freeze_override("Release 1.2.4".to_string(), Some("hotfix")),
+            "Release 1.2.4\n\nFreeze-Override: hotfix"
+        );
+        assert_eq!(
+            with_freeze_override("Release 1.2.4".to_string(), None),
+            "Release 1.2.4"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_version() {
+        let content = "[package]\nversion = '1.2.3-alpha.1.2+meta'\n";
+        assert_eq!(
+            rewrite_version(content, "version", "1.2.3-alpha.1.2+meta", "1.2.3").unwrap(),
+            "[package]\nversion = '1.2.3'\n"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_version_requires_a_match() {
+        assert!(
+            rewrite_version(
+                "[package]\nversion = \"1.0.0\"\n",
+                "version",
+                "2.0.0",
+                "2.0.1"
+            )
+            .is_err()
+        );
+        assert_eq!(
+            rewrite_version(
+                "[package]\n  version  = \"1.0.0\"\n",
+                "version",
+                "1.0.0",
+                "1.0.1"
+            )
+            .unwrap(),
+            "[package]\n  version  = \"1.0.1\"\n"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_large_manifest() {
+        let mut content =
+            "[package]\nname = \"big\"\nversion = \"1.0.0\"\n\n[dependencies]\n".to_string();
+        content.extend((0..100_000).map(|i| format!("dep-{i} = {{ version = \"1.0.0\" }}\n")));
+        let span = manifest::table_span(&content, "package").unwrap();
+        let rewritten = rewrite_span(&content, span, "version", "1.0.0", "1.0.1").unwrap();
+        assert_eq!(rewritten, content.replacen("\"1.0.0\"", "\"1.0.1\"", 1));
+    }
+
+    mod props {
+        use super::*;
+        use proptest::prelude::*;
+        use semver::{BuildMetadata, Prerelease};
+
+        const BUMPS: [VersionBump; 7] = [
+            VersionBump::Prepatch,
+            VersionBump::Patch,
+            VersionBump::Preminor,
+            VersionBump::Minor,
+            VersionBump::Premajor,
+            VersionBump::Major,
+            VersionBump::Skip,
+        ];
+
+        fn identifiers() -> impl Strategy<Value = String> {
+            let identifier = prop_oneof!["0|[1-9][0-9]{0,5}", "[0-9]*[A-Za-z-][0-9A-Za-z-]{0,5}"];
+            prop::collection::vec(identifier, 0..4).prop_map(|ids| ids.join("."))
+        }
+
+        prop_compose! {
+            fn versions()(
+                major in any::<u64>(),
+                minor in any::<u64>(),
+                patch in any::<u64>(),
+                pre in identifiers(),
+                build in identifiers(),
+            ) -> Version {
+                let mut version = Version::new(major, minor, patch);
+                version.pre = Prerelease::new(&pre).unwrap();
+                version.build = BuildMetadata::new(&build).unwrap();
+                version
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn bump_and_write_round_trips(
+                version in versions(),
+                bump in prop::sample::select(BUMPS.as_slice()),
+                preserve_prerelease in any::<bool>(),
+                build_policy in prop::sample::select(
+                    [BuildPolicy::Clear, BuildPolicy::Keep, BuildPolicy::Append].as_slice()
+                ),
+            ) {
+                let options = BumpOptions {
+                    preserve_prerelease,
+                    build_policy,
+                    ..BumpOptions::default()
+                };
+                let Ok(bumped) = bump.apply(version.clone(), &options) else {
+                    // Only overflowing a component is allowed to fail
+                    prop_assert!(version.major == u64::MAX
+                        || version.minor == u64::MAX
+                        || version.patch == u64::MAX
+                        || version.pre.split('.').any(|id| id == u64::MAX.to_string()));
+                    return Ok(());
+                };
+
+                prop_assert_eq!(Version::parse(&bumped.to_string()).unwrap(), bumped.clone());
+                if !bump.is_pre() && !matches!(bump, VersionBump::Skip) {
+                    prop_assert!(bumped.pre.is_empty());
+                }
+                if matches!(bump, VersionBump::Skip) || build_policy != BuildPolicy::Clear {
+                    prop_assert_eq!(&bumped.build, &version.build);
+                } else {
+                    prop_assert!(bumped.build.is_empty());
+                }
+                if matches!(bump, VersionBump::Skip) {
+                    prop_assert_eq!(&bumped, &version);
+                }
+                if preserve_prerelease && bump.is_pre() && !version.pre.is_empty() {
+                    let (kept, last) = version.pre.rsplit_once('.').unwrap_or(("", &version.pre));
+                    let kept = if last.parse::<u64>().is_ok() { kept } else { version.pre.as_str() };
+                    prop_assert!(bumped.pre.starts_with(kept));
+                }
+
+                let manifest = format!("[package]\nversion = \"{version}\"\n");
+                let written = rewrite_version(
+                    &manifest,
+                    "version",
+                    &version.to_string(),
+                    &bumped.to_string(),
+                )
+                .unwrap();
+                prop_assert_eq!(written, format!("[package]\nversion = \"{bumped}\"\n"));
+            }
+        }
+    }
+}
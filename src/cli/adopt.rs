@@ -0,0 +1,170 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+use cargo_manifest::Manifest;
+use colored::Colorize;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::{
+    Args, changelog, crate_names, files, git, manifest, manifest_dir, release, release_members,
+    release_tags, report_committed, report_updated, tag_message,
+};
+
+/// `version adopt`: give every manifest without a version `initial`, seed the changelog,
+/// and commit and tag the result, so a project can start releasing with this tool
+pub fn adopt(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    initial: &Version,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let unversioned = unversioned(args, manifest, config)?;
+    if unversioned.is_empty() {
+        anyhow::bail!(
+            "Every manifest already has a version, so there's nothing to adopt (see `version reconcile` to tag it)"
+        );
+    }
+
+    let tags = release_tags(
+        config,
+        &unversioned
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>(),
+        initial,
+    );
+    if let Some(existing) = tags.iter().find(|tag| git::tag_exists(root, tag)) {
+        anyhow::bail!("{existing} already exists");
+    }
+
+    let mut written = Vec::new();
+    for (_, path) in unversioned {
+        let text = files::read(&path)?;
+        let content = insert_version(&text.content, initial).ok_or(anyhow::anyhow!(
+            "{} has no [package] table to add the version to",
+            path.display()
+        ))?;
+        files::write(&path, text.encode(&content)?)?;
+        report_updated(args, &path);
+        written.push(path);
+    }
+
+    let changelog = root.join(args.changelog_path.as_deref().unwrap_or("CHANGELOG.md"));
+    if !changelog.exists() {
+        changelog::seed(&changelog)?;
+        report_updated(args, &changelog);
+        written.push(changelog);
+    }
+
+    let names = crate_names(args, manifest, config)?;
+    let tags = tags
+        .into_iter()
+        .map(|tag| {
+            let name = config
+                .parse_tag(&tag)
+                .map(|(name, _)| name)
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| names.first().cloned().unwrap_or_default());
+            let message = tag_message(args, config, &tag, &name, initial)?;
+            Ok((tag, message))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let message = format!("Adopt version {initial}");
+    release::commit_and_tag(root, &written, &message, &tags, None, false)?;
+    if !args.is_quiet() {
+        report_committed(&message, &tags);
+        eprintln!(
+            "{} Future releases bump from {} with `version`",
+            "ℹ".blue().bold(),
+            initial.to_string().bold()
+        );
+    }
+    Ok(())
+}
+
+/// The crates (by name) and manifests that declare no version at all: the package, or with
+/// `--workspace` every member that neither has one nor inherits the workspace's
+fn unversioned(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    if args.workspace {
+        return Ok(release_members(args, manifest, config)?
+            .into_iter()
+            .filter(|member| member.version.is_none() && !member.inherits_version)
+            .map(|member| (member.name, member.dir.join("Cargo.toml")))
+            .collect());
+    }
+
+    Ok(manifest
+        .package
+        .iter()
+        .filter(|package| package.version.is_none())
+        .map(|package| (package.name.clone(), args.path.clone()))
+        .collect())
+}
+
+/// `content` with `version = "<version>"` added to its `[package]` table, after `name`
+/// when there is one, or `None` if there's no `[package]` table
+fn insert_version(content: &str, version: &Version) -> Option<String> {
+    let Range { start, end } = manifest::table_span(content, "package")?;
+    let table = &content[start..end];
+    let mut offset = table.find('\n').map_or(table.len(), |newline| newline + 1);
+    let mut line_start = 0;
+    for line in table.split_inclusive('\n') {
+        if line
+            .trim_start()
+            .strip_prefix("name")
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+        {
+            offset = line_start + line.len();
+            break;
+        }
+        line_start += line.len();
+    }
+
+    let at = start + offset;
+    let newline = if content[..at].ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    Some(format!(
+        "{}{newline}version = \"{version}\"\n{}",
+        &content[..at],
+        &content[at..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_version() {
+        let version = Version::new(0, 1, 0);
+        assert_eq!(
+            insert_version(
+                "[package]\nname = \"app\"\nedition = \"2024\"\n\n[dependencies]\n",
+                &version
+            )
+            .as_deref(),
+            Some(
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2024\"\n\n[dependencies]\n"
+            )
+        );
+        assert_eq!(
+            insert_version("[package]\nedition = \"2024\"\nname = \"app\"", &version).as_deref(),
+            Some("[package]\nedition = \"2024\"\nname = \"app\"\nversion = \"0.1.0\"\n")
+        );
+        // `namespace` isn't `name`
+        assert_eq!(
+            insert_version("[package]\nnamespace = 1\n", &version).as_deref(),
+            Some("[package]\nversion = \"0.1.0\"\nnamespace = 1\n")
+        );
+        assert_eq!(insert_version("[workspace]\n", &version), None);
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// `[badge]`: a shields.io endpoint file showing the released version, e.g. for a static
+/// docs site (`https://img.shields.io/endpoint?url=<where it's served>`)
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Badge {
+    /// Where to write it, relative to the manifest
+    pub path: PathBuf,
+    /// Left-hand text [default: version]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Color for stable releases [default: blue]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Update it for prereleases too, in `prerelease_color`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prereleases: bool,
+    /// Color for prereleases [default: orange]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prerelease_color: Option<String>,
+}
+
+/// <https://shields.io/badges/endpoint-badge>
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Endpoint<'a> {
+    schema_version: u32,
+    label: &'a str,
+    message: String,
+    color: &'a str,
+}
+
+fn render(badge: &Badge, version: &Version) -> anyhow::Result<String> {
+    let color = if version.pre.is_empty() {
+        badge.color.as_deref().unwrap_or("blue")
+    } else {
+        badge.prerelease_color.as_deref().unwrap_or("orange")
+    };
+    let endpoint = Endpoint {
+        schema_version: 1,
+        label: badge.label.as_deref().unwrap_or("version"),
+        message: version.to_string(),
+        color,
+    };
+    Ok(serde_json::to_string_pretty(&endpoint)? + "\n")
+}
+
+/// Point the badge under `root` at `version`, returning its path, or `None` for a
+/// prerelease when the badge only follows stable releases
+pub fn write(root: &Path, badge: &Badge, version: &Version) -> anyhow::Result<Option<PathBuf>> {
+    if !version.pre.is_empty() && !badge.prereleases {
+        return Ok(None);
+    }
+    let path = root.join(&badge.path);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    crate::cli::files::write(&path, render(badge, version)?)?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let badge = Badge {
+            path: PathBuf::from("docs/badge.json"),
+            label: Some("crates.io".to_string()),
+            color: None,
+            prereleases: true,
+            prerelease_color: None,
+        };
+        assert_eq!(
+            render(&badge, &Version::new(1, 3, 0)).unwrap(),
+            "{\n  \"schemaVersion\": 1,\n  \"label\": \"crates.io\",\n  \"message\": \"1.3.0\",\n  \"color\": \"blue\"\n}\n"
+        );
+        let rc = render(&badge, &Version::parse("1.4.0-rc.1").unwrap()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&rc).unwrap();
+        assert_eq!(value["message"], "1.4.0-rc.1");
+        assert_eq!(value["color"], "orange");
+    }
+}
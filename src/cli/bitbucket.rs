@@ -0,0 +1,104 @@
+//! Bitbucket Cloud's API. Its pull requests have no labels, so only a description's
+//! `release:` directive is read from them, and it has no releases beyond the tag
+
+use serde::Deserialize;
+
+use crate::cli::forge::{NewPullRequest, Opened, PullRequest, Repo};
+use crate::cli::http;
+
+const API: &str = "https://api.bitbucket.org/2.0";
+
+#[derive(Deserialize)]
+struct PullRequestResponse {
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct Links {
+    html: Link,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    id: u64,
+    links: Links,
+}
+
+/// POST `body` to the API at `path` under the repository, returning the response body
+fn post(token: &str, repo: &Repo, path: &str, body: &serde_json::Value) -> anyhow::Result<String> {
+    let url = format!("{API}/repositories/{}/{path}", repo.path);
+    let authorization = format!("Bearer {token}");
+    let headers = [
+        ("Authorization", authorization.as_str()),
+        ("Accept", "application/json"),
+        ("Content-Type", "application/json"),
+    ];
+    Ok(
+        http::success(&url, http::post(&url, &headers, &body.to_string())?)?
+            .body_mut()
+            .read_to_string()?,
+    )
+}
+
+/// Fetch a pull request's description
+pub fn pull_request(token: &str, repo: &Repo, number: u64) -> anyhow::Result<PullRequest> {
+    let url = format!("{API}/repositories/{}/pullrequests/{number}", repo.path);
+    let authorization = format!("Bearer {token}");
+    let headers = [
+        ("Authorization", authorization.as_str()),
+        ("Accept", "application/json"),
+    ];
+    let body = http::success(&url, http::get(&url, &headers)?)?
+        .body_mut()
+        .read_to_string()?;
+
+    let response: PullRequestResponse = serde_json::from_str(&body)?;
+    Ok(PullRequest {
+        description: response.description.unwrap_or_default(),
+        labels: Vec::new(),
+    })
+}
+
+/// Set a successful build status on `sha`, keyed by `context`. Bitbucket requires a link
+/// on every status, so it points at the commit
+pub fn commit_status(
+    token: &str,
+    repo: &Repo,
+    sha: &str,
+    context: &str,
+    description: &str,
+) -> anyhow::Result<()> {
+    let body = serde_json::json!({
+        "state": "SUCCESSFUL",
+        "key": context,
+        "name": context,
+        "description": description,
+        "url": format!("{}/{}/commits/{sha}", repo.host, repo.path),
+    });
+    post(token, repo, &format!("commit/{sha}/statuses/build"), &body)?;
+    Ok(())
+}
+
+/// Open a pull request. Its `labels` are left off, since Bitbucket has none
+pub fn open_pull_request(
+    token: &str,
+    repo: &Repo,
+    request: &NewPullRequest,
+) -> anyhow::Result<Opened> {
+    let body = serde_json::json!({
+        "title": request.title,
+        "description": request.body,
+        "source": { "branch": { "name": request.head } },
+        "destination": { "branch": { "name": request.base } },
+    });
+    let created: Created = serde_json::from_str(&post(token, repo, "pullrequests", &body)?)?;
+    Ok(Opened {
+        number: created.id,
+        url: created.links.html.href,
+    })
+}
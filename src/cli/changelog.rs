@@ -0,0 +1,179 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::cli::git::Commit;
+
+const HEADER: &str = "# Changelog";
+
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+const TRAILER: &str = "Changelog";
+
+/// Render a "## version (date)" section listing each commit subject
+pub fn section(version: &str, date: &str, commits: &[Commit]) -> String {
+    let entries = commits.iter().filter_map(entry).collect::<Vec<_>>();
+
+    let mut out = format!("## {version} ({date})\n\n");
+    if entries.is_empty() {
+        out.push_str("- No changes\n");
+    }
+    for entry in entries {
+        let _ = writeln!(out, "{entry}");
+    }
+    out
+}
+
+/// A commit's changelog line
+fn entry(commit: &Commit) -> Option<String> {
+    Some(format!(
+        "- {} ({})",
+        entry_text(commit)?,
+        commit.short_sha()
+    ))
+}
+
+/// What the changelog says about a commit: a `Changelog: hidden` trailer drops it, any
+/// other value replaces the subject
+pub fn entry_text(commit: &Commit) -> Option<&str> {
+    match commit.trailer(TRAILER) {
+        Some(value) if value.eq_ignore_ascii_case("hidden") => None,
+        Some(value) => Some(value),
+        None => Some(&commit.subject),
+    }
+}
+
+/// Insert `section` below the changelog header, creating the header if it's missing
+fn insert(existing: &str, section: &str) -> String {
+    let rest = existing
+        .strip_prefix(HEADER)
+        .unwrap_or(existing)
+        .trim_start_matches('\n');
+
+    if rest.is_empty() {
+        format!("{HEADER}\n\n{section}")
+    } else {
+        format!("{HEADER}\n\n{section}\n{rest}")
+    }
+}
+
+/// The `## version (date)` section of `changelog`, up to the next section
+pub fn excerpt<'a>(changelog: &'a str, version: &str) -> Option<&'a str> {
+    let heading = format!("## {version} (");
+    let start = changelog
+        .match_indices(&heading)
+        .map(|(start, _)| start)
+        .find(|&start| start == 0 || changelog[..start].ends_with('\n'))?;
+    let rest = &changelog[start..];
+    let end = rest.find("\n## ").map_or(rest.len(), |end| end + 1);
+    Some(rest[..end].trim_end())
+}
+
+/// Start a changelog at `path` with just the header, for the first release to go under
+pub fn seed(path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    crate::cli::files::write(path, format!("{HEADER}\n"))?;
+    Ok(())
+}
+
+pub fn prepend(path: &Path, section: &str) -> anyhow::Result<()> {
+    let existing = if path.exists() {
+        std::fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    crate::cli::files::write(path, insert(&existing, section))?;
+    Ok(())
+}
+
+/// Civil date from a unix timestamp (Howard Hinnant's `civil_from_days`)
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+pub fn format_date(secs: u64) -> String {
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(sha: &str, subject: &str) -> Commit {
+        Commit {
+            sha: sha.to_string(),
+            subject: subject.to_string(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_section() {
+        let commits = [commit("0123456789", "Add thing"), commit("abc", "Fix")];
+        assert_eq!(
+            section("1.2.3", "2024-06-01", &commits),
+            "## 1.2.3 (2024-06-01)\n\n- Add thing (0123456)\n- Fix (abc)\n"
+        );
+    }
+
+    #[test]
+    fn test_section_trailers() {
+        let mut hidden = commit("1111111", "Bump deps");
+        hidden.body = "Changelog: hidden".to_string();
+        let mut renamed = commit("2222222", "fix: thing");
+        renamed.body = "Details\n\nChangelog: Fixed a crash on startup".to_string();
+        assert_eq!(
+            section("1.2.3", "2024-06-01", &[hidden.clone(), renamed]),
+            "## 1.2.3 (2024-06-01)\n\n- Fixed a crash on startup (2222222)\n"
+        );
+        assert_eq!(
+            section("1.2.3", "2024-06-01", &[hidden]),
+            "## 1.2.3 (2024-06-01)\n\n- No changes\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_below_header() {
+        let existing = "# Changelog\n\n## 1.0.0 (2024-01-01)\n\n- Init (abc)\n";
+        assert_eq!(
+            insert(existing, "## 1.0.1 (2024-01-02)\n\n- Fix (def)\n"),
+            "# Changelog\n\n## 1.0.1 (2024-01-02)\n\n- Fix (def)\n\n## 1.0.0 (2024-01-01)\n\n- Init (abc)\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_creates_header() {
+        assert_eq!(insert("", "## 1.0.0\n"), "# Changelog\n\n## 1.0.0\n");
+    }
+
+    #[test]
+    fn test_excerpt() {
+        let changelog = "# Changelog\n\n## 1.0.1 (2024-01-02)\n\n- Fix (def)\n\n## 1.0.0 (2024-01-01)\n\n- Init (abc)\n";
+        assert_eq!(
+            excerpt(changelog, "1.0.1"),
+            Some("## 1.0.1 (2024-01-02)\n\n- Fix (def)")
+        );
+        assert_eq!(
+            excerpt(changelog, "1.0.0"),
+            Some("## 1.0.0 (2024-01-01)\n\n- Init (abc)")
+        );
+        assert_eq!(excerpt(changelog, "1.0"), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1_717_200_000), "2024-06-01");
+    }
+}
@@ -0,0 +1,460 @@
+use std::fmt;
+use std::path::Path;
+
+use cargo_manifest::Manifest;
+use colored::Colorize;
+use schemars::JsonSchema;
+use semver::{Version, VersionReq};
+use serde::Serialize;
+
+use crate::cli::config::Config;
+use crate::cli::versioning::Versioning;
+use crate::cli::workspace::Member;
+use crate::cli::{Args, git, manifest, manifest_dir, output, release_members};
+
+/// Dependency tables whose requirements on other members are checked
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+#[derive(Serialize, JsonSchema)]
+struct Report {
+    schema_version: u32,
+    checks: Vec<Check>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Check {
+    rule: &'static str,
+    /// Where the problem is, for rules about a particular file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<Location>,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+#[derive(Debug, PartialEq, Serialize, JsonSchema)]
+struct Location {
+    /// Relative to the workspace root
+    file: String,
+    /// 1-based
+    line: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(tag = "outcome", content = "message", rename_all = "snake_case")]
+enum Outcome {
+    Pass(String),
+    Fail(String),
+    Skipped(String),
+}
+
+/// JSON Schema for `check --json`
+pub fn schema() -> schemars::Schema {
+    schemars::schema_for!(Report)
+}
+
+/// Run the policy rules enabled in the config, failing if any of them are broken
+pub fn check(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    branch: Option<&str>,
+    default_branch: Option<&str>,
+) -> anyhow::Result<()> {
+    let outcomes = run(args, manifest, config, version, branch, default_branch);
+    if args.json {
+        let failed = failures(&outcomes);
+        let report = Report {
+            schema_version: output::SCHEMA_VERSION,
+            checks: outcomes,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if failed > 0 {
+            anyhow::bail!("{failed} of {} checks failed", report.checks.len());
+        }
+        return Ok(());
+    }
+
+    report(args, &outcomes)
+}
+
+/// `--check-on-skip`: the same rules for the current branch, reported on stderr so the
+/// bump's own output is unchanged
+pub fn check_skipped(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+) -> anyhow::Result<()> {
+    report(args, &run(args, manifest, config, version, None, None))
+}
+
+fn run(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    branch: Option<&str>,
+    default_branch: Option<&str>,
+) -> Vec<Check> {
+    let root = manifest_dir(&args.path);
+    let mut outcomes = Vec::new();
+
+    if config.stable_default_branch {
+        let branch = branch
+            .map(str::to_string)
+            .or_else(|| git::current_branch(root));
+        let default_branch =
+            default_branch.map_or_else(|| git::default_branch(root), str::to_string);
+        outcomes.push(Check {
+            rule: "stable_default_branch",
+            location: None,
+            outcome: stable_default_branch(version, branch.as_deref(), &default_branch),
+        });
+    }
+
+    if let Some(upstream) = &config.upstream {
+        let outcome = match upstream.check(&args.registry_index, version) {
+            Ok(()) => Outcome::Pass(format!("{version} follows upstream {}", upstream.name)),
+            // A registry that can't be reached isn't the version's fault
+            Err(err) if err.downcast_ref::<ureq::Error>().is_some() => Outcome::Skipped(format!(
+                "Couldn't look up upstream {}: {err}",
+                upstream.name
+            )),
+            Err(err) => Outcome::Fail(err.to_string()),
+        };
+        outcomes.push(Check {
+            rule: "upstream",
+            location: None,
+            outcome,
+        });
+    }
+
+    if args.workspace {
+        match release_members(args, manifest, config) {
+            Ok(members) => outcomes.extend(workspace(root, manifest, config, &members)),
+            Err(e) => outcomes.push(Check {
+                rule: "workspace",
+                location: None,
+                outcome: Outcome::Fail(format!("Couldn't read the workspace members: {e}")),
+            }),
+        }
+    }
+
+    outcomes
+}
+
+/// With `--workspace`: every member's version, the requirements members have on each other,
+/// and the release tags, with one failure per problem rather than stopping at the first
+fn workspace(root: &Path, manifest: &Manifest, config: &Config, members: &[Member]) -> Vec<Check> {
+    let workspace_version = manifest
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.package.as_ref())
+        .and_then(|package| package.version.as_deref())
+        .and_then(|version| Version::parse(version).ok());
+    let tags = git::merged_tags(root).unwrap_or_default();
+
+    let mut versions = Vec::new();
+    let mut failures = Vec::new();
+    for member in members {
+        let path = member.dir.join("Cargo.toml");
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let file = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .into_owned();
+        let at = |line| {
+            Some(Location {
+                file: file.clone(),
+                line,
+            })
+        };
+        let version_line = key_line(&content, "package", "version").unwrap_or(1);
+        let mut fail = |rule, location, message| {
+            failures.push(Check {
+                rule,
+                location,
+                outcome: Outcome::Fail(message),
+            });
+        };
+
+        if let Some(mode) = config.versioning
+            && member.inherits_version != (mode == Versioning::Fixed)
+        {
+            let problem = if member.inherits_version {
+                "inherits the workspace version"
+            } else {
+                "declares its own version"
+            };
+            fail(
+                "versioning",
+                at(version_line),
+                format!(
+                    "{} {problem}, but versioning = \"{}\"",
+                    member.name,
+                    mode.name()
+                ),
+            );
+        }
+
+        let version = match (&member.version, member.inherits_version) {
+            (Some(version), _) => match Version::parse(version) {
+                Ok(version) => Some(version),
+                Err(e) => {
+                    fail(
+                        "member_versions",
+                        at(version_line),
+                        format!("{}: invalid version `{version}`: {e}", member.name),
+                    );
+                    None
+                }
+            },
+            (None, true) => workspace_version.clone(),
+            (None, false) => {
+                fail(
+                    "member_versions",
+                    at(key_line(&content, "package", "name").unwrap_or(1)),
+                    format!(
+                        "{} has no version (`version adopt --workspace` adds one)",
+                        member.name
+                    ),
+                );
+                None
+            }
+        };
+
+        if let Some(version) = &version
+            && let Some(tag) = newer_tag(config, &tags, &member.name, version)
+        {
+            fail(
+                "tags",
+                at(version_line),
+                format!(
+                    "{} is at {version}, behind its release tag {tag} (`version reconcile` explains the fix)",
+                    member.name
+                ),
+            );
+        }
+        versions.push((member, version, content, file));
+    }
+
+    failures.extend(requirement_checks(&versions));
+
+    if failures.is_empty() {
+        failures.push(Check {
+            rule: "workspace",
+            location: None,
+            outcome: Outcome::Pass(format!(
+                "{} members have valid versions, requirements, and tags",
+                members.len()
+            )),
+        });
+    }
+    failures
+}
+
+/// The latest release tag for the crate `name`, if it's newer than `version`
+fn newer_tag<'a>(
+    config: &Config,
+    tags: &'a [String],
+    name: &str,
+    version: &Version,
+) -> Option<&'a str> {
+    tags.iter()
+        .filter_map(|tag| config.parse_tag(tag).map(|parsed| (tag, parsed)))
+        .filter(|(_, (tag_name, _))| tag_name.is_empty() || tag_name == name)
+        .max_by(|(_, (_, a)), (_, (_, b))| a.cmp(b))
+        .filter(|(_, (_, tagged))| tagged > version)
+        .map(|(tag, _)| tag.as_str())
+}
+
+/// A failure for each requirement one member has on another that its version doesn't meet
+fn requirement_checks(versions: &[(&Member, Option<Version>, String, String)]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for (member, _, content, file) in versions {
+        for (dependency, requirement, line) in requirements(content) {
+            let Some((_, Some(version), ..)) =
+                versions.iter().find(|(other, ..)| other.name == dependency)
+            else {
+                continue;
+            };
+            let location = Some(Location {
+                file: file.clone(),
+                line,
+            });
+            let message = match VersionReq::parse(&requirement) {
+                Ok(req) if req.matches(version) => continue,
+                Ok(_) => format!(
+                    "{} requires {dependency} `{requirement}`, which {version} doesn't satisfy",
+                    member.name
+                ),
+                Err(e) => format!(
+                    "{} requires {dependency} `{requirement}`, which isn't a valid requirement: {e}",
+                    member.name
+                ),
+            };
+            checks.push(Check {
+                rule: "requirements",
+                location,
+                outcome: Outcome::Fail(message),
+            });
+        }
+    }
+
+    checks
+}
+
+/// 1-based line of `key = ...` in the `[table]` table
+fn key_line(content: &str, table: &str, key: &str) -> Option<usize> {
+    let span = crate::cli::manifest::table_span(content, table)?;
+    let first = content[..span.start].lines().count();
+    content[span]
+        .lines()
+        .position(|line| manifest::is_key(line, key))
+        .map(|index| first + index + 1)
+}
+
+/// `(crate, version requirement, line)` for every dependency with a `version` in the
+/// manifest's dependency tables, naming renamed dependencies by their `package`
+fn requirements(content: &str) -> Vec<(String, String, usize)> {
+    let Ok(manifest) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for table in DEPENDENCY_TABLES {
+        let Some(toml::Value::Table(dependencies)) = manifest.get(table) else {
+            continue;
+        };
+        for (key, dependency) in dependencies {
+            let (name, requirement) = match dependency {
+                toml::Value::String(requirement) => (key.as_str(), requirement),
+                toml::Value::Table(fields) => match fields.get("version") {
+                    Some(toml::Value::String(requirement)) => (
+                        fields
+                            .get("package")
+                            .and_then(toml::Value::as_str)
+                            .unwrap_or(key),
+                        requirement,
+                    ),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let line = key_line(content, table, key)
+                .or_else(|| {
+                    let header = format!("[{table}.{key}]");
+                    content
+                        .lines()
+                        .position(|line| line.trim() == header)
+                        .map(|index| index + 1)
+                })
+                .unwrap_or(1);
+            found.push((name.to_string(), requirement.clone(), line));
+        }
+    }
+    found
+}
+
+fn failures(outcomes: &[Check]) -> usize {
+    outcomes
+        .iter()
+        .filter(|check| matches!(check.outcome, Outcome::Fail(_)))
+        .count()
+}
+
+/// Print each outcome, failing if any rule was broken
+fn report(args: &Args, outcomes: &[Check]) -> anyhow::Result<()> {
+    if outcomes.is_empty() && !args.is_quiet() {
+        eprintln!("No check rules are enabled in the config");
+    }
+
+    for check in outcomes {
+        match &check.outcome {
+            Outcome::Pass(message) if !args.is_quiet() => {
+                eprintln!("{} {message}", "✓".green().bold());
+            }
+            Outcome::Skipped(message) if !args.is_quiet() => {
+                eprintln!("{} {message}", "-".dimmed());
+            }
+            Outcome::Fail(message) => match &check.location {
+                Some(location) => eprintln!("{} {location}: {message}", "✗".red().bold()),
+                None => eprintln!("{} {message}", "✗".red().bold()),
+            },
+            Outcome::Pass(_) | Outcome::Skipped(_) => {}
+        }
+    }
+
+    let failed = failures(outcomes);
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} checks failed", outcomes.len());
+    }
+
+    Ok(())
+}
+
+/// `stable_default_branch`: the default branch only ever holds stable versions
+fn stable_default_branch(version: &Version, branch: Option<&str>, default_branch: &str) -> Outcome {
+    let default_branch = default_branch
+        .strip_prefix("origin/")
+        .unwrap_or(default_branch);
+    match branch {
+        Some(branch) if branch == default_branch => {
+            if version.pre.is_empty() {
+                Outcome::Pass(format!("{default_branch} holds stable version {version}"))
+            } else {
+                Outcome::Fail(format!(
+                    "{default_branch} holds prerelease version {version}; prereleases belong on release branches"
+                ))
+            }
+        }
+        Some(branch) => Outcome::Skipped(format!(
+            "stable_default_branch: on {branch}, not {default_branch}"
+        )),
+        None => Outcome::Skipped("stable_default_branch: HEAD is detached".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requirements() {
+        let content = "[package]\nname = \"app\"\nversion = \"1.0.0\"\n\n[dependencies]\nserde = \"1\"\ncore = { path = \"../core\", version = \"0.2\" }\nlocal = { path = \"../local\" }\n\n[dev-dependencies.helpers]\npackage = \"test-helpers\"\nversion = \"=0.1.0\"\n";
+        assert_eq!(key_line(content, "package", "version"), Some(3));
+        assert_eq!(
+            requirements(content),
+            [
+                ("serde".to_string(), "1".to_string(), 6),
+                ("core".to_string(), "0.2".to_string(), 7),
+                ("test-helpers".to_string(), "=0.1.0".to_string(), 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stable_default_branch() {
+        let stable = Version::new(1, 2, 3);
+        let pre = Version::parse("1.3.0-rc.1").unwrap();
+        assert!(matches!(
+            stable_default_branch(&stable, Some("main"), "origin/main"),
+            Outcome::Pass(_)
+        ));
+        assert!(matches!(
+            stable_default_branch(&pre, Some("main"), "origin/main"),
+            Outcome::Fail(_)
+        ));
+        assert!(matches!(
+            stable_default_branch(&pre, Some("release/1.3"), "main"),
+            Outcome::Skipped(_)
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cli::changelog::{SECONDS_PER_DAY, format_date};
+
+/// Where "now" comes from for everything date-dependent: a fixed instant (`--date` or
+/// `SOURCE_DATE_EPOCH`, for reproducible runs) or the system clock
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    fixed: Option<u64>,
+}
+
+impl Clock {
+    pub fn new(fixed: Option<u64>) -> Self {
+        Self { fixed }
+    }
+
+    /// Seconds since the unix epoch
+    pub fn now(self) -> u64 {
+        self.fixed.unwrap_or_else(system_now)
+    }
+
+    /// The UTC date as YYYY-MM-DD
+    pub fn today(self) -> String {
+        format_date(self.now())
+    }
+
+    /// The UTC time in `format`, where `%Y`, `%m`, `%d`, `%H`, `%M`, and `%S` are the
+    /// zero-padded year, month, day, hour, minute, and second, and `%%` is a `%`
+    pub fn format(self, format: &str) -> String {
+        let now = self.now();
+        let date = format_date(now);
+        let secs = now % SECONDS_PER_DAY;
+        let time = format!("{:02}{:02}{:02}", secs / 3600, secs % 3600 / 60, secs % 60);
+        let mut out = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => out.push_str(&date[..4]),
+                Some('m') => out.push_str(&date[5..7]),
+                Some('d') => out.push_str(&date[8..10]),
+                Some('H') => out.push_str(&time[..2]),
+                Some('M') => out.push_str(&time[2..4]),
+                Some('S') => out.push_str(&time[4..]),
+                Some(other) => {
+                    out.push('%');
+                    if other != '%' {
+                        out.push(other);
+                    }
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+}
+
+/// The system clock's seconds since the unix epoch, whatever `--date` or
+/// `SOURCE_DATE_EPOCH` say. Policy gates (freezes, `min_release_interval`, `rc_soak_days`)
+/// use it, so a date pinned for reproducible output can't let a release through
+pub fn system_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// `--date`: unix seconds (the `SOURCE_DATE_EPOCH` format) or a YYYY-MM-DD date (midnight UTC)
+pub fn parse_date(value: &str) -> Result<u64, String> {
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(secs);
+    }
+
+    let invalid = || format!("expected unix seconds or YYYY-MM-DD, got {value:?}");
+    let parts = value
+        .split('-')
+        .map(|part| part.parse::<i64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let [year, month, day] = parts[..] else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || year < 1970 {
+        return Err(invalid());
+    }
+
+    let days = days_from_civil(year, month, day);
+    let secs = u64::try_from(days).map_err(|_| invalid())? * SECONDS_PER_DAY;
+    // Reject dates like 2024-02-31 that would roll over into the next month
+    if format_date(secs) != format!("{year:04}-{month:02}-{day:02}") {
+        return Err(invalid());
+    }
+    Ok(secs)
+}
+
+/// Days since the unix epoch (Howard Hinnant's `days_from_civil`, the inverse of `format_date`)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1717200000"), Ok(1_717_200_000));
+        assert_eq!(parse_date("2024-06-01"), Ok(1_717_200_000));
+        assert_eq!(parse_date("1970-01-01"), Ok(0));
+        assert_eq!(parse_date("2024-02-29"), Ok(1_709_164_800));
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("yesterday").is_err());
+
+        let clock = Clock::new(Some(parse_date("2024-06-01").unwrap()));
+        assert_eq!(clock.today(), "2024-06-01");
+        assert_eq!(clock.format("%Y%m%d"), "20240601");
+
+        let clock = Clock::new(Some(1_717_200_000 + 13 * 3600 + 5 * 60 + 9));
+        assert_eq!(clock.format("%Y%m%d%H%M%S"), "20240601130509");
+        assert_eq!(clock.format("%Y.%d%%"), "2024.01%");
+    }
+}
@@ -0,0 +1,74 @@
+use std::cmp::Ordering;
+
+use clap::ValueEnum;
+use semver::Version;
+
+/// What `compare --print` writes to stdout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Print {
+    /// `less`, `equal`, or `greater`
+    Relation,
+    /// `<`, `=`, or `>`
+    Symbol,
+}
+
+/// Compare `a` with `b` by semver precedence (build metadata is ignored), printing the
+/// relation if asked, and exit 0, 1, or 2 for less, equal, or greater (3 if either is invalid)
+pub fn compare(a: &str, b: &str, print: Option<Print>) -> ! {
+    let ordering = ordering(a, b).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(3);
+    });
+
+    match print {
+        Some(Print::Relation) => println!("{}", relation(ordering)),
+        Some(Print::Symbol) => println!("{}", symbol(ordering)),
+        None => {}
+    }
+    std::process::exit(match ordering {
+        Ordering::Less => 0,
+        Ordering::Equal => 1,
+        Ordering::Greater => 2,
+    })
+}
+
+fn ordering(a: &str, b: &str) -> anyhow::Result<Ordering> {
+    let parse = |version: &str| {
+        Version::parse(version.trim().trim_start_matches('v'))
+            .map_err(|e| anyhow::anyhow!("`{version}` is not a semver version: {e}"))
+    };
+    Ok(parse(a)?.cmp_precedence(&parse(b)?))
+}
+
+fn relation(ordering: Ordering) -> &'static str {
+    match ordering {
+        Ordering::Less => "less",
+        Ordering::Equal => "equal",
+        Ordering::Greater => "greater",
+    }
+}
+
+fn symbol(ordering: Ordering) -> &'static str {
+    match ordering {
+        Ordering::Less => "<",
+        Ordering::Equal => "=",
+        Ordering::Greater => ">",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordering() {
+        assert_eq!(ordering("1.2.3", "1.10.0").unwrap(), Ordering::Less);
+        assert_eq!(ordering("v1.0.0+a", "1.0.0+b").unwrap(), Ordering::Equal);
+        assert_eq!(ordering("1.0.0", "1.0.0-rc.10").unwrap(), Ordering::Greater);
+        assert_eq!(
+            ordering("1.0.0-rc.2", "1.0.0-rc.10").unwrap(),
+            Ordering::Less
+        );
+        assert!(ordering("1.0", "1.0.0").is_err());
+    }
+}
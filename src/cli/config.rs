@@ -0,0 +1,626 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+pub const FILE_NAME: &str = ".version.toml";
+
+#[allow(clippy::struct_excessive_bools, clippy::struct_field_names)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The config schema the file is written for; older files are migrated as they're read,
+    /// and rewritten by `--write-migrated` [default: 1]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_version: Option<u32>,
+
+    /// Template for release tags, `{version}` and `{name}` expand [default: v{version}]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_format: Option<String>,
+
+    /// The product line `{epoch}` in `tag_format` expands to, e.g. `product-{epoch}/v{version}`
+    /// [default: the highest epoch among the merged release tags, else 1]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u64>,
+
+    /// Annotation for release tags: `{version}`, `{name}`, `{tag}`, `{changelog}` (the
+    /// release's changelog section), and `{artifacts}` (the `--artifact` checksums) expand
+    /// [default: Release {version}, then the checksums]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_message: Option<String>,
+
+    /// One more tag for a `--paths` release, listing every crate it bumped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub umbrella: Option<Umbrella>,
+
+    /// A shields.io endpoint JSON file kept at the released version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub badge: Option<crate::cli::badge::Badge>,
+
+    /// What the 1.0.0 changelog section from `version graduate` opens with [default: The
+    /// first stable release: from here on, breaking changes wait for 2.0.0.]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graduation_note: Option<String>,
+
+    /// Identifier for new prereleases [default: alpha]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_id: Option<String>,
+
+    /// The prerelease channels `version promote` moves a version along, in order, before
+    /// the release [default: alpha, beta, rc]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<String>,
+
+    /// Whether workspace members share the workspace version or keep their own, checked
+    /// before every workspace bump
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub versioning: Option<crate::cli::versioning::Versioning>,
+
+    /// Workspace members (by crate name) left out of changelogs and release output
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
+    /// Path globs (relative to the manifest) for generated or vendored crates that are never
+    /// treated as members, scanned, or edited, e.g. `"vendor/**"`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+
+    /// Paths beyond a member's directory whose commits go in its per-crate changelog,
+    /// keyed by crate name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub commit_paths: BTreeMap<String, CommitPaths>,
+
+    /// Stability tiers by crate name (`stable`, `preview`, or `internal`), ahead of a
+    /// manifest's `[package.metadata.version] tier`; inferred bumps of preview crates stop
+    /// at minor and of internal crates at patch
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tiers: BTreeMap<String, crate::cli::tier::Tier>,
+
+    /// Branch-name globs and the bump a merge from a matching branch gets, e.g.
+    /// `"feature/*" = "minor"`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub branch_bumps: BTreeMap<String, String>,
+
+    /// Which marker decides the bump when prerelease and stable markers are both found
+    /// (`stable`, `prerelease`, or `largest`) [default: stable]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marker_precedence: Option<crate::inference::Precedence>,
+
+    /// The forge pull requests are looked up on and statuses set on (`github`, `gitea`, or
+    /// `bitbucket`) [default: the one the origin remote's host runs]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forge: Option<crate::cli::forge::Kind>,
+
+    /// Commands run after the manifest is bumped
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<Hook>,
+
+    /// `version status` warns once the last release tag is older than this, e.g. "90d"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale_after: Option<String>,
+
+    /// Crates at 0.0.x only get (pre)patch bumps unless one is asked for on the command line
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub experimental_patch_only: bool,
+
+    /// Number prereleases by the commits since the last stable tag, like `--pre-distance`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pre_distance: bool,
+
+    /// Number prereleases past every tag of the same prerelease, on any branch or on
+    /// origin, like `--pre-lineage`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pre_lineage: bool,
+
+    /// Number prereleases by the date in this format (`%Y`, `%m`, `%d`, `%H`, `%M`, `%S`),
+    /// like `--pre-date`, e.g. "%Y%m%d" gives `1.3.0-nightly.20240601`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_date: Option<String>,
+
+    /// After a committed stable release, commit the next patch's `-dev.0` too, like
+    /// `--next-dev`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub next_dev: bool,
+
+    /// Stable bumps record the commit they were cut from and the CI run URL under
+    /// `[package.metadata.release]`, like `--release-metadata`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub release_metadata: bool,
+
+    /// Stable (non-prerelease) bumps fail without `--approve` or `VERSION_RELEASE_APPROVED=1`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub require_approval: bool,
+
+    /// Refuse a stable release this soon after the last release tag, e.g. "1h" or "2d"
+    /// (`s`, `m`, `h`, `d`, and `w` units), unless `--ignore-release-interval` is passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_release_interval: Option<String>,
+
+    /// The most a bump may move each component, e.g. `{ major = 1 }` refuses 1.2.3 → 4.0.0,
+    /// unless `--allow-large-jump` is passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_jump: Option<MaxJump>,
+
+    /// Windows when releases are refused unless `--override-freeze` gives a reason
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub freeze: Vec<crate::cli::freeze::Freeze>,
+
+    /// Days an rc must be tagged before `--require-rc` lets it go stable [default: 0]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rc_soak_days: Option<u64>,
+
+    /// The crate this one is a fork of: bumps (and `version check`) fail unless the version
+    /// matches one of its releases as far as `track` says
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<crate::cli::upstream::Upstream>,
+
+    /// `version check` fails if the default branch holds a prerelease version
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stable_default_branch: bool,
+
+    /// Other files declaring the version, rewritten after the manifest
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sync: Vec<crate::cli::sync::Target>,
+
+    /// Submodules whose pinned release goes in the changelog, optionally required to be a
+    /// tag for stable releases
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub submodules: Vec<crate::cli::submodule::Submodule>,
+
+    /// Policy checks given the planned bump as JSON on stdin, answering on stdout
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<crate::cli::plugin::Plugin>,
+
+    /// Command `--verify-build` runs after the bump [default: cargo check --workspace]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_build: Option<String>,
+
+    /// Homebrew formulas / PKGBUILDs updated by `--packaging` after the release
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub packaging: Vec<crate::cli::packaging::Package>,
+
+    /// Named bundles of settings, selected with `--profile`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub pre_id: Option<String>,
+    pub pre_date: Option<String>,
+    pub tag_format: Option<String>,
+    pub changelog: Option<bool>,
+    pub commit: Option<bool>,
+    pub tag: Option<bool>,
+    pub push: Option<bool>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Umbrella {
+    /// Tag name, `{date}` expands to the release date, e.g. `release-{date}`
+    pub tag: String,
+    /// Leave out the per-crate tags
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub only: bool,
+}
+
+/// `max_jump`: how far a single bump may move the component it changes. A major bump is
+/// measured by the major versions alone, so 1.9.3 → 2.0.0 is a jump of 1
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaxJump {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub major: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minor: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<u64>,
+}
+
+impl MaxJump {
+    /// What's wrong with going from `old` to `new`, if it moves further than allowed
+    pub fn violation(&self, old: &Version, new: &Version) -> Option<String> {
+        let (component, from, to, limit) = if new.major != old.major {
+            ("major", old.major, new.major, self.major)
+        } else if new.minor != old.minor {
+            ("minor", old.minor, new.minor, self.minor)
+        } else {
+            ("patch", old.patch, new.patch, self.patch)
+        };
+        let limit = limit?;
+        let distance = to.saturating_sub(from);
+        (distance > limit).then(|| {
+            format!(
+                "{old} → {new} moves the {component} version by {distance}, over max_jump.{component} ({limit}); pass --allow-large-jump if that's intended"
+            )
+        })
+    }
+}
+
+/// Git pathspecs relative to the workspace root, e.g. `proto/` or `src/generated/*`
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommitPaths {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hook {
+    pub command: String,
+    /// Kill the hook after this long, e.g. "30s" or "5m" (units as for `min_release_interval`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    /// Directory to run in, relative to the manifest [default: the manifest's directory]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// The only environment variables passed through (`PREV_VERSION` and `NEW_VERSION`
+    /// are always set) [default: all of them]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<Vec<String>>,
+    /// What a failing (or timed out) hook does to the release [default: abort]
+    #[serde(default, skip_serializing_if = "OnFailure::is_abort")]
+    pub on_failure: OnFailure,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnFailure {
+    /// Stop the release with an error
+    #[default]
+    Abort,
+    /// Print a warning and carry on
+    Warn,
+}
+
+impl OnFailure {
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn is_abort(&self) -> bool {
+        *self == OnFailure::Abort
+    }
+}
+
+impl Hook {
+    /// `timeout` as a duration
+    pub fn timeout(&self) -> anyhow::Result<Option<std::time::Duration>> {
+        self.timeout
+            .as_deref()
+            .map(|timeout| {
+                parse_interval(timeout)
+                    .map(std::time::Duration::from_secs)
+                    .ok_or(anyhow::anyhow!(
+                        "`{timeout}` is not a valid hook timeout (try \"30s\" or \"5m\")"
+                    ))
+            })
+            .transpose()
+    }
+}
+
+impl Config {
+    /// The config file to read: `path` if given, else `.version.toml` beside the manifest
+    /// if it exists
+    pub fn file(path: Option<&Path>, manifest_dir: &Path) -> Option<PathBuf> {
+        match path {
+            Some(path) => Some(path.to_path_buf()),
+            None => Some(default_path(manifest_dir)).filter(|path| path.exists()),
+        }
+    }
+
+    /// Load `path`, or `.version.toml` beside the manifest if it exists
+    pub fn load(path: Option<&Path>, manifest_dir: &Path) -> anyhow::Result<Self> {
+        let Some(path) = Self::file(path, manifest_dir) else {
+            return Ok(Self::default());
+        };
+
+        let content = std::fs::read_to_string(&path)?;
+        let upgraded = crate::cli::upgrade::upgrade(&content)
+            .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        toml::from_str(&upgraded.content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {e}", path.display()))
+    }
+
+    /// Overlay the named profile's settings, returning it for the caller to apply its flags
+    pub fn apply_profile(&mut self, name: &str) -> anyhow::Result<Profile> {
+        let profile = self.profiles.get(name).cloned().ok_or_else(|| {
+            let known = self.profiles.keys().cloned().collect::<Vec<_>>();
+            anyhow::anyhow!(
+                "Unknown profile `{name}` (configured: {})",
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            )
+        })?;
+
+        if let Some(pre_id) = &profile.pre_id {
+            self.pre_id = Some(pre_id.clone());
+        }
+        if let Some(pre_date) = &profile.pre_date {
+            self.pre_date = Some(pre_date.clone());
+        }
+        if let Some(tag_format) = &profile.tag_format {
+            self.tag_format = Some(tag_format.clone());
+        }
+
+        Ok(profile)
+    }
+
+    pub fn pre_id(&self) -> anyhow::Result<&str> {
+        valid_pre_id(self.pre_id.as_deref().unwrap_or("alpha"))
+    }
+
+    /// `min_release_interval` in seconds
+    pub fn min_release_interval(&self) -> anyhow::Result<Option<u64>> {
+        self.min_release_interval
+            .as_deref()
+            .map(|interval| {
+                parse_interval(interval).ok_or(anyhow::anyhow!(
+                    "`{interval}` is not a valid min_release_interval (try \"30m\", \"1h\", or \"2d\")"
+                ))
+            })
+            .transpose()
+    }
+
+    /// `stale_after` in whole days
+    pub fn stale_after_days(&self) -> anyhow::Result<Option<u64>> {
+        self.stale_after
+            .as_deref()
+            .map(|interval| {
+                parse_interval(interval)
+                    .map(|seconds| seconds / crate::cli::changelog::SECONDS_PER_DAY)
+                    .ok_or(anyhow::anyhow!(
+                        "`{interval}` is not a valid stale_after (try \"90d\" or \"12w\")"
+                    ))
+            })
+            .transpose()
+    }
+
+    pub fn tag_format(&self) -> &str {
+        self.tag_format.as_deref().unwrap_or("v{version}")
+    }
+
+    pub fn tag(&self, name: &str, version: &semver::Version) -> String {
+        self.tag_format()
+            .replace("{name}", name)
+            .replace("{epoch}", &self.epoch.unwrap_or(1).to_string())
+            .replace("{version}", &version.to_string())
+    }
+
+    /// Settle the `{epoch}` new tags get: the configured one, else the highest among `tags`,
+    /// moved on by one for `--epoch-bump`
+    pub fn resolve_epoch(&mut self, tags: &[String], bump: bool) -> anyhow::Result<()> {
+        if !self.tag_format().contains("{epoch}") {
+            if bump {
+                anyhow::bail!("--epoch-bump needs `{{epoch}}` in tag_format");
+            }
+            return Ok(());
+        }
+        if bump && let Some(epoch) = self.epoch {
+            anyhow::bail!(
+                "epoch = {epoch} is set in the config; raise it there instead of passing --epoch-bump"
+            );
+        }
+        let current = self
+            .epoch
+            .or_else(|| tags.iter().filter_map(|tag| self.tag_epoch(tag)).max())
+            .unwrap_or(1);
+        self.epoch = Some(current + u64::from(bump));
+        Ok(())
+    }
+
+    /// The pathspecs selecting the commits for the member `name` in `dir` (relative to the
+    /// workspace root): its directory, plus and minus its `commit_paths`
+    pub fn commit_pathspecs(&self, name: &str, dir: &Path) -> Vec<String> {
+        let dir = dir.to_string_lossy();
+        let mut pathspecs = vec![if dir.is_empty() {
+            ".".to_string()
+        } else {
+            dir.into_owned()
+        }];
+        if let Some(paths) = self.commit_paths.get(name) {
+            pathspecs.extend(paths.include.iter().cloned());
+            pathspecs.extend(paths.exclude.iter().map(|path| format!(":(exclude){path}")));
+        }
+        pathspecs
+    }
+
+    /// Recover the version from a tag produced by `tag_format`
+    pub fn version_from_tag(&self, tag: &str) -> Option<semver::Version> {
+        self.parse_tag(tag).map(|(_, version)| version)
+    }
+
+    /// Recover the crate name (empty if the format has none) and version from a tag, whatever
+    /// its epoch
+    pub fn parse_tag(&self, tag: &str) -> Option<(String, semver::Version)> {
+        let captures = self.tag_pattern()?.captures(tag)?;
+        let name = captures
+            .name("name")
+            .map_or_else(String::new, |name| name.as_str().to_string());
+        Some((name, semver::Version::parse(&captures["version"]).ok()?))
+    }
+
+    /// The `{epoch}` of a tag produced by `tag_format`
+    pub fn tag_epoch(&self, tag: &str) -> Option<u64> {
+        self.tag_pattern()?
+            .captures(tag)?
+            .name("epoch")?
+            .as_str()
+            .parse()
+            .ok()
+    }
+
+    fn tag_pattern(&self) -> Option<regex::Regex> {
+        let pattern = regex::escape(self.tag_format())
+            .replace(r"\{version\}", "(?P<version>.+)")
+            .replace(r"\{name\}", "(?P<name>.+?)")
+            .replace(r"\{epoch\}", r"(?P<epoch>\d+)");
+        regex::Regex::new(&format!("^{pattern}$")).ok()
+    }
+}
+
+/// `pre_id`, if `<pre_id>.0` is a valid prerelease
+pub fn valid_pre_id(pre_id: &str) -> anyhow::Result<&str> {
+    if pre_id.is_empty() || semver::Prerelease::new(&format!("{pre_id}.0")).is_err() {
+        anyhow::bail!("`{pre_id}` is not a valid prerelease identifier");
+    }
+    Ok(pre_id)
+}
+
+pub fn default_path(manifest_dir: &Path) -> PathBuf {
+    manifest_dir.join(FILE_NAME)
+}
+
+/// Seconds in an interval like "90s", "15m", "1h", "2d", or "1w"
+fn parse_interval(interval: &str) -> Option<u64> {
+    let interval = interval.trim();
+    let split = interval.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = interval.split_at(split);
+    let unit = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    count.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_round_trip() {
+        let config = Config {
+            tag_format: Some("{name}-v{version}".to_string()),
+            ..Config::default()
+        };
+        let version = semver::Version::parse("1.2.3-alpha.0").unwrap();
+
+        let tag = config.tag("my-crate", &version);
+        assert_eq!(tag, "my-crate-v1.2.3-alpha.0");
+        assert_eq!(config.version_from_tag(&tag), Some(version.clone()));
+        assert_eq!(config.version_from_tag("v1.2.3"), None);
+        assert_eq!(
+            config.parse_tag(&tag),
+            Some(("my-crate".to_string(), version))
+        );
+    }
+
+    #[test]
+    fn test_epoch() {
+        let mut config = Config {
+            tag_format: Some("product-{epoch}/v{version}".to_string()),
+            ..Config::default()
+        };
+        let tags = ["product-1/v1.0.0", "product-2/v1.4.0", "v0.9.0"].map(String::from);
+        assert_eq!(config.tag_epoch("product-2/v1.4.0"), Some(2));
+        assert_eq!(
+            config.version_from_tag("product-1/v1.0.0"),
+            Some(semver::Version::new(1, 0, 0))
+        );
+
+        config.resolve_epoch(&tags, false).unwrap();
+        assert_eq!(
+            config.tag("", &semver::Version::new(1, 5, 0)),
+            "product-2/v1.5.0"
+        );
+        config.epoch = None;
+        config.resolve_epoch(&tags, true).unwrap();
+        assert_eq!(config.epoch, Some(3));
+        assert!(config.resolve_epoch(&tags, true).is_err());
+
+        config.epoch = None;
+        config.resolve_epoch(&[], false).unwrap();
+        assert_eq!(config.epoch, Some(1));
+        assert!(Config::default().resolve_epoch(&tags, true).is_err());
+    }
+
+    #[test]
+    fn test_default_tag_format() {
+        let config = Config::default();
+        assert_eq!(
+            config.version_from_tag("v1.2.3"),
+            Some(semver::Version::new(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        let mut config: Config = toml::from_str(
+            "tag_format = \"v{version}\"\n\n[profiles.nightly]\npre_id = \"nightly\"\ntag_format = \"nightly-{version}\"\npush = true\n",
+        )
+        .unwrap();
+
+        let profile = config.apply_profile("nightly").unwrap();
+        assert_eq!(profile.push, Some(true));
+        assert_eq!(config.pre_id().unwrap(), "nightly");
+        assert_eq!(config.tag_format(), "nightly-{version}");
+        assert!(config.apply_profile("stable").is_err());
+    }
+
+    #[test]
+    fn test_commit_pathspecs() {
+        let config: Config = toml::from_str(
+            "[commit_paths.api]\ninclude = [\"proto/\"]\nexclude = [\"crates/api/generated/\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.commit_pathspecs("api", Path::new("crates/api")),
+            ["crates/api", "proto/", ":(exclude)crates/api/generated/"]
+        );
+        assert_eq!(config.commit_pathspecs("cli", Path::new("")), ["."]);
+    }
+
+    #[test]
+    fn test_max_jump() {
+        let max_jump: MaxJump = toml::from_str("major = 1\nminor = 5\n").unwrap();
+        let v = |s| Version::parse(s).unwrap();
+        let jump = |old, new| max_jump.violation(&v(old), &v(new));
+
+        assert_eq!(jump("1.9.3", "2.0.0"), None);
+        assert_eq!(
+            jump("1.2.3", "4.0.0").as_deref(),
+            Some(
+                "1.2.3 → 4.0.0 moves the major version by 3, over max_jump.major (1); pass --allow-large-jump if that's intended"
+            )
+        );
+        assert_eq!(jump("1.2.3", "1.7.0"), None);
+        assert!(jump("1.2.3", "1.8.0").is_some());
+        // No patch limit configured
+        assert_eq!(jump("1.2.3", "1.2.300"), None);
+    }
+
+    #[test]
+    fn test_invalid_pre_id() {
+        let config = Config {
+            pre_id: Some("not valid".to_string()),
+            ..Config::default()
+        };
+        assert!(config.pre_id().is_err());
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("90s"), Some(90));
+        assert_eq!(parse_interval("1h"), Some(3600));
+        assert_eq!(parse_interval(" 2 d"), Some(172_800));
+        assert_eq!(parse_interval("1w"), Some(604_800));
+        assert_eq!(parse_interval("h"), None);
+        assert_eq!(parse_interval("10"), None);
+        assert_eq!(parse_interval("1y"), None);
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected() {
+        assert!(toml::from_str::<Config>("tag_fromat = \"v{version}\"").is_err());
+    }
+}
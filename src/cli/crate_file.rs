@@ -0,0 +1,161 @@
+//! Reading a published `.crate` file: a gzipped tarball of `<name>-<version>/...`, small
+//! enough (crates.io caps them at 10 MB) to unpack in memory
+
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+
+const BLOCK: usize = 512;
+
+/// The files of a package, by their path inside it (`Cargo.toml.orig`, `src/lib.rs`, ...)
+#[derive(Debug, Default)]
+pub struct Package {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+/// `.cargo_vcs_info.json`, which `cargo package` writes when packaging from git
+#[derive(Debug, Deserialize)]
+pub struct VcsInfo {
+    pub git: Git,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Git {
+    pub sha1: String,
+    #[serde(default)]
+    pub dirty: bool,
+}
+
+impl Package {
+    /// The text of the file at `path`, if the package has it
+    pub fn file(&self, path: &str) -> Option<&str> {
+        std::str::from_utf8(self.files.get(path)?).ok()
+    }
+
+    pub fn vcs_info(&self) -> anyhow::Result<Option<VcsInfo>> {
+        self.file(".cargo_vcs_info.json")
+            .map(|content| {
+                serde_json::from_str(content)
+                    .map_err(|e| anyhow::anyhow!("Malformed .cargo_vcs_info.json: {e}"))
+            })
+            .transpose()
+    }
+}
+
+/// A NUL-terminated header field
+fn field(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// `path` records of a pax extended header (`"<len> path=<value>\n"`)
+fn pax_path(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    text.lines().find_map(|record| {
+        let (_, record) = record.split_once(' ')?;
+        record.strip_prefix("path=").map(str::to_string)
+    })
+}
+
+/// Unpack a `.crate` file
+pub fn read(bytes: &[u8]) -> anyhow::Result<Package> {
+    let mut tar = Vec::new();
+    GzDecoder::new(bytes)
+        .read_to_end(&mut tar)
+        .map_err(|e| anyhow::anyhow!("Not a .crate file (gzip): {e}"))?;
+
+    let mut package = Package::default();
+    // A GNU long name or pax path applies to the entry after it
+    let mut long_name = None;
+    let mut offset = 0;
+    while offset + BLOCK <= tar.len() {
+        let header = &tar[offset..offset + BLOCK];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let size = usize::from_str_radix(field(&header[124..136]).trim(), 8)
+            .map_err(|_| anyhow::anyhow!("Not a .crate file: malformed tar header"))?;
+        let start = offset + BLOCK;
+        let data = tar
+            .get(start..start + size)
+            .ok_or(anyhow::anyhow!("Not a .crate file: truncated"))?;
+        offset = start + size.div_ceil(BLOCK) * BLOCK;
+
+        match header[156] {
+            b'L' => long_name = Some(field(data)),
+            b'x' => long_name = pax_path(data),
+            b'0' | 0 => {
+                let path = long_name.take().unwrap_or_else(|| {
+                    let name = field(&header[..100]);
+                    match field(&header[345..500]) {
+                        prefix if header[257..262] == *b"ustar" && !prefix.is_empty() => {
+                            format!("{prefix}/{name}")
+                        }
+                        _ => name,
+                    }
+                });
+                // Everything sits under `<name>-<version>/`
+                if let Some((_, path)) = path.split_once('/') {
+                    package.files.insert(path.to_string(), data.to_vec());
+                }
+            }
+            _ => long_name = None,
+        }
+    }
+    Ok(package)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    use super::*;
+
+    fn entry(tar: &mut Vec<u8>, kind: u8, name: &str, data: &[u8]) {
+        let mut header = [0u8; BLOCK];
+        let name = name.as_bytes();
+        header[..name.len().min(100)].copy_from_slice(&name[..name.len().min(100)]);
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        tar.extend_from_slice(&header);
+        tar.extend_from_slice(data);
+        tar.resize(tar.len().div_ceil(BLOCK) * BLOCK, 0);
+    }
+
+    #[test]
+    fn test_read() {
+        let long = format!("app-1.3.0/{}/mod.rs", "nested".repeat(20));
+        let mut tar = Vec::new();
+        entry(&mut tar, b'0', "app-1.3.0/Cargo.toml.orig", b"[package]\n");
+        entry(
+            &mut tar,
+            b'0',
+            "app-1.3.0/.cargo_vcs_info.json",
+            br#"{"git": {"sha1": "abc123"}, "path_in_vcs": "crates/app"}"#,
+        );
+        entry(&mut tar, b'L', "././@LongLink", long.as_bytes());
+        entry(&mut tar, b'0', "app-1.3.0/truncated", b"pub fn f() {}\n");
+        tar.extend_from_slice(&[0; BLOCK * 2]);
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(&tar).unwrap();
+
+        let package = read(&gz.finish().unwrap()).unwrap();
+        assert_eq!(package.file("Cargo.toml.orig"), Some("[package]\n"));
+        assert_eq!(
+            package.file(long.split_once('/').unwrap().1),
+            Some("pub fn f() {}\n")
+        );
+        assert_eq!(package.file("Cargo.toml"), None);
+        let vcs = package.vcs_info().unwrap().unwrap();
+        assert_eq!(vcs.git.sha1, "abc123");
+        assert!(!vcs.git.dirty);
+
+        assert!(read(b"not gzip").is_err());
+    }
+}
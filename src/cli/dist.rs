@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use semver::Version;
+use serde::Serialize;
+
+/// The subset of cargo-dist's `dist-manifest.json` that describes what's being announced
+#[derive(Serialize)]
+struct DistManifest {
+    announcement_tag: String,
+    announcement_is_prerelease: bool,
+    releases: Vec<Release>,
+}
+
+#[derive(Serialize)]
+struct Release {
+    app_name: String,
+    app_version: String,
+}
+
+fn render(version: &Version, tag: String, app_names: Vec<String>) -> anyhow::Result<String> {
+    let manifest = DistManifest {
+        announcement_tag: tag,
+        announcement_is_prerelease: !version.pre.is_empty(),
+        releases: app_names
+            .into_iter()
+            .map(|app_name| Release {
+                app_name,
+                app_version: version.to_string(),
+            })
+            .collect(),
+    };
+
+    Ok(serde_json::to_string_pretty(&manifest)? + "\n")
+}
+
+/// Write a dist-manifest snippet announcing `version` under `tag` for each app
+pub fn write(
+    path: &Path,
+    version: &Version,
+    tag: String,
+    app_names: Vec<String>,
+) -> anyhow::Result<()> {
+    crate::cli::files::write(path, render(version, tag, app_names)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let version = Version::parse("1.3.0-alpha.0").unwrap();
+        let tag = "v1.3.0-alpha.0".to_string();
+        let rendered = render(&version, tag, vec!["a".to_string()]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        assert_eq!(value["announcement_tag"], "v1.3.0-alpha.0");
+        assert_eq!(value["announcement_is_prerelease"], true);
+        assert_eq!(value["releases"][0]["app_name"], "a");
+        assert_eq!(value["releases"][0]["app_version"], "1.3.0-alpha.0");
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt::Write;
+
+use semver::Version;
+
+/// `export` lines for the version and its components, for `eval "$(version env)"`
+pub fn render(version: &Version) -> String {
+    // Every character semver allows is shell-safe, so nothing needs quoting
+    let mut exports = String::new();
+    for (name, value) in [
+        ("VERSION", version.to_string()),
+        ("VERSION_MAJOR", version.major.to_string()),
+        ("VERSION_MINOR", version.minor.to_string()),
+        ("VERSION_PATCH", version.patch.to_string()),
+        ("VERSION_PRE", version.pre.to_string()),
+    ] {
+        let _ = writeln!(exports, "export {name}={value}");
+    }
+    exports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(&Version::parse("1.3.0-rc.1+ci.5").unwrap()),
+            "export VERSION=1.3.0-rc.1+ci.5\nexport VERSION_MAJOR=1\nexport VERSION_MINOR=3\n\
+             export VERSION_PATCH=0\nexport VERSION_PRE=rc.1\n"
+        );
+    }
+}
@@ -0,0 +1,527 @@
+use cargo_manifest::Manifest;
+use colored::Colorize;
+use serde::Deserializer;
+use serde::de::{DeserializeOwned, Visitor};
+
+use crate::cli::config::{CommitPaths, Config, Hook, MaxJump, Profile, Umbrella};
+use crate::cli::{
+    Args, badge, freeze, manifest_dir, packaging, plugin, promote, release_members, scan,
+    submodule, sync, upgrade, upstream, versioning,
+};
+use crate::inference;
+
+/// Settings copied from cargo-release's `release.toml`, and what does their job here
+const CARGO_RELEASE: [(&str, &str); 6] = [
+    (
+        "tag-name",
+        "`tag_format` (its `{{crate_name}}` is `{name}` here)",
+    ),
+    ("tag-prefix", "`tag_format`, e.g. \"{name}-v{version}\""),
+    ("tag-message", "`tag_message`"),
+    ("pre-release-hook", "a `[[hooks]]` entry"),
+    ("pre-release-replacements", "a `[[sync]]` entry"),
+    (
+        "allow-branch",
+        "`version check` with `stable_default_branch`",
+    ),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The config can't be used as written
+    Error,
+    /// It works, but probably not as intended
+    Warning,
+}
+
+/// One problem with the config: where it is and what to do about it
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Dotted path to the setting, empty for the file as a whole
+    pub key: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+
+    fn print(&self) {
+        let symbol = match self.severity {
+            Severity::Error => "✗".red().bold(),
+            Severity::Warning => "⚠".yellow().bold(),
+        };
+        if self.key.is_empty() {
+            eprintln!("{symbol} {}", self.message);
+        } else {
+            eprintln!("{symbol} {}: {}", self.key.bold(), self.message);
+        }
+    }
+}
+
+/// `version explain-config`: report every problem in the config, failing on errors (and
+/// with `strict`, on warnings too)
+pub fn explain_config(args: &Args, manifest: &Manifest, strict: bool) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let Some(path) = Config::file(args.config.as_deref(), root) else {
+        if !args.is_quiet() {
+            eprintln!(
+                "{} No {} beside the manifest, so every setting is at its default",
+                "ℹ".blue().bold(),
+                crate::cli::config::FILE_NAME
+            );
+        }
+        return Ok(());
+    };
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+
+    let mut diagnostics = diagnose(&content);
+    if let Ok(upgraded) = upgrade::upgrade(&content)
+        && let Ok(config) = toml::from_str::<Config>(&upgraded.content)
+        && let Some(mode) = config.versioning
+        && manifest.workspace.is_some()
+    {
+        let members = release_members(args, manifest, &config)?;
+        if let Err(e) = versioning::check(&members, mode) {
+            diagnostics.push(Diagnostic::error("versioning", e.to_string()));
+        }
+    }
+
+    for diagnostic in &diagnostics {
+        diagnostic.print();
+    }
+    let errors = count(&diagnostics, Severity::Error);
+    let warnings = count(&diagnostics, Severity::Warning);
+    if errors > 0 || (strict && warnings > 0) {
+        anyhow::bail!(
+            "{} has {errors} error(s) and {warnings} warning(s)",
+            path.display()
+        );
+    }
+    if !args.is_quiet() {
+        eprintln!(
+            "{} {} is valid{}",
+            "✓".green().bold(),
+            path.display(),
+            if warnings > 0 {
+                format!(" ({warnings} warning(s))")
+            } else {
+                String::new()
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Before the config is loaded: print its warnings, and fail with every error at once rather
+/// than the first one serde trips on
+pub fn check_on_startup(args: &Args) -> anyhow::Result<()> {
+    let Some(path) = Config::file(args.config.as_deref(), manifest_dir(&args.path)) else {
+        return Ok(());
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        // Config::load reports it
+        return Ok(());
+    };
+
+    let diagnostics = diagnose(&content);
+    let errors = count(&diagnostics, Severity::Error);
+    for diagnostic in &diagnostics {
+        if diagnostic.severity == Severity::Error || !args.is_quiet() {
+            diagnostic.print();
+        }
+    }
+    if errors > 0 {
+        anyhow::bail!(
+            "{} has {errors} error(s); run `version explain-config` to check it",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn count(diagnostics: &[Diagnostic], severity: Severity) -> usize {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == severity)
+        .count()
+}
+
+/// Every problem with the config file `content`: syntax, unknown keys, values that don't
+/// parse, and settings that contradict each other
+pub fn diagnose(content: &str) -> Vec<Diagnostic> {
+    if let Err(e) = content.parse::<toml::Table>() {
+        return vec![Diagnostic::error("", e.message().to_string())];
+    }
+
+    let upgraded = match upgrade::upgrade(content) {
+        Ok(upgraded) => upgraded,
+        Err(e) => return vec![Diagnostic::error("config_version", e.to_string())],
+    };
+    let mut diagnostics = Vec::new();
+    if !upgraded.changes.is_empty() {
+        diagnostics.push(Diagnostic::warning(
+            "config_version",
+            format!(
+                "written for config_version {}, so it was migrated as it was read ({}); pass --write-migrated to update the file",
+                upgraded.from,
+                upgraded.changes.join("; ")
+            ),
+        ));
+    }
+    let table = match upgraded.content.parse::<toml::Table>() {
+        Ok(table) => table,
+        Err(e) => return vec![Diagnostic::error("", e.message().to_string())],
+    };
+    unknown_keys(&table, &mut diagnostics);
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return diagnostics;
+    }
+
+    match toml::from_str::<Config>(&upgraded.content) {
+        Ok(config) => {
+            values(&config, &mut diagnostics);
+            conflicts(&config, &mut diagnostics);
+        }
+        Err(e) => diagnostics.push(Diagnostic::error("", e.message().to_string())),
+    }
+    diagnostics
+}
+
+fn unknown_keys(table: &toml::Table, diagnostics: &mut Vec<Diagnostic>) {
+    check_keys(table, "", fields::<Config>(), diagnostics);
+
+    let nested: [(&str, &[&str]); 4] = [
+        ("umbrella", fields::<Umbrella>()),
+        ("badge", fields::<badge::Badge>()),
+        ("max_jump", fields::<MaxJump>()),
+        ("upstream", fields::<upstream::Upstream>()),
+    ];
+    for (key, expected) in nested {
+        if let Some(toml::Value::Table(nested)) = table.get(key) {
+            check_keys(nested, key, expected, diagnostics);
+        }
+    }
+
+    let maps: [(&str, &[&str]); 2] = [
+        ("profiles", fields::<Profile>()),
+        ("commit_paths", fields::<CommitPaths>()),
+    ];
+    for (key, expected) in maps {
+        let Some(toml::Value::Table(map)) = table.get(key) else {
+            continue;
+        };
+        for (name, value) in map {
+            if let toml::Value::Table(nested) = value {
+                check_keys(nested, &format!("{key}.{name}"), expected, diagnostics);
+            }
+        }
+    }
+
+    let arrays: [(&str, &[&str]); 6] = [
+        ("hooks", fields::<Hook>()),
+        ("freeze", fields::<freeze::Freeze>()),
+        ("submodules", fields::<submodule::Submodule>()),
+        ("sync", fields::<sync::Target>()),
+        ("plugins", fields::<plugin::Plugin>()),
+        ("packaging", fields::<packaging::Package>()),
+    ];
+    for (key, expected) in arrays {
+        let Some(toml::Value::Array(entries)) = table.get(key) else {
+            continue;
+        };
+        for (index, entry) in entries.iter().enumerate() {
+            if let toml::Value::Table(nested) = entry {
+                check_keys(nested, &format!("{key}[{index}]"), expected, diagnostics);
+            }
+        }
+    }
+}
+
+fn check_keys(
+    table: &toml::Table,
+    prefix: &str,
+    expected: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for key in table.keys().filter(|key| !expected.contains(&key.as_str())) {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        let replacement = CARGO_RELEASE
+            .iter()
+            .find(|(name, _)| prefix.is_empty() && name == key);
+        let message = match (replacement, closest(key, expected)) {
+            (Some((_, replacement)), _) => format!(
+                "cargo-release's setting isn't read here; use {replacement} (`version migrate --from cargo-release` translates a release.toml)"
+            ),
+            (None, Some(suggestion)) => {
+                format!("unknown setting; did you mean `{suggestion}`?")
+            }
+            (None, None) if prefix.is_empty() => "unknown setting".to_string(),
+            (None, None) => format!("unknown setting (expected {})", expected.join(", ")),
+        };
+        diagnostics.push(Diagnostic::error(path, message));
+    }
+}
+
+/// The expected key `key` is most likely a typo of
+fn closest<'a>(key: &str, expected: &[&'a str]) -> Option<&'a str> {
+    let normalized = key.to_ascii_lowercase().replace('-', "_");
+    expected
+        .iter()
+        .map(|candidate| (edit_distance(&normalized, candidate), *candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between `a` and `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Values that parse as TOML but not as what they're for
+fn values(config: &Config, diagnostics: &mut Vec<Diagnostic>) {
+    let mut check = |key: &str, result: anyhow::Result<()>| {
+        if let Err(e) = result {
+            diagnostics.push(Diagnostic::error(key, e.to_string()));
+        }
+    };
+    check("pre_id", config.pre_id().map(|_| ()));
+    check("channels", promote::channels(config).map(|_| ()));
+    check("stale_after", config.stale_after_days().map(|_| ()));
+    check(
+        "min_release_interval",
+        config.min_release_interval().map(|_| ()),
+    );
+    for (index, hook) in config.hooks.iter().enumerate() {
+        check(
+            &format!("hooks[{index}].timeout"),
+            hook.timeout().map(|_| ()),
+        );
+    }
+    for (index, window) in config.freeze.iter().enumerate() {
+        check(
+            &format!("freeze[{index}].window"),
+            freeze::blocking(
+                std::slice::from_ref(window),
+                0,
+                &semver::Version::new(0, 0, 0),
+            )
+            .map(|_| ()),
+        );
+    }
+    if !config.tag_format().contains("{version}") {
+        check(
+            "tag_format",
+            Err(anyhow::anyhow!(
+                "`{}` has no `{{version}}`, so every release would get the same tag",
+                config.tag_format()
+            )),
+        );
+    }
+    for (pattern, bump) in &config.branch_bumps {
+        let key = format!("branch_bumps.\"{pattern}\"");
+        if let Err(e) = glob::Pattern::new(pattern) {
+            check(
+                &key,
+                Err(anyhow::anyhow!("`{pattern}` isn't a valid glob: {e}")),
+            );
+        } else if !inference::MARKERS.iter().any(|(name, _)| name == bump) {
+            let names = inference::MARKERS.map(|(name, _)| name).join(", ");
+            check(
+                &key,
+                Err(anyhow::anyhow!(
+                    "`{bump}` isn't a bump (expected one of {names})"
+                )),
+            );
+        }
+    }
+
+    for (index, pattern) in config.ignore.iter().enumerate() {
+        if let Err(e) = glob::Pattern::new(pattern) {
+            check(
+                &format!("ignore[{index}]"),
+                Err(anyhow::anyhow!("`{pattern}` isn't a valid glob: {e}")),
+            );
+        }
+    }
+}
+
+/// Settings that are fine alone but contradict each other
+fn conflicts(config: &Config, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, target) in config.sync.iter().enumerate() {
+        if scan::ignored(&config.ignore, &target.path) {
+            diagnostics.push(Diagnostic::error(
+                format!("sync[{index}].path"),
+                format!(
+                    "{} is under an `ignore`d path, so it can't be rewritten",
+                    target.path.display()
+                ),
+            ));
+        }
+    }
+    if config.pre_distance && config.pre_date.is_some() {
+        diagnostics.push(Diagnostic::error(
+            "pre_date",
+            "pre_date and pre_distance both number prereleases; keep one",
+        ));
+    }
+    if config.pre_lineage && (config.pre_distance || config.pre_date.is_some()) {
+        diagnostics.push(Diagnostic::error(
+            "pre_lineage",
+            "pre_lineage numbers prereleases past their tags, so it can't be combined with pre_date or pre_distance",
+        ));
+    }
+    for (name, profile) in &config.profiles {
+        if config.pre_distance && profile.pre_date.is_some() {
+            diagnostics.push(Diagnostic::warning(
+                format!("profiles.{name}.pre_date"),
+                "pre_distance is also set, so bumps with this profile fail; set pre_distance per run with --pre-distance instead",
+            ));
+        }
+    }
+
+    if config.versioning == Some(versioning::Versioning::Independent)
+        && !config.tag_format().contains("{name}")
+    {
+        diagnostics.push(Diagnostic::warning(
+            "tag_format",
+            format!(
+                "versioning = \"independent\", but `{}` has no `{{name}}`, so members releasing the same version would share a tag",
+                config.tag_format()
+            ),
+        ));
+    }
+    if config.versioning == Some(versioning::Versioning::Fixed) && config.umbrella.is_some() {
+        diagnostics.push(Diagnostic::warning(
+            "umbrella",
+            "versioning = \"fixed\" releases every member together, so `--paths` (the only user of the umbrella tag) can't release them separately",
+        ));
+    }
+}
+
+/// The field names `T` deserializes, read off the `deserialize_struct` call its derive makes
+fn fields<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(Fields(&mut fields));
+    fields
+}
+
+struct Fields<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for Fields<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(serde::de::Error::custom("only the field names are needed"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose() {
+        assert_eq!(diagnose("tag_format = \"v{version}\"\n"), []);
+        assert_eq!(
+            diagnose(
+                "tag-format = \"v{version}\"\ntag-name = \"v{{version}}\"\n[[hooks]]\nrun = \"true\"\n"
+            ),
+            [
+                Diagnostic::error("tag-format", "unknown setting; did you mean `tag_format`?"),
+                Diagnostic::error(
+                    "tag-name",
+                    "cargo-release's setting isn't read here; use `tag_format` (its `{{crate_name}}` is `{name}` here) (`version migrate --from cargo-release` translates a release.toml)"
+                ),
+                Diagnostic::error(
+                    "hooks[0].run",
+                    "unknown setting (expected command, timeout, cwd, env, on_failure)"
+                ),
+            ]
+        );
+
+        let diagnostics = diagnose(
+            "pre_distance = true\npre_date = \"%Y%m%d\"\nmin_release_interval = \"soon\"\nversioning = \"independent\"\nignore = [\"vendor/**\", \"[\"]\n[[sync]]\npath = \"vendor/app/MODULE.bazel\"\n[branch_bumps]\n\"feature/*\" = \"minr\"\n",
+        );
+        let keys = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.key.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                (Severity::Error, "min_release_interval"),
+                (Severity::Error, "branch_bumps.\"feature/*\""),
+                (Severity::Error, "ignore[1]"),
+                (Severity::Error, "sync[0].path"),
+                (Severity::Error, "pre_date"),
+                (Severity::Warning, "tag_format"),
+            ]
+        );
+        assert_eq!(diagnose("tag_format = [").len(), 1);
+        assert_eq!(
+            diagnose("stale_after_days = 30\n")
+                .iter()
+                .map(|diagnostic| (diagnostic.severity, diagnostic.key.as_str()))
+                .collect::<Vec<_>>(),
+            [(Severity::Warning, "config_version")]
+        );
+    }
+
+    #[test]
+    fn test_closest() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(closest("hook", &["hooks", "sync"]), Some("hooks"));
+        assert_eq!(closest("frobnicate", &["hooks", "sync"]), None);
+        assert_eq!(closest("Pre-ID", &["pre_id"]), Some("pre_id"));
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+/// Attempts before giving up on a locked file (the waits add up to about 1.5s)
+const ATTEMPTS: u32 = 5;
+const BACKOFF: Duration = Duration::from_millis(50);
+
+/// Content hashes of the files [`guard`]ed this run, as last read or written
+static GUARDED: Mutex<BTreeMap<PathBuf, Vec<u8>>> = Mutex::new(BTreeMap::new());
+
+/// A text file decoded for editing, which remembers how to encode an edit back
+pub struct Text {
+    pub content: String,
+    encoding: Encoding,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Utf8 {
+        bom: bool,
+    },
+    Utf16 {
+        big_endian: bool,
+    },
+    /// Not valid UTF-8: each byte is kept as the char with the same value, so whatever the
+    /// file really is, the bytes around an (ASCII) edit come back out unchanged
+    Bytes,
+}
+
+/// Read `path` in a single pass, decoding UTF-8 (with or without a BOM) or UTF-16 by its
+/// BOM, and anything else byte for byte. A [`guard`]ed file is checked for outside changes
+pub fn read(path: &Path) -> anyhow::Result<Text> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+    check_unchanged(path, Some(&bytes))?;
+    Ok(Text::decode(bytes))
+}
+
+impl Text {
+    fn decode(bytes: Vec<u8>) -> Self {
+        let text = |content, encoding| Self { content, encoding };
+        match bytes.as_slice() {
+            [0xEF, 0xBB, 0xBF, rest @ ..] => {
+                if let Ok(content) = std::str::from_utf8(rest) {
+                    return text(content.to_string(), Encoding::Utf8 { bom: true });
+                }
+            }
+            [bom @ (0xFF | 0xFE), next, rest @ ..] if bom ^ next == 0x01 && rest.len() % 2 == 0 => {
+                let big_endian = *bom == 0xFE;
+                let units = rest.chunks_exact(2).map(|pair| {
+                    let pair = [pair[0], pair[1]];
+                    if big_endian {
+                        u16::from_be_bytes(pair)
+                    } else {
+                        u16::from_le_bytes(pair)
+                    }
+                });
+                if let Ok(content) = char::decode_utf16(units).collect::<Result<String, _>>() {
+                    return text(content, Encoding::Utf16 { big_endian });
+                }
+            }
+            _ => {}
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(content) => text(content, Encoding::Utf8 { bom: false }),
+            Err(err) => text(
+                err.into_bytes().into_iter().map(char::from).collect(),
+                Encoding::Bytes,
+            ),
+        }
+    }
+
+    /// `content` (usually an edit of [`Text::content`]) in the file's original encoding
+    pub fn encode(&self, content: &str) -> anyhow::Result<Vec<u8>> {
+        Ok(match self.encoding {
+            Encoding::Utf8 { bom } => {
+                let bom: &[u8] = if bom { &[0xEF, 0xBB, 0xBF] } else { &[] };
+                [bom, content.as_bytes()].concat()
+            }
+            Encoding::Utf16 { big_endian } => std::iter::once('\u{feff}')
+                .chain(content.chars())
+                .collect::<String>()
+                .encode_utf16()
+                .flat_map(|unit| {
+                    if big_endian {
+                        unit.to_be_bytes()
+                    } else {
+                        unit.to_le_bytes()
+                    }
+                })
+                .collect(),
+            Encoding::Bytes => content
+                .chars()
+                .map(|c| {
+                    u8::try_from(c).map_err(|_| {
+                        anyhow::anyhow!("Can't write {c:?} into a file that isn't UTF-8")
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+/// The contents of files about to be edited, so the edits can be undone
+pub struct Backup {
+    /// `None` for files that didn't exist yet
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Backup {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> anyhow::Result<Self> {
+        let files = paths
+            .into_iter()
+            .map(|path| match std::fs::read(&path) {
+                Ok(contents) => Ok((path, Some(contents))),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok((path, None)),
+                Err(err) => Err(anyhow::anyhow!("Failed to read {}: {err}", path.display())),
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { files })
+    }
+
+    /// Put every file back the way it was
+    pub fn restore(&self) -> anyhow::Result<()> {
+        for (path, contents) in &self.files {
+            match contents {
+                Some(contents) => write(path, contents)?,
+                None if path.exists() => std::fs::remove_file(path)?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Remember `path`'s content hash so [`write`] refuses to replace it if something else
+/// changes it in the meantime (another job pushing while a long pipeline runs). A file
+/// that can't be read isn't guarded; reading it for real reports why
+pub fn guard(path: &Path) {
+    if let Ok(bytes) = std::fs::read(path) {
+        guarded().insert(canonical(path), hash(&bytes));
+    }
+}
+
+fn guarded() -> std::sync::MutexGuard<'static, BTreeMap<PathBuf, Vec<u8>>> {
+    GUARDED
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn hash(bytes: &[u8]) -> Vec<u8> {
+    Sha256::digest(bytes).to_vec()
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Fail if a [`guard`]ed `path` no longer holds what it did when it was read, given its
+/// current contents (`None` if it's gone)
+fn check_unchanged(path: &Path, current: Option<&[u8]>) -> anyhow::Result<()> {
+    let Some(expected) = guarded().get(&canonical(path)).cloned() else {
+        return Ok(());
+    };
+    if current.map(hash).as_ref() != Some(&expected) {
+        anyhow::bail!(
+            "{} changed since it was read (did another job release?); re-run to bump from its new contents",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Replace `path` atomically while holding cargo's package cache lock, retrying briefly if
+/// another process (an IDE, a parallel cargo build) has the file or the lock
+///
+/// The new contents go to a temporary file beside `path` that's renamed over it, so a
+/// concurrent reader sees either the old manifest or the new one, never a truncated one.
+/// A [`guard`]ed file is checked for outside changes first.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    let _lock = lock_package_cache();
+
+    // Write through symlinks rather than replacing them
+    let path = &canonical(path);
+    check_unchanged(path, std::fs::read(path).ok().as_deref())?;
+    let contents = contents.as_ref();
+    let file_name = path
+        .file_name()
+        .ok_or(anyhow::anyhow!("{} is not a file", path.display()))?;
+    let temp = path.with_file_name(format!(".{}.version-tmp", file_name.to_string_lossy()));
+    std::fs::write(&temp, contents)?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        std::fs::set_permissions(&temp, metadata.permissions())?;
+    }
+
+    let mut attempt = 0;
+    loop {
+        match std::fs::rename(&temp, path) {
+            Ok(()) => {
+                if let Some(expected) = guarded().get_mut(path) {
+                    *expected = hash(contents);
+                }
+                return Ok(());
+            }
+            Err(err) if is_transient(&err) && attempt + 1 < ATTEMPTS => {
+                std::thread::sleep(BACKOFF * 2u32.pow(attempt));
+                attempt += 1;
+            }
+            Err(err) => {
+                let _ = std::fs::remove_file(&temp);
+                return Err(anyhow::anyhow!("Failed to write {}: {err}", path.display()));
+            }
+        }
+    }
+}
+
+/// Errors another process holding the file causes, which go away on their own
+fn is_transient(err: &std::io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION surface as PermissionDenied or raw codes
+    let windows_sharing = cfg!(windows)
+        && (err.kind() == ErrorKind::PermissionDenied
+            || matches!(err.raw_os_error(), Some(32 | 33)));
+    windows_sharing || err.kind() == ErrorKind::ResourceBusy
+}
+
+/// Cargo's `$CARGO_HOME/.package-cache` lock, which cargo holds while resolving and
+/// rewriting lockfiles. Best effort: without a cargo home, or if cargo keeps it, carry on
+fn lock_package_cache() -> Option<File> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(cargo_home()?.join(".package-cache"))
+        .ok()?;
+
+    for attempt in 0..ATTEMPTS {
+        match file.try_lock() {
+            Ok(()) => return Some(file),
+            Err(TryLockError::WouldBlock) => std::thread::sleep(BACKOFF * 2u32.pow(attempt)),
+            Err(TryLockError::Error(_)) => return None,
+        }
+    }
+    None
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::home_dir().map(|home| home.join(".cargo")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_replaces_without_leftovers() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("Cargo.toml");
+        std::fs::write(&path, "version = \"1.0.0\"\n").unwrap();
+
+        write(&path, "version = \"1.0.1\"\n").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "version = \"1.0.1\"\n"
+        );
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_guard_refuses_outside_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("Cargo.toml");
+        std::fs::write(&path, "version = \"1.0.0\"\n").unwrap();
+
+        guard(&path);
+        write(&path, "version = \"1.0.1\"\n").unwrap();
+        // Its own writes don't count as changes
+        write(&path, "version = \"1.0.2\"\n").unwrap();
+
+        std::fs::write(&path, "version = \"1.1.0\"\n").unwrap();
+        assert!(read(&path).is_err());
+        assert!(write(&path, "version = \"1.0.3\"\n").is_err());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "version = \"1.1.0\"\n"
+        );
+    }
+
+    #[test]
+    fn test_backup_restores() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let manifest = dir.join("Cargo.toml");
+        let lockfile = dir.join("Cargo.lock");
+        std::fs::write(&manifest, "version = \"1.0.0\"\n").unwrap();
+
+        let backup = Backup::new([manifest.clone(), lockfile.clone()]).unwrap();
+        write(&manifest, "version = \"1.0.1\"\n").unwrap();
+        std::fs::write(&lockfile, "# generated\n").unwrap();
+        backup.restore().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&manifest).unwrap(),
+            "version = \"1.0.0\"\n"
+        );
+        assert!(!lockfile.exists());
+    }
+
+    #[test]
+    fn test_text_round_trips_encodings() {
+        let little_endian = [0xFF, 0xFE, b'v', 0, b'=', 0, b'1', 0];
+        let big_endian = [0xFE, 0xFF, 0, b'v', 0, b'=', 0, b'1'];
+        let cases: [&[u8]; 5] = [
+            b"v=1 # caf\xc3\xa9",
+            b"\xef\xbb\xbfv=1",
+            &little_endian,
+            &big_endian,
+            b"v=1 # caf\xe9 \xff",
+        ];
+        for bytes in cases {
+            let text = Text::decode(bytes.to_vec());
+            assert!(text.content.starts_with("v=1"), "{:?}", text.content);
+            assert_eq!(text.encode(&text.content).unwrap(), bytes);
+
+            let edited = text.encode(&text.content.replacen('1', "2", 1)).unwrap();
+            let changed = bytes.iter().zip(&edited).filter(|(a, b)| a != b).count();
+            assert_eq!((edited.len(), changed), (bytes.len(), 1));
+        }
+
+        let latin1 = Text::decode(b"v=1 # caf\xe9".to_vec());
+        assert!(latin1.encode("v=1 # \u{2603}").is_err());
+    }
+}
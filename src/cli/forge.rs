@@ -0,0 +1,258 @@
+//! The hosts pull requests are looked up and opened on, and commit statuses and releases
+//! are made on: GitHub, Gitea (and Forgejo), and Bitbucket Cloud. The forge is picked from
+//! the origin remote's host, or by the config's `forge` for a self-hosted Gitea
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{bitbucket, gitea, github};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Github,
+    /// Gitea or Forgejo, at the origin remote's host
+    Gitea,
+    /// Bitbucket Cloud
+    Bitbucket,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Github => "GitHub",
+            Kind::Gitea => "Gitea",
+            Kind::Bitbucket => "Bitbucket",
+        }
+    }
+
+    /// Whether pull requests can be labelled
+    pub fn has_labels(self) -> bool {
+        !matches!(self, Kind::Bitbucket)
+    }
+
+    /// The forge a host is known to run
+    fn detect(host: &str) -> Option<Self> {
+        match host {
+            "github.com" => Some(Kind::Github),
+            "gitea.com" | "codeberg.org" => Some(Kind::Gitea),
+            "bitbucket.org" => Some(Kind::Bitbucket),
+            _ => None,
+        }
+    }
+}
+
+/// A repository on a forge
+#[derive(Clone, Debug, PartialEq)]
+pub struct Repo {
+    pub kind: Kind,
+    /// Where the remote is hosted, e.g. `https://codeberg.org`
+    pub host: String,
+    /// `owner/repo`, or `workspace/repo` on Bitbucket
+    pub path: String,
+}
+
+impl fmt::Display for Repo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+/// The parts of a pull request bumps are inferred from
+pub struct PullRequest {
+    pub description: String,
+    /// Always empty on Bitbucket, which has no labels
+    pub labels: Vec<String>,
+}
+
+/// A pull request to open, from the `head` branch into `base`
+pub struct NewPullRequest {
+    pub title: String,
+    pub body: String,
+    pub head: String,
+    pub base: String,
+    /// Ignored on Bitbucket, which has no labels
+    pub labels: Vec<String>,
+}
+
+/// A pull request that was opened
+pub struct Opened {
+    pub number: u64,
+    pub url: String,
+}
+
+/// The host (as a URL, https unless the remote is http) and `owner/repo` path of a remote
+/// URL: http(s) with or without a user, scp-style ssh, or `ssh://`. An ssh port is dropped,
+/// since the API isn't on it
+fn split_remote(url: &str) -> Option<(String, String)> {
+    let url = url.trim();
+    let (scheme, host, path) = if let Some((scheme, rest)) = url
+        .split_once("://")
+        .filter(|(scheme, _)| ["https", "http"].contains(scheme))
+    {
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        (scheme, host, path)
+    } else if let Some(rest) = url.strip_prefix("ssh://") {
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        ("https", host.split(':').next()?, path)
+    } else {
+        let (authority, path) = url.split_once(':')?;
+        let (_, host) = authority.split_once('@')?;
+        ("https", host, path)
+    };
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    (!host.is_empty() && path.split('/').count() == 2 && !path.starts_with('/'))
+        .then(|| (format!("{scheme}://{host}"), path.to_string()))
+}
+
+/// The repository to use: at `path` (`--github-repo`) when given, else the origin
+/// `remote`'s, on the `forced` forge or the one the remote's host runs. A path without a
+/// remote is on GitHub
+pub fn repo(forced: Option<Kind>, path: Option<&str>, remote: Option<&str>) -> Option<Repo> {
+    let remote = remote.and_then(split_remote);
+    let detected = remote
+        .as_ref()
+        .and_then(|(host, _)| Kind::detect(host.split_once("://").map_or(host, |(_, host)| host)));
+    let kind = match (forced, detected, &remote) {
+        (Some(kind), _, _) | (None, Some(kind), _) => kind,
+        (None, None, None) if path.is_some() => Kind::Github,
+        (None, None, _) => return None,
+    };
+    let host = match (kind, &remote) {
+        (Kind::Github, _) => "https://github.com".to_string(),
+        (Kind::Bitbucket, _) => "https://bitbucket.org".to_string(),
+        (Kind::Gitea, Some((host, _))) => host.clone(),
+        (Kind::Gitea, None) => return None,
+    };
+    let path = path
+        .map(str::to_string)
+        .or_else(|| remote.map(|(_, path)| path))?;
+    Some(Repo { kind, host, path })
+}
+
+/// Fetch a pull request's description and label names
+pub fn pull_request(token: &str, repo: &Repo, number: u64) -> anyhow::Result<PullRequest> {
+    match repo.kind {
+        Kind::Github => github::pull_request(token, &repo.path, number),
+        Kind::Gitea => gitea::pull_request(token, repo, number),
+        Kind::Bitbucket => bitbucket::pull_request(token, repo, number),
+    }
+}
+
+/// Set a successful commit status on `sha`, under `context`
+pub fn commit_status(
+    token: &str,
+    repo: &Repo,
+    sha: &str,
+    context: &str,
+    description: &str,
+) -> anyhow::Result<()> {
+    match repo.kind {
+        Kind::Github => github::commit_status(token, &repo.path, sha, context, description),
+        Kind::Gitea => gitea::commit_status(token, repo, sha, context, description),
+        Kind::Bitbucket => bitbucket::commit_status(token, repo, sha, context, description),
+    }
+}
+
+/// Create a release for the pushed `tag` with `notes`, returning its URL; `None` on
+/// Bitbucket, where the tag is the release
+pub fn create_release(
+    token: &str,
+    repo: &Repo,
+    tag: &str,
+    notes: &str,
+    prerelease: bool,
+) -> anyhow::Result<Option<String>> {
+    match repo.kind {
+        Kind::Github => github::create_release(token, &repo.path, tag, notes, prerelease).map(Some),
+        Kind::Gitea => gitea::create_release(token, repo, tag, notes, prerelease).map(Some),
+        Kind::Bitbucket => Ok(None),
+    }
+}
+
+/// Open a pull request, labelled where the forge has labels
+pub fn open_pull_request(
+    token: &str,
+    repo: &Repo,
+    request: &NewPullRequest,
+) -> anyhow::Result<Opened> {
+    match repo.kind {
+        Kind::Github => github::open_pull_request(token, &repo.path, request),
+        Kind::Gitea => gitea::open_pull_request(token, repo, request),
+        Kind::Bitbucket => bitbucket::open_pull_request(token, repo, request),
+    }
+}
+
+/// The PR number from a squash-merge subject like "Add thing (#123)", or Bitbucket's
+/// "Merged in fix/typo (pull request #123)"
+pub fn pr_number(subject: &str) -> Option<u64> {
+    let rest = subject.trim_end().strip_suffix(')')?;
+    let digits = match rest.rsplit_once("(#") {
+        Some((_, digits)) => digits,
+        None => rest.rsplit_once("(pull request #")?.1,
+    };
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo() {
+        let remote = |url: &str| repo(None, None, Some(url));
+        let github = Some(Repo {
+            kind: Kind::Github,
+            host: "https://github.com".to_string(),
+            path: "owner/repo".to_string(),
+        });
+        assert_eq!(remote("git@github.com:owner/repo.git"), github);
+        assert_eq!(remote("https://github.com/owner/repo"), github);
+        assert_eq!(remote("ssh://git@github.com/owner/repo.git"), github);
+        assert_eq!(repo(None, Some("owner/repo"), None), github);
+
+        let bitbucket = remote("https://ada@bitbucket.org/team/app.git").unwrap();
+        assert_eq!(bitbucket.kind, Kind::Bitbucket);
+        assert_eq!(bitbucket.path, "team/app");
+        assert_eq!(
+            remote("git@codeberg.org:owner/repo.git").map(|repo| repo.kind),
+            Some(Kind::Gitea)
+        );
+
+        // A self-hosted Gitea is only known from the config
+        assert_eq!(
+            remote("ssh://git@git.example.com:2222/owner/repo.git"),
+            None
+        );
+        assert_eq!(
+            repo(
+                Some(Kind::Gitea),
+                None,
+                Some("ssh://git@git.example.com:2222/owner/repo.git")
+            ),
+            Some(Repo {
+                kind: Kind::Gitea,
+                host: "https://git.example.com".to_string(),
+                path: "owner/repo".to_string(),
+            })
+        );
+        assert_eq!(repo(Some(Kind::Gitea), Some("owner/repo"), None), None);
+        assert_eq!(remote("https://gitlab.com/group/sub/repo"), None);
+    }
+
+    #[test]
+    fn test_pr_number() {
+        assert_eq!(pr_number("Add thing (#123)"), Some(123));
+        assert_eq!(pr_number("Add thing (#123)  "), Some(123));
+        assert_eq!(pr_number("Merged in fix/typo (pull request #45)"), Some(45));
+        assert_eq!(pr_number("Add thing (#abc)"), None);
+        assert_eq!(pr_number("Fix #123"), None);
+    }
+}
@@ -0,0 +1,207 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::changelog::SECONDS_PER_DAY;
+use crate::cli::clock::parse_date;
+
+const MINUTES_PER_DAY: u64 = 24 * 60;
+const MINUTES_PER_WEEK: u64 = 7 * MINUTES_PER_DAY;
+
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// `[[freeze]]`: a window during which releases are refused unless `--override-freeze`
+/// gives a reason
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Freeze {
+    /// "Fri 16:00 - Mon 08:00" every week, "17:00 - 09:00" every day, or
+    /// "2024-12-20 - 2025-01-02" once, all UTC. An end date without a time includes that day
+    pub window: String,
+    /// Let prereleases through during the window
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_prereleases: bool,
+}
+
+/// A parsed `window`
+#[derive(Debug, PartialEq)]
+enum Window {
+    /// Minutes into the day or week (from Monday 00:00), wrapping when `end < start`
+    Recurring { period: u64, start: u64, end: u64 },
+    /// Unix seconds, end exclusive
+    Once { start: u64, end: u64 },
+}
+
+impl Window {
+    fn parse(window: &str) -> anyhow::Result<Self> {
+        let invalid = || {
+            anyhow::anyhow!(
+                "`{window}` is not a valid freeze window (try \"Fri 16:00 - Mon 08:00\", \"17:00 - 09:00\", or \"2024-12-20 - 2025-01-02\")"
+            )
+        };
+        let (start, end) = window
+            .split_once('–')
+            .or_else(|| window.split_once(" - "))
+            .ok_or_else(invalid)?;
+        let (start, end) = (start.trim(), end.trim());
+
+        if let (Some(start), Some(end)) = (weekly(start), weekly(end)) {
+            return Ok(Self::Recurring {
+                period: MINUTES_PER_WEEK,
+                start,
+                end,
+            });
+        }
+        if let (Some(start), Some(end)) = (time(start), time(end)) {
+            return Ok(Self::Recurring {
+                period: MINUTES_PER_DAY,
+                start,
+                end,
+            });
+        }
+        match (instant(start, false), instant(end, true)) {
+            (Some(start), Some(end)) if start < end => Ok(Self::Once { start, end }),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn contains(&self, now: u64) -> bool {
+        match *self {
+            Self::Recurring { period, start, end } => {
+                // The epoch was a Thursday, three days after a Monday
+                let minute = (now / 60 + 3 * MINUTES_PER_DAY) % period;
+                if start <= end {
+                    (start..end).contains(&minute)
+                } else {
+                    minute >= start || minute < end
+                }
+            }
+            Self::Once { start, end } => (start..end).contains(&now),
+        }
+    }
+}
+
+/// "Fri 16:00" as minutes into the week
+fn weekly(value: &str) -> Option<u64> {
+    let (day, at) = value.split_once(' ')?;
+    let day = day.to_ascii_lowercase();
+    let index = WEEKDAYS
+        .iter()
+        .position(|name| day.get(..3).is_some_and(|prefix| name.starts_with(prefix)))?;
+    Some(index as u64 * MINUTES_PER_DAY + time(at.trim())?)
+}
+
+/// "16:00" as minutes into the day
+fn time(value: &str) -> Option<u64> {
+    let (hours, minutes) = value.split_once(':')?;
+    let (hours, minutes) = (hours.parse::<u64>().ok()?, minutes.parse::<u64>().ok()?);
+    (hours < 24 && minutes < 60 && value.len() == 5).then_some(hours * 60 + minutes)
+}
+
+/// "2024-12-20" or "2024-12-20 08:00" as unix seconds; a bare end date means the end of
+/// that day
+fn instant(value: &str, end: bool) -> Option<u64> {
+    let (date, at) = match value.split_once(' ') {
+        Some((date, at)) => (date, Some(time(at.trim())?)),
+        None => (value, None),
+    };
+    let midnight = parse_date(date).ok().filter(|_| date.contains('-'))?;
+    Some(match at {
+        Some(minutes) => midnight + minutes * 60,
+        None if end => midnight + SECONDS_PER_DAY,
+        None => midnight,
+    })
+}
+
+/// The first window `now` falls in that `version` can't be released during, checking
+/// every window parses either way
+pub fn blocking<'a>(
+    freezes: &'a [Freeze],
+    now: u64,
+    version: &Version,
+) -> anyhow::Result<Option<&'a Freeze>> {
+    let mut blocking = None;
+    for freeze in freezes {
+        let window = Window::parse(&freeze.window)?;
+        let allowed = freeze.allow_prereleases && !version.pre.is_empty();
+        if blocking.is_none() && !allowed && window.contains(now) {
+            blocking = Some(freeze);
+        }
+    }
+    Ok(blocking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows() {
+        // 2024-06-01 was a Saturday
+        let saturday = parse_date("2024-06-01").unwrap();
+        let at = |days: u64, hours: u64| saturday + days * SECONDS_PER_DAY + hours * 3600;
+
+        let weekend = Window::parse("Fri 16:00 – Mon 08:00").unwrap();
+        assert_eq!(
+            weekend,
+            Window::Recurring {
+                period: MINUTES_PER_WEEK,
+                start: 4 * MINUTES_PER_DAY + 16 * 60,
+                end: 8 * 60,
+            }
+        );
+        assert!(weekend.contains(at(0, 12)));
+        assert!(weekend.contains(at(2, 7)));
+        assert!(!weekend.contains(at(2, 8)));
+        assert!(!weekend.contains(at(6, 15)));
+        assert!(weekend.contains(at(6, 16)));
+
+        let nights = Window::parse("Friday 22:00 - Friday 23:30").unwrap();
+        assert!(nights.contains(at(6, 23)));
+        assert!(!nights.contains(at(0, 23)));
+
+        let evenings = Window::parse("17:00 - 09:00").unwrap();
+        assert!(evenings.contains(at(3, 20)));
+        assert!(evenings.contains(at(3, 2)));
+        assert!(!evenings.contains(at(3, 12)));
+
+        let holidays = Window::parse("2024-06-03 - 2024-06-04").unwrap();
+        assert!(!holidays.contains(at(1, 23)));
+        assert!(holidays.contains(at(2, 0)));
+        assert!(holidays.contains(at(3, 23)));
+        assert!(!holidays.contains(at(4, 0)));
+        let afternoon = Window::parse("2024-06-03 12:00 - 2024-06-03 18:00").unwrap();
+        assert!(afternoon.contains(at(2, 13)));
+        assert!(!afternoon.contains(at(2, 18)));
+
+        for invalid in [
+            "Fri 16:00",
+            "Fri 16:00 - 08:00",
+            "Funday 1:00 - Mon 08:00",
+            "2024-06-04 - 2024-06-03",
+            "25:00 - 09:00",
+        ] {
+            assert!(Window::parse(invalid).is_err(), "{invalid}");
+        }
+
+        let freezes = [Freeze {
+            window: "Fri 16:00 - Mon 08:00".to_string(),
+            allow_prereleases: true,
+        }];
+        let v = |s| Version::parse(s).unwrap();
+        assert!(
+            blocking(&freezes, at(0, 12), &v("1.3.0"))
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            blocking(&freezes, at(0, 12), &v("1.3.0-rc.1"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            blocking(&freezes, at(3, 12), &v("1.3.0"))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
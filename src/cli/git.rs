@@ -0,0 +1,441 @@
+use std::collections::{BTreeSet, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use colored::Colorize;
+
+/// Repositories already known to have their full history this run
+static FULL_HISTORY: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug)]
+pub struct Commit {
+    pub sha: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Commit {
+    pub fn short_sha(&self) -> &str {
+        &self.sha[..self.sha.len().min(7)]
+    }
+
+    /// Value of the `key: value` trailer in the body's last paragraph (key is case-insensitive)
+    pub fn trailer(&self, key: &str) -> Option<&str> {
+        let trailers = self.body.trim_end().rsplit("\n\n").next()?;
+        trailers.lines().rev().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case(key)
+                .then(|| value.trim())
+                .filter(|value| !value.is_empty())
+        })
+    }
+}
+
+/// Run git in `dir`, returning trimmed stdout or an error carrying stderr
+pub fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Don't say so when a shallow clone's history is fetched
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Fetch the rest of the history and the tags when `dir` is a shallow clone (CI checkouts
+/// usually are), since the missing tags and commits would quietly change what's inferred
+fn unshallow(dir: &Path) -> anyhow::Result<()> {
+    let mut known = FULL_HISTORY.lock().unwrap_or_else(PoisonError::into_inner);
+    if known.contains(dir) {
+        return Ok(());
+    }
+
+    let shallow =
+        || git(dir, &["rev-parse", "--is-shallow-repository"]).is_ok_and(|out| out == "true");
+    if shallow() {
+        if !QUIET.load(Ordering::Relaxed) {
+            eprintln!(
+                "{} This is a shallow clone; fetching its full history and tags",
+                "ℹ".blue().bold()
+            );
+        }
+        // Without a remote, `fetch` succeeds having fetched nothing
+        let fetched = git(dir, &["fetch", "--quiet", "--unshallow", "--tags"]).and_then(|_| {
+            if shallow() {
+                anyhow::bail!("there's no remote to fetch from");
+            }
+            Ok(())
+        });
+        fetched.map_err(|e| {
+            anyhow::anyhow!(
+                "This is a shallow clone, so release tags and commits are missing, and fetching them failed: {e}. Check out the full history instead, e.g. with `fetch-depth: 0` for actions/checkout"
+            )
+        })?;
+    }
+    known.insert(dir.to_path_buf());
+    Ok(())
+}
+
+/// Whether `ancestor` is reachable from `descendant`
+pub fn is_ancestor(dir: &Path, ancestor: &str, descendant: &str) -> anyhow::Result<bool> {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["merge-base", "--is-ancestor", ancestor, descendant])
+        .status()?;
+
+    match status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => anyhow::bail!("git merge-base --is-ancestor {ancestor} {descendant} failed"),
+    }
+}
+
+/// The remote's default branch (`origin/HEAD`), else `main`
+pub fn default_branch(dir: &Path) -> String {
+    git(
+        dir,
+        &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"],
+    )
+    .unwrap_or_else(|_| "main".to_string())
+}
+
+/// The checked out branch, if HEAD isn't detached
+pub fn current_branch(dir: &Path) -> Option<String> {
+    git(dir, &["branch", "--show-current"])
+        .ok()
+        .filter(|branch| !branch.is_empty())
+}
+
+/// Whether `tag` exists locally (false outside a git repository)
+pub fn tag_exists(dir: &Path, tag: &str) -> bool {
+    git(
+        dir,
+        &[
+            "rev-parse",
+            "--quiet",
+            "--verify",
+            &format!("refs/tags/{tag}"),
+        ],
+    )
+    .is_ok()
+}
+
+/// The most recent tag reachable from HEAD, if any
+pub fn last_tag(dir: &Path) -> Option<String> {
+    if let Err(e) = unshallow(dir) {
+        eprintln!("{} {e}", "⚠".yellow().bold());
+    }
+    git(dir, &["describe", "--tags", "--abbrev=0"])
+        .ok()
+        .filter(|tag| !tag.is_empty())
+}
+
+/// Unix timestamp of when `tag` was created (its commit's, for lightweight tags)
+pub fn tag_timestamp(dir: &Path, tag: &str) -> anyhow::Result<u64> {
+    let timestamp = git(
+        dir,
+        &[
+            "for-each-ref",
+            "--format=%(creatordate:unix)",
+            &format!("refs/tags/{tag}"),
+        ],
+    )?;
+    timestamp
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Tag {tag} not found"))
+}
+
+/// Every tag reachable from HEAD
+pub fn merged_tags(dir: &Path) -> anyhow::Result<Vec<String>> {
+    unshallow(dir)?;
+    let tags = git(dir, &["tag", "--merged", "HEAD"])?;
+    Ok(tags.lines().map(str::to_string).collect())
+}
+
+/// Every tag, on any branch, here or on origin (when there is one), so prereleases cut on
+/// another branch or by another clone count too
+pub fn all_tags(dir: &Path) -> anyhow::Result<Vec<String>> {
+    let mut tags = git(dir, &["tag", "--list"])?
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if git(dir, &["remote", "get-url", "origin"]).is_ok() {
+        let remote = git(dir, &["ls-remote", "--tags", "--refs", "origin"])
+            .map_err(|e| anyhow::anyhow!("Couldn't list origin's tags: {e}"))?;
+        tags.extend(
+            remote
+                .lines()
+                .filter_map(|line| line.split_once("refs/tags/"))
+                .map(|(_, tag)| tag.to_string()),
+        );
+    }
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
+/// Number of commits in HEAD's history after `since` (all of them without it)
+pub fn distance(dir: &Path, since: Option<&str>) -> anyhow::Result<u64> {
+    unshallow(dir)?;
+    let range = since.map_or_else(|| "HEAD".to_string(), |tag| format!("{tag}..HEAD"));
+    Ok(git(dir, &["rev-list", "--count", &range])?.parse()?)
+}
+
+/// The author and unix timestamp of each commit in HEAD's history after `since` (all of
+/// them without it)
+pub fn authorship(dir: &Path, since: Option<&str>) -> anyhow::Result<Vec<(String, u64)>> {
+    unshallow(dir)?;
+    let range = since.map_or_else(|| "HEAD".to_string(), |tag| format!("{tag}..HEAD"));
+    let log = git(dir, &["log", "--format=%aN%x1f%at", &range])?;
+    Ok(log
+        .lines()
+        .filter_map(|line| {
+            let (author, timestamp) = line.split_once('\x1f')?;
+            Some((author.to_string(), timestamp.parse().ok()?))
+        })
+        .collect())
+}
+
+/// Unix timestamp of the commit `rev` points at
+pub fn commit_timestamp(dir: &Path, rev: &str) -> anyhow::Result<u64> {
+    let timestamp = git(dir, &["log", "-1", "--format=%ct", rev])?;
+    Ok(timestamp.parse()?)
+}
+
+/// Commits after `since` (or the whole history) touching any of `pathspecs`, relative to `dir`
+///
+/// `since` doesn't have to be an ancestor of HEAD: commits whose patch already shipped on the
+/// tag's side of the merge base (cherry-picked onto or merged back from a release branch) are
+/// dropped so they aren't counted twice.
+pub fn commits(
+    dir: &Path,
+    since: Option<&str>,
+    pathspecs: &[String],
+) -> anyhow::Result<Vec<Commit>> {
+    commits_until(dir, since, "HEAD", pathspecs)
+}
+
+/// [`commits`] up to `until` instead of HEAD
+pub fn commits_until(
+    dir: &Path,
+    since: Option<&str>,
+    until: &str,
+    pathspecs: &[String],
+) -> anyhow::Result<Vec<Commit>> {
+    unshallow(dir)?;
+    let range = since.map_or_else(|| until.to_string(), |tag| format!("{tag}..{until}"));
+    let mut args = vec!["log", "--format=%H%x1f%s%x1f%b%x1e", range.as_str()];
+    if !pathspecs.is_empty() {
+        args.push("--");
+        args.extend(pathspecs.iter().map(String::as_str));
+    }
+
+    let log = git(dir, &args)?;
+    let mut commits = log
+        .split('\x1e')
+        .filter_map(|entry| {
+            let mut fields = entry.trim_start_matches('\n').splitn(3, '\x1f');
+            let sha = fields.next()?.to_string();
+            if sha.is_empty() {
+                return None;
+            }
+            Some(Commit {
+                sha,
+                subject: fields.next().unwrap_or_default().to_string(),
+                body: fields.next().unwrap_or_default().trim().to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if let (Some(tag), false) = (since, commits.is_empty()) {
+        let shipped = shipped_commits(dir, tag, &range)?;
+        commits.retain(|commit| !shipped.contains(&commit.sha));
+    }
+
+    Ok(commits)
+}
+
+/// Commits in `range` whose patch is already part of `tag`, counting from the merge base of
+/// the tag and everything `range` pulls in
+fn shipped_commits(dir: &Path, tag: &str, range: &str) -> anyhow::Result<HashSet<String>> {
+    let boundary = git(dir, &["rev-list", "--boundary", range])?;
+    let mut bases = boundary
+        .lines()
+        .filter_map(|line| line.strip_prefix('-'))
+        .collect::<Vec<_>>();
+    if bases.is_empty() {
+        return Ok(HashSet::new());
+    }
+    bases.push(tag);
+
+    let base = git(
+        dir,
+        &[&["merge-base", "--octopus"], bases.as_slice()].concat(),
+    )?;
+    let released = patch_ids(dir, &format!("{base}..{tag}"))?;
+    if released.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    Ok(patch_ids(dir, range)?
+        .into_iter()
+        .filter(|(patch_id, _)| released.iter().any(|(id, _)| id == patch_id))
+        .map(|(_, sha)| sha)
+        .collect())
+}
+
+/// `(patch id, commit sha)` for every non-merge commit in `range`
+fn patch_ids(dir: &Path, range: &str) -> anyhow::Result<Vec<(String, String)>> {
+    let patches = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["log", "-p", "--no-merges", "--format=commit %H", range])
+        .output()?;
+    if !patches.status.success() {
+        anyhow::bail!(
+            "git log -p {range} failed: {}",
+            String::from_utf8_lossy(&patches.stderr).trim()
+        );
+    }
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["patch-id", "--stable"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or(anyhow::anyhow!("Failed to open git patch-id stdin"))?
+        .write_all(&patches.stdout)?;
+    let output = child.wait_with_output()?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(patch_id, sha)| (patch_id.to_string(), sha.to_string()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailer() {
+        let commit = Commit {
+            sha: String::new(),
+            subject: "Fix".to_string(),
+            body: "Changelog: not a trailer\n\nSigned-off-by: a\nchangelog:  Fixed the thing "
+                .to_string(),
+        };
+        assert_eq!(commit.trailer("Changelog"), Some("Fixed the thing"));
+        assert_eq!(commit.trailer("Signed-off-by"), Some("a"));
+        assert_eq!(commit.trailer("Reviewed-by"), None);
+    }
+
+    fn run(dir: &Path, args: &[&str]) {
+        git(dir, args).unwrap();
+    }
+
+    fn commit_file(dir: &Path, file: &str, content: &str, message: &str) {
+        std::fs::write(dir.join(file), content).unwrap();
+        run(dir, &["add", file]);
+        run(dir, &["commit", "-qm", message]);
+    }
+
+    #[test]
+    fn test_unshallow() {
+        let dir = tempfile::tempdir().unwrap();
+        let origin = dir.path().join("origin");
+        let clone = dir.path().join("clone");
+        std::fs::create_dir_all(&origin).unwrap();
+        run(&origin, &["init", "-q", "-b", "main"]);
+        run(&origin, &["config", "user.name", "test"]);
+        run(&origin, &["config", "user.email", "test@example.com"]);
+        commit_file(&origin, "a", "a", "Initial");
+        run(&origin, &["tag", "v1.0.0"]);
+        commit_file(&origin, "b", "b", "Fix crash");
+
+        let url = format!("file://{}", origin.display());
+        let parent = clone.parent().unwrap();
+        run(
+            parent,
+            &[
+                "clone",
+                "-q",
+                "--depth",
+                "1",
+                &url,
+                &clone.to_string_lossy(),
+            ],
+        );
+        set_quiet(true);
+        assert_eq!(merged_tags(&clone).unwrap(), ["v1.0.0"]);
+        assert_eq!(
+            git(&clone, &["rev-parse", "--is-shallow-repository"]).unwrap(),
+            "false"
+        );
+    }
+
+    #[test]
+    fn test_commits_skip_cherry_picked_release_fixes() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        run(dir, &["init", "-q", "-b", "main"]);
+        run(dir, &["config", "user.name", "test"]);
+        run(dir, &["config", "user.email", "test@example.com"]);
+
+        commit_file(dir, "a", "a", "Initial");
+        run(dir, &["tag", "v1.0.0"]);
+        commit_file(dir, "next", "next", "Start next release");
+        commit_file(dir, "fix", "fix", "Fix crash");
+        commit_file(dir, "feature", "feature", "Add feature");
+
+        // Release branch cut from v1.0.0 with the fix cherry-picked and tagged
+        run(dir, &["checkout", "-qb", "release", "v1.0.0"]);
+        run(dir, &["cherry-pick", "main~1"]);
+        run(dir, &["tag", "v1.0.1"]);
+        run(dir, &["checkout", "-q", "main"]);
+
+        let subjects = |since| {
+            commits(dir, Some(since), &[])
+                .unwrap()
+                .into_iter()
+                .map(|commit| commit.subject)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            subjects("v1.0.0"),
+            ["Add feature", "Fix crash", "Start next release"]
+        );
+        assert_eq!(subjects("v1.0.1"), ["Add feature", "Start next release"]);
+
+        // Merging the release branch back doesn't bring the fix in a second time
+        run(dir, &["merge", "-q", "--no-edit", "release"]);
+        run(dir, &["tag", "v1.1.0", "main~1"]);
+        commit_file(dir, "later", "later", "Later change");
+        assert_eq!(
+            subjects("v1.1.0"),
+            ["Later change", "Merge branch 'release'"]
+        );
+    }
+}
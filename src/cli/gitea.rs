@@ -0,0 +1,189 @@
+//! Gitea's (and Forgejo's) API, which shapes pull requests, statuses, and releases like
+//! GitHub's, except that labels are set by ID
+
+use serde::Deserialize;
+
+use crate::cli::forge::{NewPullRequest, Opened, PullRequest, Repo};
+use crate::cli::http;
+
+#[derive(Deserialize)]
+struct Label {
+    #[serde(default)]
+    id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PullRequestResponse {
+    body: Option<String>,
+    labels: Vec<Label>,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    #[serde(default)]
+    number: u64,
+    html_url: String,
+}
+
+/// Labels listed per page, Gitea's default maximum
+const PAGE: usize = 50;
+
+fn headers(authorization: &str) -> [(&str, &str); 3] {
+    [
+        ("Authorization", authorization),
+        ("Accept", "application/json"),
+        ("Content-Type", "application/json"),
+    ]
+}
+
+/// POST `body` to the API at `path` under the repository, returning the response body
+fn post(token: &str, repo: &Repo, path: &str, body: &serde_json::Value) -> anyhow::Result<String> {
+    let url = format!("{}/api/v1/repos/{}/{path}", repo.host, repo.path);
+    let authorization = format!("token {token}");
+    Ok(http::success(
+        &url,
+        http::post(&url, &headers(&authorization), &body.to_string())?,
+    )?
+    .body_mut()
+    .read_to_string()?)
+}
+
+/// Every label defined on the repository
+fn labels(token: &str, repo: &Repo) -> anyhow::Result<Vec<Label>> {
+    let authorization = format!("token {token}");
+    let mut labels = Vec::new();
+    for page in 1.. {
+        let url = format!(
+            "{}/api/v1/repos/{}/labels?page={page}&limit={PAGE}",
+            repo.host, repo.path
+        );
+        let body = http::success(&url, http::get(&url, &headers(&authorization))?)?
+            .body_mut()
+            .read_to_string()?;
+        let listed: Vec<Label> = serde_json::from_str(&body)?;
+        let last = listed.len() < PAGE;
+        labels.extend(listed);
+        if last {
+            break;
+        }
+    }
+    Ok(labels)
+}
+
+/// The IDs of the `wanted` label names among `labels`
+fn label_ids(labels: &[Label], wanted: &[String]) -> anyhow::Result<Vec<u64>> {
+    wanted
+        .iter()
+        .map(|name| {
+            labels
+                .iter()
+                .find(|label| label.name == *name)
+                .map(|label| label.id)
+                .ok_or_else(|| anyhow::anyhow!("The repository has no label `{name}`"))
+        })
+        .collect()
+}
+
+/// Fetch a pull request's description and label names
+pub fn pull_request(token: &str, repo: &Repo, number: u64) -> anyhow::Result<PullRequest> {
+    let url = format!("{}/api/v1/repos/{}/pulls/{number}", repo.host, repo.path);
+    let authorization = format!("token {token}");
+    let headers = [
+        ("Authorization", authorization.as_str()),
+        ("Accept", "application/json"),
+    ];
+    let body = http::success(&url, http::get(&url, &headers)?)?
+        .body_mut()
+        .read_to_string()?;
+
+    let response: PullRequestResponse = serde_json::from_str(&body)?;
+    Ok(PullRequest {
+        description: response.body.unwrap_or_default(),
+        labels: response
+            .labels
+            .into_iter()
+            .map(|label| label.name)
+            .collect(),
+    })
+}
+
+/// Set a successful commit status on `sha`, under `context`
+pub fn commit_status(
+    token: &str,
+    repo: &Repo,
+    sha: &str,
+    context: &str,
+    description: &str,
+) -> anyhow::Result<()> {
+    let body = serde_json::json!({
+        "state": "success",
+        "context": context,
+        "description": description,
+    });
+    post(token, repo, &format!("statuses/{sha}"), &body)?;
+    Ok(())
+}
+
+/// Create a release for the pushed `tag`, returning its URL
+pub fn create_release(
+    token: &str,
+    repo: &Repo,
+    tag: &str,
+    notes: &str,
+    prerelease: bool,
+) -> anyhow::Result<String> {
+    let body = serde_json::json!({
+        "tag_name": tag,
+        "name": tag,
+        "body": notes,
+        "prerelease": prerelease,
+    });
+    let created: Created = serde_json::from_str(&post(token, repo, "releases", &body)?)?;
+    Ok(created.html_url)
+}
+
+/// Open a pull request with its labels
+pub fn open_pull_request(
+    token: &str,
+    repo: &Repo,
+    request: &NewPullRequest,
+) -> anyhow::Result<Opened> {
+    let labels = if request.labels.is_empty() {
+        Vec::new()
+    } else {
+        label_ids(&labels(token, repo)?, &request.labels)?
+    };
+    let body = serde_json::json!({
+        "title": request.title,
+        "body": request.body,
+        "head": request.head,
+        "base": request.base,
+        "labels": labels,
+    });
+    let created: Created = serde_json::from_str(&post(token, repo, "pulls", &body)?)?;
+    Ok(Opened {
+        number: created.number,
+        url: created.html_url,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_ids() {
+        let labels: Vec<Label> =
+            serde_json::from_str(r#"[{"id": 3, "name": "release"}, {"id": 7, "name": "bot"}]"#)
+                .unwrap();
+        let wanted = |names: &[&str]| {
+            label_ids(
+                &labels,
+                &names.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(wanted(&["bot", "release"]).unwrap(), [7, 3]);
+        assert!(wanted(&["missing"]).is_err());
+    }
+}
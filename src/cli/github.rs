@@ -0,0 +1,123 @@
+use serde::Deserialize;
+
+use crate::cli::forge::{NewPullRequest, Opened, PullRequest};
+use crate::cli::http;
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PullRequestResponse {
+    body: Option<String>,
+    labels: Vec<Label>,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    #[serde(default)]
+    number: u64,
+    html_url: String,
+}
+
+/// POST `body` to the API at `path` with the token, returning the response body
+fn post(token: &str, path: &str, body: &serde_json::Value) -> anyhow::Result<String> {
+    let url = format!("https://api.github.com/repos/{path}");
+    let authorization = format!("Bearer {token}");
+    let headers = [
+        ("Authorization", authorization.as_str()),
+        ("Accept", "application/vnd.github+json"),
+        ("Content-Type", "application/json"),
+    ];
+    Ok(
+        http::success(&url, http::post(&url, &headers, &body.to_string())?)?
+            .body_mut()
+            .read_to_string()?,
+    )
+}
+
+/// Fetch a pull request's description and label names
+pub fn pull_request(token: &str, repo: &str, number: u64) -> anyhow::Result<PullRequest> {
+    let url = format!("https://api.github.com/repos/{repo}/pulls/{number}");
+    let authorization = format!("Bearer {token}");
+    let headers = [
+        ("Authorization", authorization.as_str()),
+        ("Accept", "application/vnd.github+json"),
+    ];
+    let body = http::success(&url, http::get(&url, &headers)?)?
+        .body_mut()
+        .read_to_string()?;
+
+    let response: PullRequestResponse = serde_json::from_str(&body)?;
+    Ok(PullRequest {
+        description: response.body.unwrap_or_default(),
+        labels: response
+            .labels
+            .into_iter()
+            .map(|label| label.name)
+            .collect(),
+    })
+}
+
+/// Set a successful commit status on `sha`, under `context`
+pub fn commit_status(
+    token: &str,
+    repo: &str,
+    sha: &str,
+    context: &str,
+    description: &str,
+) -> anyhow::Result<()> {
+    let body = serde_json::json!({
+        "state": "success",
+        "context": context,
+        "description": description,
+    });
+    post(token, &format!("{repo}/statuses/{sha}"), &body)?;
+    Ok(())
+}
+
+/// Create a release for the pushed `tag`, returning its URL
+pub fn create_release(
+    token: &str,
+    repo: &str,
+    tag: &str,
+    notes: &str,
+    prerelease: bool,
+) -> anyhow::Result<String> {
+    let body = serde_json::json!({
+        "tag_name": tag,
+        "name": tag,
+        "body": notes,
+        "prerelease": prerelease,
+    });
+    let created: Created = serde_json::from_str(&post(token, &format!("{repo}/releases"), &body)?)?;
+    Ok(created.html_url)
+}
+
+/// Open a pull request, then label it
+pub fn open_pull_request(
+    token: &str,
+    repo: &str,
+    request: &NewPullRequest,
+) -> anyhow::Result<Opened> {
+    let body = serde_json::json!({
+        "title": request.title,
+        "body": request.body,
+        "head": request.head,
+        "base": request.base,
+    });
+    let created: Created = serde_json::from_str(&post(token, &format!("{repo}/pulls"), &body)?)?;
+    if !request.labels.is_empty() {
+        let labels = serde_json::json!({ "labels": request.labels });
+        post(
+            token,
+            &format!("{repo}/issues/{}/labels", created.number),
+            &labels,
+        )?;
+    }
+    Ok(Opened {
+        number: created.number,
+        url: created.html_url,
+    })
+}
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use semver::{BuildMetadata, Version};
+
+use crate::cli::git;
+use crate::cli::sync::{Format, Target};
+
+/// A Go module holding one of the `go` sync targets
+#[derive(Debug, PartialEq)]
+struct Module {
+    /// From `go.mod`, e.g. `example.com/app/v2`
+    path: String,
+    /// Where `go.mod` is, relative to the repository root (empty at the root)
+    dir: PathBuf,
+}
+
+/// The tags Go resolves the release from, one per module holding a `go` sync target,
+/// warning about module paths whose major version suffix doesn't match
+pub fn tags(
+    root: &Path,
+    targets: &[Target],
+    version: &Version,
+    quiet: bool,
+) -> anyhow::Result<Vec<String>> {
+    let modules = modules(root, targets)?;
+    let mut tags = Vec::new();
+    for module in &modules {
+        if !quiet && let Some(problem) = major_suffix_problem(&module.path, version) {
+            eprintln!("{} {problem}", "⚠".yellow().bold());
+        }
+        let tag = tag(module, version);
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+fn modules(root: &Path, targets: &[Target]) -> anyhow::Result<Vec<Module>> {
+    let toplevel = std::fs::canonicalize(git::git(root, &["rev-parse", "--show-toplevel"])?)?;
+    let mut modules = Vec::new();
+    for target in targets
+        .iter()
+        .filter(|target| target.format() == Some(Format::Go))
+    {
+        let file = std::fs::canonicalize(root.join(&target.path))
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", target.path.display()))?;
+        let dir = file
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&toplevel))
+            .find(|dir| dir.join("go.mod").is_file())
+            .ok_or(anyhow::anyhow!(
+                "{} isn't in a Go module (no go.mod above it in the repository)",
+                target.path.display()
+            ))?;
+        let go_mod = std::fs::read_to_string(dir.join("go.mod"))?;
+        let module = Module {
+            path: module_path(&go_mod).ok_or(anyhow::anyhow!(
+                "{} has no `module` directive",
+                dir.join("go.mod").display()
+            ))?,
+            dir: dir.strip_prefix(&toplevel)?.to_path_buf(),
+        };
+        if !modules.contains(&module) {
+            modules.push(module);
+        }
+    }
+    Ok(modules)
+}
+
+/// The `module` directive of a `go.mod`
+fn module_path(go_mod: &str) -> Option<String> {
+    go_mod.lines().find_map(|line| {
+        let line = line.split("//").next()?.trim();
+        let path = line.strip_prefix("module")?.trim();
+        (!path.is_empty()).then(|| path.trim_matches(['"', '`']).to_string())
+    })
+}
+
+/// `v1.2.3`, under the module's directory when it isn't at the repository root. Go
+/// rejects build metadata in tags, so it's dropped
+fn tag(module: &Module, version: &Version) -> String {
+    let mut version = version.clone();
+    version.build = BuildMetadata::EMPTY;
+    if module.dir.as_os_str().is_empty() {
+        format!("v{version}")
+    } else {
+        format!(
+            "{}/v{version}",
+            module.dir.to_string_lossy().replace('\\', "/")
+        )
+    }
+}
+
+/// Why Go won't see `version` as a release of the module at `path`: from v2 on, the
+/// module path has to end in the major version (`/v2`, or `.v2` for gopkg.in)
+fn major_suffix_problem(path: &str, version: &Version) -> Option<String> {
+    let separator = if path.starts_with("gopkg.in/") {
+        '.'
+    } else {
+        '/'
+    };
+    let suffix = path
+        .rsplit_once(separator)
+        .and_then(|(_, last)| last.strip_prefix('v')?.parse::<u64>().ok());
+
+    match (version.major, suffix) {
+        (0 | 1, Some(major)) if major >= 2 => Some(format!(
+            "{path} is a v{major} module; Go ignores v{version} tags for it"
+        )),
+        (major, Some(suffix)) if major >= 2 && suffix != major => Some(format!(
+            "{path} should end in {separator}v{major} for v{version}, or Go won't resolve the tag"
+        )),
+        (major, None) if major >= 2 => Some(format!(
+            "{path} needs a {separator}v{major} suffix for v{version}, or Go won't resolve the tag"
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_path() {
+        let go_mod = "// comment\nmodule example.com/app/v2 // trailing\n\ngo 1.22\n";
+        assert_eq!(module_path(go_mod).as_deref(), Some("example.com/app/v2"));
+        assert_eq!(module_path("go 1.22\n"), None);
+    }
+
+    #[test]
+    fn test_tag() {
+        let version = Version::parse("1.3.0-rc.1+build.5").unwrap();
+        let root = Module {
+            path: "example.com/app".to_string(),
+            dir: PathBuf::new(),
+        };
+        let nested = Module {
+            path: "example.com/app/tools".to_string(),
+            dir: PathBuf::from("tools"),
+        };
+        assert_eq!(tag(&root, &version), "v1.3.0-rc.1");
+        assert_eq!(tag(&nested, &version), "tools/v1.3.0-rc.1");
+    }
+
+    #[test]
+    fn test_major_suffix_problem() {
+        let v = |s| Version::parse(s).unwrap();
+        assert_eq!(major_suffix_problem("example.com/app", &v("1.4.0")), None);
+        assert_eq!(
+            major_suffix_problem("example.com/app/v2", &v("2.1.0")),
+            None
+        );
+        assert_eq!(major_suffix_problem("gopkg.in/yaml.v3", &v("3.0.1")), None);
+        assert!(major_suffix_problem("example.com/app", &v("2.0.0")).is_some());
+        assert!(major_suffix_problem("example.com/app/v2", &v("3.0.0")).is_some());
+        assert!(major_suffix_problem("example.com/app/v2", &v("1.9.0")).is_some());
+        assert!(major_suffix_problem("gopkg.in/yaml.v2", &v("3.0.0")).is_some());
+    }
+}
@@ -0,0 +1,117 @@
+//! `version graduate`: the one-time 0.x → 1.0.0 release. It runs the normal bump to
+//! 1.0.0 with a changelog, plus what only this release needs: the changelog section opens
+//! with a note, and the workspace's requirements on the graduating crates move to 1.0 (a
+//! caret `0.4` doesn't match 1.0.0, so they'd stop resolving otherwise)
+
+use std::path::PathBuf;
+
+use cargo_manifest::Manifest;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::{Args, Command, files, manifest, manifest_dir, report_updated, sync, workspace};
+
+const NOTE: &str = "The first stable release: from here on, breaking changes wait for 2.0.0.";
+
+/// Whether this run is a `graduate`
+pub fn active(args: &Args) -> bool {
+    matches!(args.command, Some(Command::Graduate))
+}
+
+/// Turn `args` into the bump to 1.0.0, refusing crates that are already stable
+pub fn prepare(args: &mut Args, version: &Version) -> anyhow::Result<()> {
+    let release_candidate =
+        (version.major, version.minor, version.patch) == (1, 0, 0) && !version.pre.is_empty();
+    if version.major != 0 && !release_candidate {
+        anyhow::bail!("{version} is already stable; graduate releases a 0.x version as 1.0.0");
+    }
+    if args.version_bump.is_some() || args.to.is_some() || args.from_git {
+        anyhow::bail!("graduate always releases 1.0.0, so it takes no bump, --to, or --from-git");
+    }
+    if !args.paths.is_empty() {
+        anyhow::bail!("graduate releases the package or the whole workspace, not --paths");
+    }
+    args.to = Some(Version::new(1, 0, 0));
+    args.changelog = true;
+    Ok(())
+}
+
+/// `section` with the config's `graduation_note` (or the default one) below its heading
+pub fn announce(section: &str, config: &Config) -> String {
+    let note = config.graduation_note.as_deref().unwrap_or(NOTE).trim();
+    match section.split_once("\n\n") {
+        Some((heading, entries)) => format!("{heading}\n\n{note}\n\n{entries}"),
+        None => format!("{section}\n\n{note}\n"),
+    }
+}
+
+/// The manifests that may require the graduating crates: with `--workspace`, the root
+/// manifest (for `[workspace.dependencies]`) and every member's
+pub fn dependent_manifests(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+) -> anyhow::Result<Vec<PathBuf>> {
+    if !args.workspace || args.format != manifest::Format::Cargo {
+        return Ok(Vec::new());
+    }
+    let mut paths = vec![args.path.clone()];
+    for member in workspace::members(manifest_dir(&args.path), manifest, &config.ignore)? {
+        let path = member.dir.join("Cargo.toml");
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+/// Point the workspace's requirements on `names` at `new`, keeping their operator and
+/// precision, returning the manifests changed
+pub fn retarget_dependents(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    names: &[String],
+    new: &Version,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for path in dependent_manifests(args, manifest, config)? {
+        let text = files::read(&path)?;
+        let mut content = text.content.clone();
+        for name in names {
+            if let Some(rewritten) = sync::rewrite_requirements(&content, &path, name, None, new)
+                .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?
+            {
+                content = rewritten;
+            }
+        }
+        if content != text.content {
+            files::write(&path, text.encode(&content)?)?;
+            report_updated(args, &path);
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announce() {
+        let section = "## 1.0.0 (2024-06-01)\n\n- Stabilize the API (abc1234)\n";
+        assert_eq!(
+            announce(section, &Config::default()),
+            format!("## 1.0.0 (2024-06-01)\n\n{NOTE}\n\n- Stabilize the API (abc1234)\n")
+        );
+        let config = Config {
+            graduation_note: Some("Stable at last.\n".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(
+            announce(section, &config),
+            "## 1.0.0 (2024-06-01)\n\nStable at last.\n\n- Stabilize the API (abc1234)\n"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use semver::Version;
+
+use crate::cli::config::{Hook, OnFailure};
+
+/// How often a hook with a timeout is checked on
+const POLL: Duration = Duration::from_millis(50);
+
+/// Run each hook through the shell in `dir`, with the same `PREV_VERSION` and
+/// `NEW_VERSION` environment cargo-release provides, stopping at the first failure unless
+/// the hook says to only warn
+pub fn run(
+    hooks: &[Hook],
+    dir: &Path,
+    old: &Version,
+    new: &Version,
+    quiet: bool,
+) -> anyhow::Result<()> {
+    // Catch a bad timeout before any hook has run
+    for hook in hooks {
+        hook.timeout()?;
+    }
+
+    for hook in hooks {
+        match (run_hook(hook, dir, old, new), hook.on_failure) {
+            (Ok(()), _) => {}
+            (Err(e), OnFailure::Abort) => anyhow::bail!("Hook {e}"),
+            (Err(e), OnFailure::Warn) => {
+                if !quiet {
+                    eprintln!(
+                        "{} Hook {e}; carrying on (on_failure = \"warn\")",
+                        "⚠".yellow().bold()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `command` through the shell the way hooks are run
+pub fn run_command(command: &str, dir: &Path, old: &Version, new: &Version) -> anyhow::Result<()> {
+    let hook = Hook {
+        command: command.to_string(),
+        ..Hook::default()
+    };
+    run_hook(&hook, dir, old, new)
+}
+
+fn run_hook(hook: &Hook, dir: &Path, old: &Version, new: &Version) -> anyhow::Result<()> {
+    let command = &hook.command;
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell.current_dir(
+        hook.cwd
+            .as_ref()
+            .map_or(dir.to_path_buf(), |cwd| dir.join(cwd)),
+    );
+    if let Some(allowed) = &hook.env {
+        shell.env_clear();
+        for name in allowed {
+            if let Some(value) = std::env::var_os(name) {
+                shell.env(name, value);
+            }
+        }
+    }
+    shell
+        .env("PREV_VERSION", old.to_string())
+        .env("NEW_VERSION", new.to_string())
+        // Keep stdout clean for --json
+        .stdout(std::io::stderr());
+
+    let mut child = shell
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("`{command}` couldn't start: {e}"))?;
+    let status = match hook.timeout()? {
+        Some(timeout) => wait_timeout(&mut child, timeout)?.ok_or_else(|| {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::anyhow!("`{command}` timed out after {}s", timeout.as_secs())
+        })?,
+        None => child.wait()?,
+    };
+
+    if !status.success() {
+        anyhow::bail!("`{command}` failed with {status}");
+    }
+    Ok(())
+}
+
+/// The child's exit status, or `None` if it's still running after `timeout`
+fn wait_timeout(child: &mut Child, timeout: Duration) -> anyhow::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(POLL.min(deadline - now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(command: &str) -> Hook {
+        Hook {
+            command: command.to_string(),
+            ..Hook::default()
+        }
+    }
+
+    #[test]
+    fn test_hook_limits() {
+        let dir = std::env::temp_dir();
+        let (old, new) = (Version::new(1, 0, 0), Version::new(1, 0, 1));
+
+        let hung = Hook {
+            timeout: Some("1s".to_string()),
+            ..hook("exec sleep 30")
+        };
+        let started = Instant::now();
+        let err = run_hook(&hung, &dir, &old, &new).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let warn = Hook {
+            on_failure: OnFailure::Warn,
+            ..hook("exit 1")
+        };
+        assert!(run(&[warn], &dir, &old, &new, true).is_ok());
+        assert!(run(&[hook("exit 1")], &dir, &old, &new, true).is_err());
+
+        // Only the allowlisted variables (and the versions) get through
+        let isolated = Hook {
+            env: Some(Vec::new()),
+            ..hook(r#"test -z "$HOME" && test "$NEW_VERSION" = 1.0.1"#)
+        };
+        run_hook(&isolated, &dir, &old, &new).unwrap();
+    }
+}
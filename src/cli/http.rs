@@ -0,0 +1,182 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use ureq::http::{Response, StatusCode};
+use ureq::{Agent, Body};
+
+const USER_AGENT: &str = concat!("version/", env!("CARGO_PKG_VERSION"));
+
+/// First retry delay when the server doesn't send `Retry-After`; doubled on each attempt
+const BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest wait between attempts, however long `Retry-After` asks for
+const MAX_DELAY: Duration = Duration::from_mins(1);
+
+/// How every request to the registry, GitHub, or a download is made
+#[derive(Clone, Copy, Debug)]
+pub struct Settings {
+    /// Attempts after the first one for a transient failure
+    pub retries: u32,
+    /// Limit on each attempt as a whole, from connecting to reading the body
+    pub timeout: Duration,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+static AGENT: OnceLock<Agent> = OnceLock::new();
+
+/// Set `--http-retries` and `--http-timeout` for this run
+pub fn init(settings: Settings) {
+    let _ = SETTINGS.set(settings);
+}
+
+fn settings() -> Settings {
+    *SETTINGS.get_or_init(|| Settings {
+        retries: 3,
+        timeout: Duration::from_secs(30),
+    })
+}
+
+/// One agent for the whole run, so connections are reused. HTTP errors are returned as
+/// responses, for callers that handle a 404 themselves
+fn agent() -> &'static Agent {
+    AGENT.get_or_init(|| {
+        Agent::config_builder()
+            .timeout_global(Some(settings().timeout))
+            .http_status_as_error(false)
+            .build()
+            .into()
+    })
+}
+
+/// GET `url`, retrying transient failures
+pub fn get(url: &str, headers: &[(&str, &str)]) -> anyhow::Result<Response<Body>> {
+    retry(|| {
+        headers
+            .iter()
+            .fold(agent().get(url), |request, (name, value)| {
+                request.header(*name, *value)
+            })
+            .header("User-Agent", USER_AGENT)
+            .call()
+    })
+}
+
+/// POST `body` to `url`, retrying transient failures
+pub fn post(url: &str, headers: &[(&str, &str)], body: &str) -> anyhow::Result<Response<Body>> {
+    retry(|| {
+        headers
+            .iter()
+            .fold(agent().post(url), |request, (name, value)| {
+                request.header(*name, *value)
+            })
+            .header("User-Agent", USER_AGENT)
+            .send(body)
+    })
+}
+
+/// `response`, or an error naming `url` if its status isn't a success
+pub fn success(url: &str, response: Response<Body>) -> anyhow::Result<Response<Body>> {
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("{url} returned HTTP {}", status.as_u16());
+    }
+    Ok(response)
+}
+
+/// Send with `attempt` until it succeeds, fails for good, or runs out of retries. Connection
+/// failures, timeouts, 429, and 5xx gateway errors are retried; the last response or error
+/// is returned as it was
+fn retry(
+    mut attempt: impl FnMut() -> Result<Response<Body>, ureq::Error>,
+) -> anyhow::Result<Response<Body>> {
+    let retries = settings().retries;
+    for tries in 0.. {
+        let result = attempt();
+        let retry_after = match &result {
+            Ok(response) if transient(response.status()) => Some(
+                response
+                    .headers()
+                    .get("Retry-After")
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse::<u64>().ok()),
+            ),
+            Err(e) if transient_error(e) => Some(None),
+            Ok(_) | Err(_) => None,
+        };
+        match retry_after {
+            Some(retry_after) if tries < retries => {
+                std::thread::sleep(delay(tries, retry_after));
+            }
+            _ => return Ok(result?),
+        }
+    }
+    unreachable!("the loop only ends by returning")
+}
+
+fn transient(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+}
+
+fn transient_error(error: &ureq::Error) -> bool {
+    matches!(
+        error,
+        ureq::Error::Io(_)
+            | ureq::Error::Timeout(_)
+            | ureq::Error::ConnectionFailed
+            | ureq::Error::HostNotFound
+    )
+}
+
+/// How long to wait before retry number `tries + 1`: what `Retry-After` asked for, else an
+/// exponential backoff, capped at [`MAX_DELAY`] either way
+fn delay(tries: u32, retry_after: Option<u64>) -> Duration {
+    retry_after
+        .map_or_else(
+            || BACKOFF.saturating_mul(2u32.saturating_pow(tries)),
+            Duration::from_secs,
+        )
+        .min(MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn test_delay() {
+        assert_eq!(delay(0, None), Duration::from_millis(500));
+        assert_eq!(delay(2, None), Duration::from_secs(2));
+        assert_eq!(delay(30, None), MAX_DELAY);
+        assert_eq!(delay(0, Some(7)), Duration::from_secs(7));
+        assert_eq!(delay(0, Some(3600)), MAX_DELAY);
+    }
+
+    #[test]
+    fn test_retries_transient_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let responses = [
+                "HTTP/1.1 502 Bad Gateway\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+            ];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let mut response = get(&url, &[]).unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.body_mut().read_to_string().unwrap(), "ok");
+        server.join().unwrap();
+    }
+}
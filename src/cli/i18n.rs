@@ -0,0 +1,213 @@
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+/// A language with a message catalog
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    /// English
+    En,
+    /// German
+    De,
+}
+
+/// The user-facing strings that go through a catalog. Placeholders like `{path}` are filled
+/// in by [`format`]; errors and JSON stay in English
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    VersionBump,
+    TagSafe,
+    RegistrySafe,
+    MajorRelease,
+    MinorRelease,
+    PatchRelease,
+    PremajorRelease,
+    PreminorRelease,
+    PrepatchRelease,
+    SkipRelease,
+    Frozen,
+    Updated,
+    Committed,
+    Tagged,
+    Noted,
+    Pushed,
+    VerifiedBuild,
+    StatusVersion,
+    StatusLastTag,
+    StatusNone,
+    StatusDaysAgo,
+    StatusCommitsSince,
+    StatusPendingBump,
+    StatusNoMarkers,
+    StatusStale,
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Pick the language for this run: `--lang`, else the locale in `LC_ALL`, `LC_MESSAGES`,
+/// or `LANG`, else English
+pub fn init(explicit: Option<Lang>) {
+    let lang = explicit
+        .or_else(|| {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|var| std::env::var(var).ok())
+                .find(|locale| !locale.is_empty())
+                .and_then(|locale| from_locale(&locale))
+        })
+        .unwrap_or(Lang::En);
+    let _ = LANG.set(lang);
+}
+
+/// The catalog for a POSIX locale name like `de_DE.UTF-8`
+fn from_locale(locale: &str) -> Option<Lang> {
+    let language = locale.split(['_', '.', '@', '-']).next()?;
+    Lang::from_str(language, true).ok()
+}
+
+/// `message` in the current language
+pub fn tr(message: Message) -> &'static str {
+    catalog(*LANG.get().unwrap_or(&Lang::En), message)
+}
+
+/// `message` in the current language, with each `{key}` replaced by its value
+pub fn format(message: Message, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(tr(message).to_string(), |text, (key, value)| {
+            text.replace(&format!("{{{key}}}"), value)
+        })
+}
+
+fn catalog(lang: Lang, message: Message) -> &'static str {
+    match lang {
+        Lang::En => english(message),
+        Lang::De => german(message),
+    }
+}
+
+fn english(message: Message) -> &'static str {
+    match message {
+        Message::VersionBump => "Version bump:",
+        Message::TagSafe => "tag-safe:",
+        Message::RegistrySafe => "registry-safe:",
+        Message::MajorRelease => "major release",
+        Message::MinorRelease => "minor release",
+        Message::PatchRelease => "patch release",
+        Message::PremajorRelease => "pre-major release",
+        Message::PreminorRelease => "pre-minor release",
+        Message::PrepatchRelease => "pre-patch release",
+        Message::SkipRelease => "skip version bump",
+        Message::Frozen => "{names} is frozen ({annotation}), skipping",
+        Message::Updated => "Updated {path}",
+        Message::Committed => "Committed {message}",
+        Message::Tagged => "Tagged {tag}",
+        Message::Noted => "Noted the release in {ref}",
+        Message::Pushed => "Pushed to origin",
+        Message::VerifiedBuild => "Verified the build with `{command}`",
+        Message::StatusVersion => "Version:",
+        Message::StatusLastTag => "Last tag:",
+        Message::StatusNone => "none",
+        Message::StatusDaysAgo => "{days} days ago",
+        Message::StatusCommitsSince => "Commits since:",
+        Message::StatusPendingBump => "Pending bump:",
+        Message::StatusNoMarkers => "no markers since the last tag",
+        Message::StatusStale => "The last release is {days} days old (threshold: {threshold} days)",
+    }
+}
+
+fn german(message: Message) -> &'static str {
+    match message {
+        Message::VersionBump => "Versionserhöhung:",
+        Message::TagSafe => "für Tags:",
+        Message::RegistrySafe => "für Registries:",
+        Message::MajorRelease => "Major-Release",
+        Message::MinorRelease => "Minor-Release",
+        Message::PatchRelease => "Patch-Release",
+        Message::PremajorRelease => "Pre-Major-Release",
+        Message::PreminorRelease => "Pre-Minor-Release",
+        Message::PrepatchRelease => "Pre-Patch-Release",
+        Message::SkipRelease => "Versionserhöhung übersprungen",
+        Message::Frozen => "{names} ist eingefroren ({annotation}) und wird übersprungen",
+        Message::Updated => "{path} aktualisiert",
+        Message::Committed => "{message} committet",
+        Message::Tagged => "{tag} getaggt",
+        Message::Noted => "Release in {ref} vermerkt",
+        Message::Pushed => "Nach origin gepusht",
+        Message::VerifiedBuild => "Build mit `{command}` geprüft",
+        Message::StatusVersion => "Version:",
+        Message::StatusLastTag => "Letzter Tag:",
+        Message::StatusNone => "keiner",
+        Message::StatusDaysAgo => "vor {days} Tagen",
+        Message::StatusCommitsSince => "Commits seitdem:",
+        Message::StatusPendingBump => "Anstehende Erhöhung:",
+        Message::StatusNoMarkers => "keine Marker seit dem letzten Tag",
+        Message::StatusStale => {
+            "Das letzte Release ist {days} Tage alt (Schwellenwert: {threshold} Tage)"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGES: [Message; 25] = [
+        Message::VersionBump,
+        Message::TagSafe,
+        Message::RegistrySafe,
+        Message::MajorRelease,
+        Message::MinorRelease,
+        Message::PatchRelease,
+        Message::PremajorRelease,
+        Message::PreminorRelease,
+        Message::PrepatchRelease,
+        Message::SkipRelease,
+        Message::Frozen,
+        Message::Updated,
+        Message::Committed,
+        Message::Tagged,
+        Message::Noted,
+        Message::Pushed,
+        Message::VerifiedBuild,
+        Message::StatusVersion,
+        Message::StatusLastTag,
+        Message::StatusNone,
+        Message::StatusDaysAgo,
+        Message::StatusCommitsSince,
+        Message::StatusPendingBump,
+        Message::StatusNoMarkers,
+        Message::StatusStale,
+    ];
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut keys = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(key, _)| key))
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn test_catalogs_agree_on_placeholders() {
+        for lang in Lang::value_variants() {
+            for message in MESSAGES {
+                assert_eq!(
+                    placeholders(catalog(*lang, message)),
+                    placeholders(english(message)),
+                    "{lang:?} {message:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(from_locale("de_DE.UTF-8"), Some(Lang::De));
+        assert_eq!(from_locale("en_US"), Some(Lang::En));
+        assert_eq!(from_locale("C"), None);
+        assert_eq!(from_locale("ja_JP.UTF-8"), None);
+    }
+}
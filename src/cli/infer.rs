@@ -0,0 +1,531 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::git::Commit;
+use crate::cli::output::Reason;
+use crate::cli::{Args, VersionBump, extract_version, forge, forge_repo, manifest};
+use crate::inference::{
+    self, Found, MARKERS, Repository, bump_from_description, component, split_message,
+};
+
+/// The manifest and the commits since the last release, newest first, as git gave them
+struct History<'a> {
+    args: &'a Args,
+    commits: &'a [Commit],
+}
+
+impl Repository for History<'_> {
+    fn version(&self) -> anyhow::Result<Version> {
+        extract_version(self.args, &manifest::load(&self.args.path)?)
+    }
+
+    fn unreleased_messages(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .commits
+            .iter()
+            .map(|commit| format!("{}\n{}", commit.subject, commit.body))
+            .collect())
+    }
+}
+
+/// The manifest and the `--message-file` messages that could be read, in order
+struct MessageFiles<'a> {
+    args: &'a Args,
+    messages: Vec<(PathBuf, String)>,
+}
+
+impl Repository for MessageFiles<'_> {
+    fn version(&self) -> anyhow::Result<Version> {
+        extract_version(self.args, &manifest::load(&self.args.path)?)
+    }
+
+    fn unreleased_messages(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .messages
+            .iter()
+            .map(|(_, message)| message.clone())
+            .collect())
+    }
+}
+
+/// The bump [`inference::infer`] settles on for `repository`, with the marker it comes from
+/// (`reason` says where a marker was found). When prerelease and stable markers disagree,
+/// says which bump that made and why
+fn effective(
+    args: &Args,
+    config: &Config,
+    repository: &impl Repository,
+    reason: impl Fn(&Found) -> Reason,
+) -> Option<(VersionBump, Reason)> {
+    let precedence = config.marker_precedence.unwrap_or_default();
+    let bump = inference::infer(repository, precedence).ok()??;
+    let found = inference::found(&repository.unreleased_messages().ok()?)
+        .iter()
+        .map(|found| (found.bump, reason(found)))
+        .collect::<Vec<_>>();
+    // A prerelease of a stable marker's component comes from that marker
+    let (_, reason) = found.iter().find(|(found, _)| *found == bump).or_else(|| {
+        found
+            .iter()
+            .find(|(found, _)| !found.is_pre() && component(*found) == component(bump))
+    })?;
+
+    let markers = found
+        .iter()
+        .filter(|(bump, _)| *bump != VersionBump::Skip)
+        .collect::<Vec<_>>();
+    let conflict = markers.iter().any(|(bump, _)| bump.is_pre())
+        && markers.iter().any(|(bump, _)| !bump.is_pre());
+    if conflict && !args.is_quiet() {
+        eprintln!(
+            "{} Effective bump: {} (marker_precedence = \"{}\"), from {}",
+            "ℹ".blue().bold(),
+            bump.name(),
+            precedence.name(),
+            markers
+                .iter()
+                .map(|(_, reason)| reason.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    Some((bump, reason.clone()))
+}
+
+/// Branch patterns used by `--branch` when the config has no `branch_bumps`
+const BRANCH_BUMPS: [(&str, VersionBump); 3] = [
+    ("breaking/*", VersionBump::Major),
+    ("feature/*", VersionBump::Minor),
+    ("fix/*", VersionBump::Patch),
+];
+
+/// Infer the bump from the commit message, trying each source in turn:
+/// markers in the subject, markers in the body, a `release:` directive in the
+/// `--pr-description` file, the merged branch's name, then (for squash merges with a
+/// `(#123)` suffix) the pull request's description and labels. With several messages,
+/// the most significant marker among them wins, and the sources after the markers go by
+/// the first message's subject. Markers that disagree are settled by `marker_precedence`
+pub fn infer_version_bump(args: &Args, config: &Config) -> Option<(VersionBump, Reason)> {
+    if !args.from_git {
+        return None;
+    }
+    let messages = messages(args)
+        .into_iter()
+        .filter_map(|(file, message)| Some((file, message.ok()?)))
+        .collect::<Vec<_>>();
+    let files = MessageFiles { args, messages };
+    let several = files.messages.len() > 1;
+    let subject = files
+        .messages
+        .first()
+        .map_or("", |(_, message)| split_message(message).0);
+    let reason = |found: &Found| {
+        let (file, _) = &files.messages[found.message];
+        marker_reason(found, None, several.then(|| file.display().to_string()))
+    };
+
+    effective(args, config, &files, reason)
+        .or_else(|| bump_from_pr_file(args, subject))
+        .or_else(|| bump_from_branch(args, config, subject))
+        .or_else(|| bump_from_pr(args, config, subject))
+}
+
+/// The `--message-file` messages in order: each file, and each file directly inside a
+/// directory (by name, skipping dotfiles), e.g. a folder of exported PR descriptions
+pub fn messages(args: &Args) -> Vec<(PathBuf, std::io::Result<String>)> {
+    let mut messages = Vec::new();
+    for path in &args.message_file {
+        if !path.is_dir() {
+            messages.push((path.clone(), std::fs::read_to_string(path)));
+            continue;
+        }
+        let mut files = match std::fs::read_dir(path) {
+            Ok(entries) => entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|file| {
+                    file.is_file()
+                        && !file
+                            .file_name()
+                            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+                })
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                messages.push((path.clone(), Err(e)));
+                continue;
+            }
+        };
+        files.sort();
+        messages.extend(files.into_iter().map(|file| {
+            let message = std::fs::read_to_string(&file);
+            (file, message)
+        }));
+    }
+    messages
+}
+
+/// `--branch`, else the branch a merge commit's subject names, matched against
+/// `branch_bumps` (or [`BRANCH_BUMPS`]). Only used when one of the two is set, and the most
+/// significant matching bump wins
+fn bump_from_branch(args: &Args, config: &Config, subject: &str) -> Option<(VersionBump, Reason)> {
+    if args.branch.is_none() && config.branch_bumps.is_empty() {
+        return None;
+    }
+    let branch = args.branch.clone().or_else(|| merged_branch(subject))?;
+
+    let patterns = if config.branch_bumps.is_empty() {
+        BRANCH_BUMPS
+            .iter()
+            .map(|(pattern, bump)| ((*pattern).to_string(), *bump))
+            .collect::<Vec<_>>()
+    } else {
+        config
+            .branch_bumps
+            .iter()
+            .filter_map(|(pattern, bump)| {
+                let bump = MARKERS.iter().find(|(name, _)| name == bump)?.1;
+                Some((pattern.clone(), bump))
+            })
+            .collect()
+    };
+
+    MARKERS.iter().find_map(|(_, bump)| {
+        let (pattern, _) = patterns.iter().find(|(pattern, pattern_bump)| {
+            pattern_bump == bump
+                && glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(&branch))
+        })?;
+        let reason = Reason::Branch {
+            branch: branch.clone(),
+            pattern: pattern.clone(),
+        };
+        Some((*bump, reason))
+    })
+}
+
+/// The source branch of a merge commit: `Merge pull request #12 from owner/feature/x` (GitHub)
+/// or `Merge branch 'feature/x' into 'main'` (git and GitLab)
+fn merged_branch(subject: &str) -> Option<String> {
+    if let Some(rest) = subject.strip_prefix("Merge pull request #") {
+        let (_, source) = rest.split_once(" from ")?;
+        let (_, branch) = source.trim().split_once('/')?;
+        return Some(branch.to_string());
+    }
+    let rest = subject.strip_prefix("Merge branch '")?;
+    let (branch, _) = rest.split_once('\'')?;
+    Some(branch.to_string())
+}
+
+/// The bump the markers across a range of commits ask for, settled by `marker_precedence`
+pub fn bump_from_commits(
+    args: &Args,
+    config: &Config,
+    commits: &[Commit],
+) -> Option<(VersionBump, Reason)> {
+    let history = History { args, commits };
+    let reason =
+        |found: &Found| marker_reason(found, Some(commits[found.message].sha.clone()), None);
+    effective(args, config, &history, reason)
+}
+
+/// Why a marker `found` in a commit (or a `--message-file`) decides the bump
+fn marker_reason(found: &Found, commit: Option<String>, file: Option<String>) -> Reason {
+    Reason::Marker {
+        marker: format!("[{}]", found.name),
+        source: if found.in_body { "body" } else { "subject" },
+        commit,
+        file,
+    }
+}
+
+/// `--pr-description`: the description saved to a file (e.g. by `gh pr view`)
+fn bump_from_pr_file(args: &Args, subject: &str) -> Option<(VersionBump, Reason)> {
+    let description = std::fs::read_to_string(args.pr_description.as_ref()?).ok()?;
+    let (bump, directive) = bump_from_description(&description)?;
+    let reason = Reason::Description {
+        directive,
+        pull_request: forge::pr_number(subject),
+    };
+    Some((bump, reason))
+}
+
+/// A `release:` directive in the pull request description, else its labels named like the
+/// markers (`minor`, `semver:minor`)
+fn bump_from_pr(args: &Args, config: &Config, subject: &str) -> Option<(VersionBump, Reason)> {
+    let token = args.github_token.as_deref()?;
+    let number = forge::pr_number(subject)?;
+    let repo = forge_repo(args, config)?;
+
+    let pull_request = match forge::pull_request(token, &repo, number) {
+        Ok(pull_request) => pull_request,
+        Err(e) => {
+            if !args.is_quiet() {
+                eprintln!(
+                    "{} Failed to fetch {repo}#{number}: {e}",
+                    "⚠".yellow().bold()
+                );
+            }
+            return None;
+        }
+    };
+
+    if let Some((bump, directive)) = bump_from_description(&pull_request.description) {
+        let reason = Reason::Description {
+            directive,
+            pull_request: Some(number),
+        };
+        return Some((bump, reason));
+    }
+
+    MARKERS.iter().find_map(|(name, bump)| {
+        let label = pull_request.labels.iter().find(|label| {
+            let label = label.to_lowercase();
+            label.strip_prefix("semver:").unwrap_or(&label) == *name
+        })?;
+        let reason = Reason::Label {
+            label: label.clone(),
+            pull_request: number,
+        };
+        Some((*bump, reason))
+    })
+}
+
+/// `--explain-skip`: what inference looked at, in the order it tried, and why none of it
+/// decided the bump
+pub fn explain(args: &Args, config: &Config) -> Vec<String> {
+    let markers = MARKERS
+        .iter()
+        .map(|(name, _)| format!("[{name}]"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let messages = messages(args);
+    let several = messages.len() > 1;
+    let subject = messages
+        .iter()
+        .find_map(|(_, message)| message.as_ref().ok())
+        .map_or_else(String::new, |message| {
+            split_message(message).0.trim_end().to_string()
+        });
+    let mut lines = messages
+        .iter()
+        .map(|(file, message)| match message {
+            Ok(message) => {
+                let subject = split_message(message).0.trim_end();
+                let prefix = if several {
+                    format!("{}: ", file.display())
+                } else {
+                    String::new()
+                };
+                format!("{prefix}commit message \"{subject}\": no {markers} in the subject or body")
+            }
+            Err(e) => format!(
+                "couldn't read the commit message in {}: {e}",
+                file.display()
+            ),
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        lines.push(if args.message_file.is_empty() {
+            "no commit message to scan (pass --message-file)".to_string()
+        } else {
+            "no commit messages in the --message-file directories".to_string()
+        });
+    }
+
+    lines.push(match &args.pr_description {
+        None => "no pull request description (pass --pr-description)".to_string(),
+        Some(file) => format!(
+            "{}: no `release:` front-matter or fenced directive",
+            file.display()
+        ),
+    });
+
+    let branch = args.branch.clone().or_else(|| merged_branch(&subject));
+    lines.push(match branch {
+        _ if args.branch.is_none() && config.branch_bumps.is_empty() => {
+            "branch not checked (pass --branch or configure branch_bumps)".to_string()
+        }
+        None => "no branch: --branch wasn't given and the subject isn't a merge".to_string(),
+        Some(branch) => {
+            let patterns = if config.branch_bumps.is_empty() {
+                BRANCH_BUMPS
+                    .iter()
+                    .map(|(pattern, _)| (*pattern).to_string())
+                    .collect::<Vec<_>>()
+            } else {
+                config.branch_bumps.keys().cloned().collect()
+            };
+            format!("branch {branch} matches none of {}", patterns.join(", "))
+        }
+    });
+
+    lines.push(match (&args.github_token, forge::pr_number(&subject)) {
+        (None, _) => "pull request not checked (no --github-token)".to_string(),
+        (Some(_), None) => "no pull request: the subject has no `(#123)` suffix".to_string(),
+        (Some(_), Some(number)) if forge_repo(args, config).is_none() => format!(
+            "pull request #{number} not checked (pass --github-repo, add a GitHub, Gitea, or Bitbucket origin remote, or set `forge` for a self-hosted Gitea)"
+        ),
+        (Some(_), Some(number)) => format!(
+            "pull request #{number}: no `release:` directive, and no label named like a marker (unless it couldn't be fetched, as warned above)"
+        ),
+    });
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::Precedence;
+
+    #[test]
+    fn test_marker_reason() {
+        let found = Found {
+            name: "minor",
+            bump: VersionBump::Minor,
+            message: 0,
+            in_body: true,
+        };
+        assert!(matches!(
+            marker_reason(&found, Some("abc1234".to_string()), None),
+            Reason::Marker { ref marker, source: "body", commit: Some(_), file: None } if marker == "[minor]"
+        ));
+    }
+
+    #[test]
+    fn test_explain() {
+        use clap::Parser;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let message = dir.join("COMMIT_EDITMSG");
+        std::fs::write(&message, "Merge branch 'docs/readme' into 'main'\n").unwrap();
+
+        let args = Args::parse_from([
+            "version",
+            "--from-git",
+            "--message-file",
+            message.to_str().unwrap(),
+        ]);
+        let config = Config {
+            branch_bumps: [("feature/*".to_string(), "minor".to_string())].into(),
+            ..Config::default()
+        };
+        let lines = explain(&args, &config);
+        assert!(lines[0].starts_with("commit message \"Merge branch 'docs/readme' into 'main'\""));
+        assert_eq!(
+            lines[1],
+            "no pull request description (pass --pr-description)"
+        );
+        assert_eq!(lines[2], "branch docs/readme matches none of feature/*");
+        assert_eq!(lines[3], "pull request not checked (no --github-token)");
+
+        let lines = explain(
+            &Args::parse_from(["version", "--from-git"]),
+            &Config::default(),
+        );
+        assert_eq!(lines[0], "no commit message to scan (pass --message-file)");
+        assert_eq!(
+            lines[2],
+            "branch not checked (pass --branch or configure branch_bumps)"
+        );
+    }
+
+    #[test]
+    fn test_several_messages() {
+        use clap::Parser;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let exported = dir.join("prs");
+        std::fs::create_dir_all(&exported).unwrap();
+        std::fs::write(dir.join("COMMIT_EDITMSG"), "Release train (#40)\n").unwrap();
+        std::fs::write(exported.join("12.md"), "Fix typo [patch]\n").unwrap();
+        std::fs::write(exported.join("13.md"), "Add export\n\n[minor]\n").unwrap();
+        std::fs::write(exported.join(".draft.md"), "[major]\n").unwrap();
+
+        let args = Args::parse_from([
+            "version",
+            "--from-git",
+            "--message-file",
+            dir.join("COMMIT_EDITMSG").to_str().unwrap(),
+            "--message-file",
+            exported.to_str().unwrap(),
+        ]);
+        assert_eq!(messages(&args).len(), 3);
+        let Some((bump, reason)) = infer_version_bump(&args, &Config::default()) else {
+            panic!("expected a marker");
+        };
+        assert_eq!(bump, VersionBump::Minor);
+        assert_eq!(
+            reason.to_string(),
+            format!(
+                "[minor] in the body of {}",
+                exported.join("13.md").display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_merged_branch() {
+        assert_eq!(
+            merged_branch("Merge pull request #12 from owner/feature/login").as_deref(),
+            Some("feature/login")
+        );
+        assert_eq!(
+            merged_branch("Merge branch 'fix/typo' into 'main'").as_deref(),
+            Some("fix/typo")
+        );
+        assert_eq!(merged_branch("Fix typo (#13)"), None);
+    }
+
+    #[test]
+    fn test_bump_from_commits() {
+        use clap::Parser;
+
+        let commit = |sha: &str, subject: &str, body: &str| Commit {
+            sha: sha.to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+        };
+        let commits = [
+            commit("a", "Fix [patch]", ""),
+            commit("b", "Add thing", "[minor]"),
+        ];
+
+        let args = Args::parse_from(["version", "--quiet"]);
+        let Some((
+            bump,
+            Reason::Marker {
+                commit: sha,
+                source,
+                ..
+            },
+        )) = bump_from_commits(&args, &Config::default(), &commits)
+        else {
+            panic!("expected a marker");
+        };
+        assert!(matches!(bump, VersionBump::Minor));
+        assert_eq!(sha.as_deref(), Some("b"));
+        assert_eq!(source, "body");
+        assert!(bump_from_commits(&args, &Config::default(), &commits[..0]).is_none());
+
+        // A prerelease marker alongside a stable one
+        let commits = [
+            commit("a", "Fix [patch]", ""),
+            commit("b", "Start the 2.0 series [prepatch]", ""),
+            commit("c", "Add thing [minor]", ""),
+        ];
+        let config = Config {
+            marker_precedence: Some(Precedence::Prerelease),
+            ..Config::default()
+        };
+        let Some((bump, Reason::Marker { commit: sha, .. })) =
+            bump_from_commits(&args, &config, &commits)
+        else {
+            panic!("expected a marker");
+        };
+        assert_eq!(bump, VersionBump::Preminor);
+        assert_eq!(sha.as_deref(), Some("c"));
+    }
+}
@@ -0,0 +1,165 @@
+//! Keeping `Cargo.lock` in step with a bump. Lockfiles of large workspaces run to
+//! megabytes, so this never parses one: a single pass over the lines finds the
+//! `[[package]]` blocks of the bumped crates and only their version strings are replaced
+
+use std::ops::Range;
+use std::path::Path;
+
+use crate::cli::{files, git};
+
+/// A crate whose lockfile entry moves from `old` to `new`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bump {
+    pub name: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// The `[[package]]` block being scanned
+#[derive(Default)]
+struct Package<'a> {
+    name: Option<&'a str>,
+    version: Option<(&'a str, Range<usize>)>,
+    /// Registry and git dependencies have a `source`; the workspace's own crates don't
+    source: bool,
+}
+
+impl Package<'_> {
+    /// The replacement for this block's version, when it's a local crate being bumped
+    fn edit<'b>(&self, bumps: &'b [Bump]) -> Option<(Range<usize>, &'b str)> {
+        let (version, span) = self.version.as_ref()?;
+        let bump = bumps
+            .iter()
+            .find(|bump| Some(bump.name.as_str()) == self.name && bump.old == *version)?;
+        (!self.source).then(|| (span.clone(), bump.new.as_str()))
+    }
+}
+
+/// The value of a `key = "value"` line, and where it sits in the line
+fn string_value<'a>(line: &'a str, key: &str) -> Option<(&'a str, Range<usize>)> {
+    let rest = line.strip_prefix(key)?.strip_prefix(" = \"")?;
+    let value = &rest[..rest.find('"')?];
+    let start = line.len() - rest.len();
+    Some((value, start..start + value.len()))
+}
+
+/// `content` with the entries of `bumps` moved to their new versions, along with the
+/// `"name version"` references other packages make to them
+pub fn update(content: &str, bumps: &[Bump]) -> String {
+    let mut edits = Vec::new();
+    let mut package = None::<Package>;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed.starts_with('[') {
+            edits.extend(package.take().and_then(|package| package.edit(bumps)));
+            if trimmed == "[[package]]" {
+                package = Some(Package::default());
+            }
+        } else if let Some(package) = &mut package {
+            if let Some((name, _)) = string_value(trimmed, "name") {
+                package.name = Some(name);
+            } else if let Some((version, span)) = string_value(trimmed, "version") {
+                package.version = Some((version, offset + span.start..offset + span.end));
+            } else if trimmed.starts_with("source = ") {
+                package.source = true;
+            } else if let Some(reference) = trimmed.trim_start().strip_prefix('"') {
+                // A dependency: `"name"`, `"name version"`, or `"name version (source)"`
+                let reference = reference.trim_end_matches(',').trim_end_matches('"');
+                if let Some((name, version)) = reference.split_once(' ')
+                    && let Some(bump) = bumps
+                        .iter()
+                        .find(|bump| bump.name == name && bump.old == version)
+                {
+                    let start = offset + line.find('"').unwrap_or_default() + name.len() + 2;
+                    edits.push((start..start + version.len(), bump.new.as_str()));
+                }
+            }
+        }
+        offset += line.len();
+    }
+    edits.extend(package.and_then(|package| package.edit(bumps)));
+    // A block's own version is only known to be bumped once the block ends
+    edits.sort_by_key(|(span, _)| span.start);
+
+    let mut updated = String::with_capacity(content.len());
+    let mut copied = 0;
+    for (span, new) in edits {
+        updated.push_str(&content[copied..span.start]);
+        updated.push_str(new);
+        copied = span.end;
+    }
+    updated.push_str(&content[copied..]);
+    updated
+}
+
+/// Update the lockfile at `path` for `bumps`, returning whether it changed. A missing or
+/// git-ignored lockfile is left alone, since it isn't part of the release
+pub fn write(path: &Path, bumps: &[Bump]) -> anyhow::Result<bool> {
+    let Some(dir) = path.parent() else {
+        return Ok(false);
+    };
+    if bumps.is_empty()
+        || !path.is_file()
+        || git::git(dir, &["check-ignore", "-q", "Cargo.lock"]).is_ok()
+    {
+        return Ok(false);
+    }
+    let text = files::read(path)?;
+    let content = update(&text.content, bumps);
+    if content == text.content {
+        return Ok(false);
+    }
+    files::write(path, text.encode(&content)?)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        let lock = r#"# This file is automatically @generated by Cargo.
+version = 4
+
+[[package]]
+name = "app"
+version = "1.2.3"
+dependencies = [
+ "core 1.2.3",
+ "core 1.2.3 (registry+https://github.com/rust-lang/crates.io-index)",
+ "serde",
+]
+
+[[package]]
+name = "core"
+version = "1.2.3"
+
+[[package]]
+name = "core"
+version = "1.2.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "abc"
+
+[[package]]
+name = "serde"
+version = "1.2.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+        let bumps = ["app", "core", "serde"].map(|name| Bump {
+            name: name.to_string(),
+            old: "1.2.3".to_string(),
+            new: "1.3.0".to_string(),
+        });
+        assert_eq!(
+            update(lock, &bumps),
+            lock.replacen("version = \"1.2.3\"", "version = \"1.3.0\"", 2)
+                .replacen("\"core 1.2.3\"", "\"core 1.3.0\"", 1)
+        );
+        // Already moved on, or not bumped at all
+        let moved = update(lock, &bumps);
+        assert_eq!(update(&moved, &bumps), moved);
+        assert_eq!(update(lock, &[]), lock);
+    }
+}
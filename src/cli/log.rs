@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use colored::Colorize;
+use schemars::JsonSchema;
+use semver::Version;
+use serde::Serialize;
+
+use crate::cli::config::Config;
+use crate::cli::{Args, VersionBump, changelog, git, manifest_dir, output};
+
+#[derive(Serialize, JsonSchema)]
+struct Log {
+    schema_version: u32,
+    versions: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+struct Entry {
+    tag: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    name: String,
+    version: String,
+    date: String,
+    /// Bump from the previous version of the same crate, `None` for the first
+    bump: Option<String>,
+}
+
+/// JSON Schema for `log --json`
+pub fn schema() -> schemars::Schema {
+    schemars::schema_for!(Log)
+}
+
+/// List every version found in release tags, oldest first, with the bump between each
+pub fn log(args: &Args, config: &Config) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let refs = git::git(
+        root,
+        &[
+            "for-each-ref",
+            "refs/tags",
+            "--format=%(refname:short)%1f%(creatordate:unix)",
+        ],
+    )?;
+
+    let tags = refs
+        .lines()
+        .filter_map(|line| {
+            let (tag, timestamp) = line.split_once('\x1f')?;
+            let (name, version) = config.parse_tag(tag)?;
+            Some((
+                tag.to_string(),
+                name,
+                version,
+                timestamp.parse().unwrap_or(0),
+            ))
+        })
+        .collect::<Vec<_>>();
+    let versions = entries(tags);
+
+    if args.json {
+        let log = Log {
+            schema_version: output::SCHEMA_VERSION,
+            versions,
+        };
+        println!("{}", serde_json::to_string_pretty(&log)?);
+    } else if !args.is_quiet() {
+        if versions.is_empty() {
+            println!("No tags match {}", config.tag_format().bold());
+        }
+        for entry in &versions {
+            println!(
+                "{}  {}  {}",
+                entry.tag.bold(),
+                entry.date.dimmed(),
+                entry.bump.as_deref().unwrap_or("initial")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Sort `(tag, name, version, timestamp)` by semver and work out each bump
+fn entries(mut tags: Vec<(String, String, Version, u64)>) -> Vec<Entry> {
+    tags.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.1.cmp(&b.1)));
+
+    let mut previous = HashMap::new();
+    tags.into_iter()
+        .map(|(tag, name, version, timestamp)| {
+            let bump = previous
+                .insert(name.clone(), version.clone())
+                .map(|last| VersionBump::between(&last, &version).name());
+            Entry {
+                tag,
+                name,
+                version: version.to_string(),
+                date: changelog::format_date(timestamp),
+                bump,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries() {
+        let tag = |name: &str, version: &str| {
+            let prefix = if name.is_empty() { "v" } else { "-v" };
+            (
+                format!("{name}{prefix}{version}"),
+                name.to_string(),
+                Version::parse(version).unwrap(),
+                0,
+            )
+        };
+        let sorted = entries(vec![
+            tag("", "1.10.0"),
+            tag("", "1.2.0"),
+            tag("", "1.2.1"),
+            tag("", "2.0.0-rc.1"),
+        ]);
+        let summary = sorted
+            .iter()
+            .map(|e| (e.version.as_str(), e.bump.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("1.2.0", None),
+                ("1.2.1", Some("patch")),
+                ("1.10.0", Some("minor")),
+                ("2.0.0-rc.1", Some("premajor")),
+            ]
+        );
+
+        // Bumps are tracked per crate
+        let entries = entries(vec![
+            tag("a", "1.0.0"),
+            tag("b", "0.1.0"),
+            tag("a", "1.1.0"),
+        ]);
+        assert_eq!(entries[0].bump, None);
+        assert_eq!(entries[1].bump, None);
+        assert_eq!(entries[2].bump.as_deref(), Some("minor"));
+    }
+}
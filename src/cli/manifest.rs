@@ -0,0 +1,545 @@
+use std::ops::Range;
+use std::path::Path;
+
+use cargo_manifest::Manifest;
+use clap::ValueEnum;
+use colored::Colorize;
+use toml::Table;
+
+/// What `--path` points at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// A `Cargo.toml`
+    Cargo,
+    /// A file holding nothing but the version, like `VERSION` or `.version`
+    Raw,
+    /// A standalone TOML file holding the version under `--version-key`, like a
+    /// `version.toml` shared with a build script and CI
+    Toml,
+}
+
+/// The only keys the tool reads; everything else is dropped by the fallback parse
+const PACKAGE_KEYS: &[&str] = &["name", "version", "publish", "metadata"];
+const WORKSPACE_KEYS: &[&str] = &["members", "exclude", "package", "metadata"];
+
+/// Parse a manifest, falling back to just the keys we need when `cargo_manifest` rejects
+/// syntax cargo has stabilized since (e.g. a new edition or a new shape for some field)
+pub fn load(path: &Path) -> anyhow::Result<Manifest> {
+    load_package(path, None)
+}
+
+/// Like [`load`], but for generated manifests declaring several `[[package]]` tables,
+/// pick the one named `package` (which is required in that case)
+pub fn load_package(path: &Path, package: Option<&str>) -> anyhow::Result<Manifest> {
+    parse_package(path, &crate::cli::files::read(path)?.content, package)
+}
+
+/// Like [`load_package`], for the `content` already read from `path`
+pub fn parse_package(
+    path: &Path,
+    content: &str,
+    package: Option<&str>,
+) -> anyhow::Result<Manifest> {
+    if let Ok(table) = toml::from_str::<Table>(content)
+        && let Some(toml::Value::Array(packages)) = table.get("package")
+    {
+        return select_package(path, packages, package);
+    }
+
+    let parsed = content.parse::<Manifest>().and_then(|mut manifest| {
+        manifest.complete_from_path(path)?;
+        Ok(manifest)
+    });
+    let manifest = match parsed {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            let manifest = tolerant(content).map_err(|_| {
+                anyhow::anyhow!("Failed to parse {}: {err}", path.to_string_lossy())
+            })?;
+            let reason = err.to_string();
+            eprintln!(
+                "{} {} has manifest syntax this version doesn't understand ({}), only reading the version keys",
+                "⚠".yellow().bold(),
+                path.to_string_lossy(),
+                reason.lines().next().unwrap_or_default()
+            );
+            manifest
+        }
+    };
+
+    if let (Some(wanted), Some(found)) = (package, &manifest.package)
+        && found.name != wanted
+    {
+        anyhow::bail!(
+            "{} has no package named `{wanted}` (it declares `{}`)",
+            path.to_string_lossy(),
+            found.name
+        );
+    }
+    Ok(manifest)
+}
+
+/// The `content` of a bare version string file (`--format raw`) at `path`, as an
+/// unpublishable package named after its directory so the rest of the tool can treat it
+/// like a manifest
+pub fn parse_raw(path: &Path, content: &str) -> anyhow::Result<Manifest> {
+    let version = content.trim();
+    semver::Version::parse(version).map_err(|e| {
+        anyhow::anyhow!(
+            "{} doesn't hold a version ({e}): {version:?}",
+            path.to_string_lossy()
+        )
+    })?;
+    standalone(path, version)
+}
+
+/// The version under the dotted `key` of a standalone TOML file (`--format toml`), as a
+/// package like [`parse_raw`]'s
+pub fn parse_toml(path: &Path, content: &str, key: &str) -> anyhow::Result<Manifest> {
+    let table = toml::from_str::<Table>(content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {e}", path.to_string_lossy()))?;
+    let mut value = None::<&toml::Value>;
+    for part in key.split('.') {
+        let next = match value {
+            None => table.get(part),
+            Some(value) => value.get(part),
+        };
+        value = Some(next.ok_or(anyhow::anyhow!(
+            "{} has no `{key}` (pass --version-key to point at the version)",
+            path.to_string_lossy()
+        ))?);
+    }
+    let version = value.and_then(toml::Value::as_str).ok_or(anyhow::anyhow!(
+        "`{key}` in {} isn't a version string",
+        path.to_string_lossy()
+    ))?;
+    semver::Version::parse(version).map_err(|e| {
+        anyhow::anyhow!(
+            "`{key}` in {} isn't a version ({e}): {version:?}",
+            path.to_string_lossy()
+        )
+    })?;
+    standalone(path, version)
+}
+
+/// Where the dotted `key` of a standalone TOML file is set: the span of the deepest
+/// table declared for its prefix (else the top-level keys), and the rest of the key as
+/// a pattern for the `field = "version"` rewrite
+pub fn key_span(content: &str, key: &str) -> (Range<usize>, String) {
+    let parts = key.split('.').collect::<Vec<_>>();
+    let field = |parts: &[&str]| {
+        parts
+            .iter()
+            .map(|part| regex::escape(part))
+            .collect::<Vec<_>>()
+            .join(r"\.")
+    };
+    for split in (1..parts.len()).rev() {
+        if let Some(span) = table_span(content, &parts[..split].join(".")) {
+            return (span, field(&parts[split..]));
+        }
+    }
+    (0..top_level(content), field(&parts))
+}
+
+/// The length of `content`'s top-level keys, before its first table header
+pub fn top_level(content: &str) -> usize {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if line.trim_start().starts_with('[') {
+            break;
+        }
+        offset += line.len();
+    }
+    offset
+}
+
+/// A package holding `version`, unpublishable and named after the directory of `path`
+fn standalone(path: &Path, version: &str) -> anyhow::Result<Manifest> {
+    let name = std::path::absolute(path)?
+        .parent()
+        .and_then(Path::file_name)
+        .map_or_else(
+            || "version".to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+    let mut package = Table::new();
+    package.insert("name".to_string(), name.into());
+    package.insert("version".to_string(), version.into());
+    package.insert("publish".to_string(), false.into());
+    let mut table = Table::new();
+    table.insert("package".to_string(), package.into());
+    tolerant(&toml::to_string(&table)?)
+}
+
+/// The `[[package]]` entry named `package`, as a single-package manifest
+fn select_package(
+    path: &Path,
+    packages: &[toml::Value],
+    package: Option<&str>,
+) -> anyhow::Result<Manifest> {
+    let names = packages
+        .iter()
+        .filter_map(|entry| entry.get("name")?.as_str())
+        .collect::<Vec<_>>();
+    let Some(wanted) = package else {
+        anyhow::bail!(
+            "{} declares {} [[package]] tables ({}); pass --package <NAME> to pick the one to bump",
+            path.to_string_lossy(),
+            packages.len(),
+            names.join(", ")
+        );
+    };
+
+    let entry = packages
+        .iter()
+        .find(|entry| entry.get("name").and_then(toml::Value::as_str) == Some(wanted))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} has no [[package]] named `{wanted}` (found: {})",
+                path.to_string_lossy(),
+                names.join(", ")
+            )
+        })?;
+
+    let mut table = Table::new();
+    table.insert("package".to_string(), entry.clone());
+    tolerant(&toml::to_string(&table)?)
+}
+
+/// Whether `line` assigns `key`
+pub fn is_key(line: &str, key: &str) -> bool {
+    line.trim_start()
+        .strip_prefix(key)
+        .is_some_and(|rest| rest.trim_start().starts_with('='))
+}
+
+/// Byte range of the `[header]` table, up to the next table header
+pub fn table_span(content: &str, header: &str) -> Option<Range<usize>> {
+    let mut offset = 0;
+    let mut start = None;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if let Some(start) = start {
+                return Some(start..offset);
+            }
+            let name = trimmed.split('#').next().unwrap_or_default().trim();
+            if name
+                .strip_prefix('[')
+                .and_then(|n| n.strip_suffix(']'))
+                .map(str::trim)
+                == Some(header)
+            {
+                start = Some(offset);
+            }
+        }
+        offset += line.len();
+    }
+    start.map(|start| start..content.len())
+}
+
+/// Byte range of the `[[package]]` table named `name` (through its `[package.*]` subtables)
+pub fn package_span(content: &str, name: &str) -> Option<Range<usize>> {
+    let name_pattern = regex::Regex::new(&format!(
+        r#"(?m)^\s*name\s*=\s*['"]{}['"]"#,
+        regex::escape(name)
+    ))
+    .ok()?;
+
+    let mut offset = 0;
+    let mut start = None;
+    for line in content.split_inclusive('\n') {
+        let header = line.trim_start();
+        if header.starts_with('[') && !header.starts_with("[package.") {
+            if let Some(start) = start
+                && name_pattern.is_match(&content[start..offset])
+            {
+                return Some(start..offset);
+            }
+            start = header.starts_with("[[package]]").then_some(offset);
+        }
+        offset += line.len();
+    }
+
+    start
+        .filter(|&start| name_pattern.is_match(&content[start..]))
+        .map(|start| start..content.len())
+}
+
+/// Why a manifest is frozen against automated bumps: a `# version:frozen` comment or
+/// `[package.metadata.version] frozen = true`
+pub fn frozen(content: &str) -> Option<&'static str> {
+    let commented = content.lines().any(|line| {
+        line.split_once('#').is_some_and(|(_, comment)| {
+            comment
+                .replace(' ', "")
+                .eq_ignore_ascii_case("version:frozen")
+        })
+    });
+    if commented {
+        return Some("# version:frozen");
+    }
+
+    let table: Table = toml::from_str(content).ok()?;
+    table
+        .get("package")?
+        .get("metadata")?
+        .get("version")?
+        .get("frozen")?
+        .as_bool()?
+        .then_some("[package.metadata.version] frozen = true")
+}
+
+/// Why `path` looks like a manifest nobody means to bump by hand: a copy in cargo's build
+/// output (e.g. `target/package/*`), a vendored or registry dependency, or a git submodule
+/// of the repository being worked in
+pub fn unsafe_location(path: &Path) -> Option<String> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = path.parent()?;
+
+    // cargo marks its target directory with CACHEDIR.TAG
+    if let Some(target) = dir
+        .ancestors()
+        .find(|dir| dir.join("CACHEDIR.TAG").is_file())
+    {
+        return Some(format!(
+            "{} is inside the build directory {}",
+            path.display(),
+            target.display()
+        ));
+    }
+    if [".cargo-checksum.json", ".cargo-ok"]
+        .iter()
+        .any(|marker| dir.join(marker).is_file())
+    {
+        return Some(format!(
+            "{} is a vendored or registry copy of a dependency",
+            path.display()
+        ));
+    }
+
+    let superproject =
+        crate::cli::git::git(dir, &["rev-parse", "--show-superproject-working-tree"])
+            .ok()
+            .filter(|superproject| !superproject.is_empty())?;
+    let cwd = std::env::current_dir().ok()?;
+    let toplevel = |dir: &Path| crate::cli::git::git(dir, &["rev-parse", "--show-toplevel"]).ok();
+    (toplevel(&cwd) != toplevel(dir))
+        .then(|| format!("{} is in a git submodule of {superproject}", path.display()))
+}
+
+/// Keep `[package]`/`[workspace]` keys the tool uses and deserialize just those
+fn tolerant(content: &str) -> anyhow::Result<Manifest> {
+    let table: Table = toml::from_str(content)?;
+
+    let mut reduced = Table::new();
+    for (section, keys) in [("package", PACKAGE_KEYS), ("workspace", WORKSPACE_KEYS)] {
+        let Some(toml::Value::Table(original)) = table.get(section) else {
+            continue;
+        };
+        let mut kept = original
+            .iter()
+            .filter(|(key, _)| keys.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Table>();
+        if section == "workspace" {
+            // Only the version of `[workspace.package]` matters here
+            if let Some(toml::Value::Table(package)) = kept.get_mut("package") {
+                package.retain(|key, _| key == "version");
+            }
+        }
+        reduced.insert(section.to_string(), toml::Value::Table(kept));
+    }
+
+    Ok(toml::Value::Table(reduced).try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsafe_location() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let packaged = dir.join("target/package/app-1.0.0");
+        let vendored = dir.join("vendor/dep");
+        std::fs::create_dir_all(&packaged).unwrap();
+        std::fs::create_dir_all(&vendored).unwrap();
+        std::fs::write(dir.join("target/CACHEDIR.TAG"), "").unwrap();
+        std::fs::write(vendored.join(".cargo-checksum.json"), "{}").unwrap();
+        for manifest in [
+            dir.join("Cargo.toml"),
+            packaged.join("Cargo.toml"),
+            vendored.join("Cargo.toml"),
+        ] {
+            std::fs::write(manifest, "[package]\n").unwrap();
+        }
+
+        assert_eq!(unsafe_location(&dir.join("Cargo.toml")), None);
+        assert!(
+            unsafe_location(&packaged.join("Cargo.toml"))
+                .is_some_and(|why| why.contains("build directory"))
+        );
+        assert!(
+            unsafe_location(&vendored.join("Cargo.toml"))
+                .is_some_and(|why| why.contains("vendored"))
+        );
+    }
+
+    #[test]
+    fn test_parse_raw() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("VERSION");
+
+        let package = parse_raw(&path, "1.2.3-rc.1\n").unwrap().package.unwrap();
+        assert_eq!(package.name, dir.file_name().unwrap().to_string_lossy());
+        assert_eq!(
+            package.version.unwrap().as_local().as_deref(),
+            Some("1.2.3-rc.1")
+        );
+        assert!(!crate::cli::workspace::is_publishable(
+            package.publish.as_ref()
+        ));
+
+        assert!(parse_raw(&path, "version = \"1.2.3\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("version.toml");
+
+        let content = "# Read by build.rs\n[release]\nversion = \"2.0.1\"\n";
+        let package = parse_toml(&path, content, "release.version")
+            .unwrap()
+            .package
+            .unwrap();
+        assert_eq!(package.name, dir.file_name().unwrap().to_string_lossy());
+        assert_eq!(
+            package.version.unwrap().as_local().as_deref(),
+            Some("2.0.1")
+        );
+        assert!(parse_toml(&path, content, "version").is_err());
+        assert!(parse_toml(&path, content, "release").is_err());
+    }
+
+    #[test]
+    fn test_key_span() {
+        let content = "version = \"1.0.0\"\n\n[release]\nversion = \"2.0.1\"\n\n[other]\n";
+        assert_eq!(key_span(content, "version"), (0..19, "version".to_string()));
+        assert_eq!(
+            key_span(content, "release.version"),
+            (19..48, "version".to_string())
+        );
+        // A dotted key at the top level, when no table declares the prefix
+        assert_eq!(
+            key_span("meta.version = \"1.0.0\"\n", "meta.version"),
+            (0..23, r"meta\.version".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tolerant_ignores_unknown_syntax() {
+        let content = "[package]\nname = \"pkg\"\nversion = \"1.2.3\"\nedition = \"2099\"\n\n\
+                       [lints.future]\nshape = { new = true }\n";
+        assert!(toml::from_str::<Manifest>(content).is_err());
+
+        let manifest = tolerant(content).unwrap();
+        let package = manifest.package.unwrap();
+        assert_eq!(package.name, "pkg");
+        assert_eq!(
+            package
+                .version
+                .and_then(cargo_manifest::MaybeInherited::as_local),
+            Some("1.2.3".to_string())
+        );
+    }
+
+    const MULTI: &str = "[[package]]\nname = \"a\"\nversion = \"1.0.0\"\n\n\
+                         [package.metadata.x]\nversion = \"9\"\n\n\
+                         [[package]]\nname = \"b\"\nversion = \"2.0.0\"\n\n\
+                         [dependencies]\n";
+
+    #[test]
+    fn test_select_package() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("Cargo.toml");
+        std::fs::write(&path, MULTI).unwrap();
+
+        let error = load(&path).unwrap_err().to_string();
+        assert!(error.contains("2 [[package]] tables (a, b)"), "{error}");
+        let package = load_package(&path, Some("b")).unwrap().package.unwrap();
+        assert_eq!(package.name, "b");
+        assert!(load_package(&path, Some("c")).is_err());
+    }
+
+    #[test]
+    fn test_table_span() {
+        let content = "[dependencies]\nfoo = { version = \"1.0.0\" }\n\n\
+                       [package]\nname = \"a\"\nversion = \"1.0.0\"\n\n[features]\n";
+        let span = table_span(content, "package").unwrap();
+        assert_eq!(
+            &content[span],
+            "[package]\nname = \"a\"\nversion = \"1.0.0\"\n\n"
+        );
+
+        let content =
+            "[workspace]\nmembers = []\n\n[workspace.package] # shared\nversion = \"0.1.0\"\n";
+        let span = table_span(content, "workspace.package").unwrap();
+        assert_eq!(
+            &content[span],
+            "[workspace.package] # shared\nversion = \"0.1.0\"\n"
+        );
+        assert_eq!(table_span(content, "package"), None);
+    }
+
+    #[test]
+    fn test_package_span() {
+        let a = package_span(MULTI, "a").unwrap();
+        assert!(MULTI[a].ends_with("version = \"9\"\n\n"));
+        let b = package_span(MULTI, "b").unwrap();
+        assert_eq!(
+            &MULTI[b],
+            "[[package]]\nname = \"b\"\nversion = \"2.0.0\"\n\n"
+        );
+        assert_eq!(package_span(MULTI, "c"), None);
+    }
+
+    #[test]
+    fn test_frozen() {
+        assert_eq!(
+            frozen("# version:frozen\n[package]\nname = \"a\"\n"),
+            Some("# version:frozen")
+        );
+        assert_eq!(
+            frozen("[package]\nversion = \"1.0.0\" # version: frozen\n"),
+            Some("# version:frozen")
+        );
+        assert_eq!(
+            frozen("[package]\nname = \"a\"\n\n[package.metadata.version]\nfrozen = true\n"),
+            Some("[package.metadata.version] frozen = true")
+        );
+        assert_eq!(
+            frozen("[package]\nname = \"a\"\n\n[package.metadata.version]\nfrozen = false\n"),
+            None
+        );
+        assert_eq!(frozen("# frozen for now\n[package]\nname = \"a\"\n"), None);
+    }
+
+    #[test]
+    fn test_tolerant_workspace_version() {
+        let content = "[workspace]\nmembers = [\"crates/*\"]\nresolver = \"9\"\n\n\
+                       [workspace.package]\nversion = \"0.4.0\"\nedition = \"2099\"\n";
+        let workspace = tolerant(content).unwrap().workspace.unwrap();
+        assert_eq!(workspace.members, ["crates/*"]);
+        assert_eq!(
+            workspace.package.and_then(|package| package.version),
+            Some("0.4.0".to_string())
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::path::Path;
+
+use cargo_manifest::Manifest;
+use clap::ValueEnum;
+use toml::Value;
+
+use crate::cli::config::{Config, Hook};
+use crate::cli::workspace;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Source {
+    CargoRelease,
+}
+
+/// The translated config plus any settings that have no equivalent here
+pub struct Migration {
+    pub config: Config,
+    pub untranslated: Vec<String>,
+}
+
+const TRANSLATED: [&str; 4] = ["tag-name", "tag-prefix", "pre-release-hook", "release"];
+
+pub fn migrate(source: Source, root: &Path, manifest: &Manifest) -> anyhow::Result<Migration> {
+    match source {
+        Source::CargoRelease => from_cargo_release(root, manifest),
+    }
+}
+
+/// cargo-release settings for the crate or workspace at `dir`: the manifest's
+/// `[workspace.metadata.release]` / `[package.metadata.release]`, overridden by `release.toml`
+fn cargo_release_settings(dir: &Path) -> anyhow::Result<toml::Table> {
+    let mut settings = toml::Table::new();
+
+    let manifest: toml::Table = toml::from_str(&std::fs::read_to_string(dir.join("Cargo.toml"))?)?;
+    for section in ["workspace", "package"] {
+        if let Some(Value::Table(release)) = manifest
+            .get(section)
+            .and_then(|v| v.get("metadata"))
+            .and_then(|v| v.get("release"))
+        {
+            settings.extend(release.clone());
+        }
+    }
+
+    let release_toml = dir.join("release.toml");
+    if release_toml.exists() {
+        let file: toml::Table = toml::from_str(&std::fs::read_to_string(release_toml)?)?;
+        settings.extend(file);
+    }
+
+    Ok(settings)
+}
+
+fn from_cargo_release(root: &Path, manifest: &Manifest) -> anyhow::Result<Migration> {
+    let settings = cargo_release_settings(root)?;
+    let mut config = Config {
+        config_version: Some(crate::cli::upgrade::CONFIG_VERSION),
+        ..Config::default()
+    };
+    let mut untranslated = settings
+        .keys()
+        .filter(|key| !TRANSLATED.contains(&key.as_str()))
+        .map(|key| format!("{key} (release.toml)"))
+        .collect::<Vec<_>>();
+
+    let is_workspace = manifest.workspace.is_some();
+    let prefix = match settings.get("tag-prefix").and_then(Value::as_str) {
+        Some(prefix) => prefix.to_string(),
+        None if is_workspace => "{name}-".to_string(),
+        None => String::new(),
+    };
+    let tag_name = settings
+        .get("tag-name")
+        .and_then(Value::as_str)
+        .unwrap_or("{{prefix}}v{{version}}");
+    let tag_format = tag_name
+        .replace("{{prefix}}", &prefix)
+        .replace("{{crate_name}}", "{name}")
+        .replace("{{version}}", "{version}");
+    if tag_format.contains("{{") {
+        untranslated.push(format!(
+            "tag-name = \"{tag_name}\" (unsupported placeholder)"
+        ));
+    } else if tag_format != "v{version}" {
+        config.tag_format = Some(tag_format);
+    }
+
+    match settings.get("pre-release-hook") {
+        Some(Value::String(command)) => config.hooks.push(Hook {
+            command: command.clone(),
+            ..Hook::default()
+        }),
+        Some(Value::Array(argv)) => config.hooks.push(Hook {
+            command: argv
+                .iter()
+                .filter_map(Value::as_str)
+                .map(shell_quote)
+                .collect::<Vec<_>>()
+                .join(" "),
+            ..Hook::default()
+        }),
+        _ => {}
+    }
+
+    if is_workspace {
+        for member in workspace::members(root, manifest, &config.ignore)? {
+            let member_settings = cargo_release_settings(&member.dir)?;
+            if member_settings.get("release").and_then(Value::as_bool) == Some(false) {
+                config.exclude.push(member.name.clone());
+            }
+            untranslated.extend(
+                member_settings
+                    .keys()
+                    .filter(|key| !TRANSLATED.contains(&key.as_str()))
+                    .map(|key| format!("{key} ({})", member.name)),
+            );
+        }
+    }
+
+    Ok(Migration {
+        config,
+        untranslated,
+    })
+}
+
+fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:@%+".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("cargo"), "cargo");
+        assert_eq!(shell_quote("--flag=value"), "--flag=value");
+        assert_eq!(shell_quote("two words"), "'two words'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_from_cargo_release() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"a\"\nversion = \"1.0.0\"\n\n[package.metadata.release]\nsign-tag = true\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("release.toml"),
+            "tag-name = \"release-{{version}}\"\npre-release-hook = [\"sh\", \"-c\", \"make docs\"]\n",
+        )
+        .unwrap();
+
+        let manifest = Manifest::from_path(dir.join("Cargo.toml")).unwrap();
+        let migration = migrate(Source::CargoRelease, dir, &manifest).unwrap();
+
+        assert_eq!(
+            migration.config.tag_format.as_deref(),
+            Some("release-{version}")
+        );
+        assert_eq!(migration.config.hooks[0].command, "sh -c 'make docs'");
+        assert_eq!(migration.untranslated, ["sign-tag (release.toml)"]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt::Write as _;
+
+use cargo_manifest::Manifest;
+use clap::ValueEnum;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::git::Commit;
+use crate::cli::{Args, changelog, crate_names, git, manifest_dir};
+
+/// What `version notes` renders the release notes as
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Style {
+    /// The changelog section, e.g. for a GitHub release body
+    Markdown,
+    /// An `<h2>` and a `<ul>`, e.g. for an email
+    Html,
+    /// Slack's mrkdwn, e.g. for a webhook's `text`
+    Slack,
+    /// Unformatted text
+    Plain,
+}
+
+/// One release's notes, rendered into every style from this
+#[derive(Debug, PartialEq)]
+struct Notes {
+    version: String,
+    date: String,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    text: String,
+    sha: String,
+}
+
+/// `version notes`: the commits that went into `version` (the manifest's by default), since
+/// the release before it. A version that isn't tagged yet gets the commits up to HEAD
+pub fn notes(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    current: &Version,
+    version: Option<&Version>,
+    style: Style,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let version = version.unwrap_or(current);
+    let names = crate_names(args, manifest, config)?;
+    let name = names.first().map_or("", String::as_str);
+    let tag = config.tag(name, version);
+
+    let (until, date) = if git::tag_exists(root, &tag) {
+        let date = changelog::format_date(git::tag_timestamp(root, &tag)?);
+        (tag, date)
+    } else if version == current {
+        ("HEAD".to_string(), args.clock().today())
+    } else {
+        anyhow::bail!("{version} was never tagged ({tag} not found)");
+    };
+    let previous = previous_release(config, name, version, &git::merged_tags(root)?);
+    let commits = git::commits_until(root, previous.as_deref(), &until, &[])?;
+
+    print!(
+        "{}",
+        render(&model(&version.to_string(), &date, &commits), style)
+    );
+    Ok(())
+}
+
+/// The tag of the release `version` follows: the highest lower version of the same crate,
+/// skipping prereleases for a stable version so its notes cover every rc before it
+fn previous_release(
+    config: &Config,
+    name: &str,
+    version: &Version,
+    tags: &[String],
+) -> Option<String> {
+    tags.iter()
+        .filter_map(|tag| {
+            let (tag_name, tag_version) = config.parse_tag(tag)?;
+            // A tag format without `{name}` names every crate
+            ((tag_name.is_empty() || tag_name == name)
+                && tag_version < *version
+                && (!version.pre.is_empty() || tag_version.pre.is_empty()))
+            .then_some((tag_version, tag))
+        })
+        .max()
+        .map(|(_, tag)| tag.clone())
+}
+
+fn model(version: &str, date: &str, commits: &[Commit]) -> Notes {
+    Notes {
+        version: version.to_string(),
+        date: date.to_string(),
+        entries: commits
+            .iter()
+            .filter_map(|commit| {
+                Some(Entry {
+                    text: changelog::entry_text(commit)?.to_string(),
+                    sha: commit.short_sha().to_string(),
+                })
+            })
+            .collect(),
+    }
+}
+
+fn render(notes: &Notes, style: Style) -> String {
+    let Notes {
+        version,
+        date,
+        entries,
+    } = notes;
+    let mut out = String::new();
+    match style {
+        Style::Markdown | Style::Plain => {
+            let heading = if style == Style::Markdown { "## " } else { "" };
+            let _ = writeln!(out, "{heading}{version} ({date})\n");
+            if entries.is_empty() {
+                out.push_str("- No changes\n");
+            }
+            for Entry { text, sha } in entries {
+                let _ = writeln!(out, "- {text} ({sha})");
+            }
+        }
+        Style::Html => {
+            let _ = writeln!(out, "<h2>{} ({date})</h2>", escape_html(version));
+            if entries.is_empty() {
+                out.push_str("<p>No changes</p>\n");
+            } else {
+                out.push_str("<ul>\n");
+                for Entry { text, sha } in entries {
+                    let _ = writeln!(out, "<li>{} (<code>{sha}</code>)</li>", escape_html(text));
+                }
+                out.push_str("</ul>\n");
+            }
+        }
+        Style::Slack => {
+            let _ = writeln!(out, "*{}* ({date})", escape_slack(version));
+            if entries.is_empty() {
+                out.push_str("No changes\n");
+            }
+            for Entry { text, sha } in entries {
+                let _ = writeln!(out, "• {} (`{sha}`)", escape_slack(text));
+            }
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Slack only needs these three escaped; everything else is literal or formatting
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(sha: &str, subject: &str) -> Commit {
+        Commit {
+            sha: sha.to_string(),
+            subject: subject.to_string(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let commits = [
+            commit("0123456789", "Fix <Option> & friends"),
+            commit("abc", "Add thing"),
+        ];
+        let notes = model("1.2.3", "2024-06-01", &commits);
+        assert_eq!(
+            render(&notes, Style::Markdown),
+            changelog::section("1.2.3", "2024-06-01", &commits)
+        );
+        assert_eq!(
+            render(&notes, Style::Html),
+            "<h2>1.2.3 (2024-06-01)</h2>\n<ul>\n<li>Fix &lt;Option&gt; &amp; friends (<code>0123456</code>)</li>\n<li>Add thing (<code>abc</code>)</li>\n</ul>\n"
+        );
+        assert_eq!(
+            render(&notes, Style::Slack),
+            "*1.2.3* (2024-06-01)\n• Fix &lt;Option&gt; &amp; friends (`0123456`)\n• Add thing (`abc`)\n"
+        );
+        assert!(render(&notes, Style::Plain).starts_with("1.2.3 (2024-06-01)\n\n- Fix <Option>"));
+    }
+
+    #[test]
+    fn test_previous_release() {
+        let config = Config::default();
+        let tags = ["v1.1.0", "v1.2.0", "v1.3.0-rc.1", "v1.3.0-rc.2", "v1.3.0"].map(str::to_string);
+        let previous =
+            |version| previous_release(&config, "", &Version::parse(version).unwrap(), &tags);
+        assert_eq!(previous("1.3.0").as_deref(), Some("v1.2.0"));
+        assert_eq!(previous("1.3.0-rc.2").as_deref(), Some("v1.3.0-rc.1"));
+        assert_eq!(previous("1.1.0"), None);
+        assert_eq!(
+            previous_release(&config, "pkg", &Version::new(1, 2, 0), &tags).as_deref(),
+            Some("v1.1.0")
+        );
+    }
+}
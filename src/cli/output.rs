@@ -0,0 +1,299 @@
+use std::cell::RefCell;
+use std::fmt::Write as _;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+thread_local! {
+    /// Where [`Output::print`] writes instead of stdout while [`capture`] is running
+    static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, collecting the JSON it prints instead of writing it to stdout
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Option<String>) {
+    CAPTURED.set(Some(String::new()));
+    let result = f();
+    let json = CAPTURED.take().filter(|json| !json.is_empty());
+    (result, json)
+}
+
+/// Bump the JSON contract whenever a field is removed or changes meaning
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, JsonSchema)]
+pub struct Output {
+    pub schema_version: u32,
+    /// False when every crate was skipped, so nothing is being released
+    pub released: bool,
+    pub crates: Vec<CrateResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct CrateResult {
+    pub name: String,
+    pub old_version: String,
+    pub new_version: String,
+    /// `new_version` with `+` escaped as `_`, for tags that can't hold build metadata
+    pub tag_safe_version: String,
+    /// `new_version` without build metadata, for registries that reject or ignore it
+    pub registry_safe_version: String,
+    pub bump: String,
+    pub reasons: Vec<Reason>,
+    /// The crate's name at the last release tag, if it has been renamed since
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    /// The crate's stability tier, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier: Option<crate::cli::tier::Tier>,
+}
+
+/// Why a bump was chosen
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Reason {
+    /// Passed explicitly on the command line
+    Argument,
+    /// A `[marker]` in the commit message
+    Marker {
+        marker: String,
+        source: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        commit: Option<String>,
+        /// The `--message-file` it was in, when several were scanned
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file: Option<String>,
+    },
+    /// A label on the squash-merged pull request
+    Label { label: String, pull_request: u64 },
+    /// A `release:` directive in the pull request description
+    Description {
+        directive: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pull_request: Option<u64>,
+    },
+    /// The merged branch's name matched a `branch_bumps` pattern
+    Branch { branch: String, pattern: String },
+    /// Nothing was found, so the default bump was used
+    Default,
+    /// The manifest is annotated as frozen, so it was skipped
+    Frozen { annotation: &'static str },
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Argument => write!(f, "given on the command line"),
+            Reason::Marker {
+                marker,
+                source,
+                commit: Some(commit),
+                ..
+            } => write!(
+                f,
+                "{marker} in the {source} of {}",
+                &commit[..commit.len().min(7)]
+            ),
+            Reason::Marker {
+                marker,
+                source,
+                file: Some(file),
+                ..
+            } => write!(f, "{marker} in the {source} of {file}"),
+            Reason::Marker { marker, source, .. } => {
+                write!(f, "{marker} in the commit {source}")
+            }
+            Reason::Label {
+                label,
+                pull_request,
+            } => write!(f, "label `{label}` on #{pull_request}"),
+            Reason::Description {
+                directive,
+                pull_request: Some(pull_request),
+            } => write!(f, "`{directive}` in the description of #{pull_request}"),
+            Reason::Description { directive, .. } => {
+                write!(f, "`{directive}` in the pull request description")
+            }
+            Reason::Branch { branch, pattern } => {
+                write!(f, "branch `{branch}` matched `{pattern}`")
+            }
+            Reason::Default => write!(f, "nothing to infer it from, so the default"),
+            Reason::Frozen { annotation } => write!(f, "frozen by {annotation}"),
+        }
+    }
+}
+
+impl CrateResult {
+    pub fn new(
+        name: String,
+        old_version: String,
+        new_version: String,
+        bump: String,
+        reasons: Vec<Reason>,
+    ) -> Self {
+        Self {
+            name,
+            old_version,
+            tag_safe_version: tag_safe(&new_version),
+            registry_safe_version: registry_safe(&new_version).to_string(),
+            new_version,
+            bump,
+            reasons,
+            renamed_from: None,
+            tier: None,
+        }
+    }
+}
+
+/// Escape `+` as `_` (the convention OCI/Helm use), keeping the build metadata
+pub fn tag_safe(version: &str) -> String {
+    version.replace('+', "_")
+}
+
+/// Strip `+build` metadata
+pub fn registry_safe(version: &str) -> &str {
+    version
+        .split_once('+')
+        .map_or(version, |(version, _)| version)
+}
+
+/// `--porcelain`'s format. Each version's lines never change; new fields only go in a new
+/// version, so scripts pin the one they parse
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Porcelain {
+    /// One line per crate: name, old version, new version, bump, tag-safe version, and
+    /// registry-safe version, separated by tabs
+    V1,
+}
+
+impl Output {
+    /// JSON Schema for the `--json` bump result
+    pub fn schema() -> schemars::Schema {
+        schemars::schema_for!(Output)
+    }
+
+    pub fn new(crates: Vec<CrateResult>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            released: crates.iter().any(|result| result.bump != "skip"),
+            crates,
+        }
+    }
+
+    pub fn print(&self) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        let captured = CAPTURED.with_borrow_mut(|captured| {
+            captured
+                .as_mut()
+                .map(|captured| writeln!(captured, "{json}"))
+                .is_some()
+        });
+        if !captured {
+            println!("{json}");
+        }
+        Ok(())
+    }
+
+    /// `--porcelain`: tab-separated lines, none of whose fields can hold a tab or newline
+    pub fn porcelain(&self, format: Porcelain) -> String {
+        let mut lines = String::new();
+        match format {
+            Porcelain::V1 => {
+                for result in &self.crates {
+                    let _ = writeln!(
+                        lines,
+                        "{}\t{}\t{}\t{}\t{}\t{}",
+                        result.name,
+                        result.old_version,
+                        result.new_version,
+                        result.bump,
+                        result.tag_safe_version,
+                        result.registry_safe_version
+                    );
+                }
+            }
+        }
+        lines
+    }
+
+    /// `key=value` lines for a GitHub Actions step output, describing the first crate
+    fn github_output(&self) -> String {
+        let mut lines = format!("released={}\n", self.released);
+        if let Some(first) = self.crates.first() {
+            let _ = write!(
+                lines,
+                "version={}\nprevious_version={}\nbump={}\n",
+                first.new_version, first.old_version, first.bump
+            );
+        }
+        lines
+    }
+
+    /// `--github-output`: append the outputs to the file `$GITHUB_OUTPUT` names
+    pub fn write_github_output(&self) -> anyhow::Result<()> {
+        use std::io::Write as _;
+
+        let path = std::env::var_os("GITHUB_OUTPUT").ok_or(anyhow::anyhow!(
+            "--github-output needs GITHUB_OUTPUT to be set"
+        ))?;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(self.github_output().as_bytes()))
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", path.to_string_lossy()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reason_display() {
+        let marker = Reason::Marker {
+            marker: "[minor]".to_string(),
+            source: "body",
+            commit: Some("0123456789".to_string()),
+            file: None,
+        };
+        assert_eq!(marker.to_string(), "[minor] in the body of 0123456");
+        let label = Reason::Label {
+            label: "semver:major".to_string(),
+            pull_request: 12,
+        };
+        assert_eq!(label.to_string(), "label `semver:major` on #12");
+    }
+
+    #[test]
+    fn test_safe_versions() {
+        assert_eq!(tag_safe("1.2.3-rc.1+ci.42"), "1.2.3-rc.1_ci.42");
+        assert_eq!(registry_safe("1.2.3-rc.1+ci.42"), "1.2.3-rc.1");
+        assert_eq!(registry_safe("1.2.3"), "1.2.3");
+    }
+
+    #[test]
+    fn test_github_output() {
+        let result = |bump: &str, new: &str| {
+            CrateResult::new(
+                "app".to_string(),
+                "1.2.3".to_string(),
+                new.to_string(),
+                bump.to_string(),
+                vec![Reason::Default],
+            )
+        };
+        assert_eq!(
+            Output::new(vec![result("minor", "1.3.0")]).github_output(),
+            "released=true\nversion=1.3.0\nprevious_version=1.2.3\nbump=minor\n"
+        );
+        let skipped = Output::new(vec![result("skip", "1.2.3")]);
+        assert!(!skipped.released);
+        assert!(skipped.github_output().starts_with("released=false\n"));
+
+        assert_eq!(
+            Output::new(vec![result("patch", "1.2.4+ci.5"), result("skip", "1.2.3")])
+                .porcelain(Porcelain::V1),
+            "app\t1.2.3\t1.2.4+ci.5\tpatch\t1.2.4_ci.5\t1.2.4\napp\t1.2.3\t1.2.3\tskip\t1.2.3\t1.2.3\n"
+        );
+    }
+}
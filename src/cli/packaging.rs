@@ -0,0 +1,214 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::cli::http;
+
+/// A downstream package definition (often in a sibling tap/AUR repo) bumped after a release
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Package {
+    pub kind: Kind,
+    /// Relative to the manifest, e.g. `../homebrew-tap/Formula/app.rb`
+    pub path: PathBuf,
+    /// Source tarball URL, `{version}` and `{tag}` expand. Homebrew formulas default to the
+    /// existing `url` with the old version swapped for the new one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Download the source tarball and update its sha256
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub checksum: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    /// `url`, `version`, and `sha256` in a Homebrew formula
+    Homebrew,
+    /// `pkgver`, `pkgrel`, and `sha256sums` in an Arch PKGBUILD
+    Pkgbuild,
+}
+
+impl Package {
+    /// Rewrite the package definition for `new`, returning its path
+    pub fn update(
+        &self,
+        root: &Path,
+        old: &Version,
+        new: &Version,
+        tag: &str,
+    ) -> anyhow::Result<PathBuf> {
+        let path = root.join(&self.path);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+
+        let url = match (&self.url, self.kind) {
+            (Some(template), _) => Some(
+                template
+                    .replace("{version}", &new.to_string())
+                    .replace("{tag}", tag),
+            ),
+            (None, Kind::Homebrew) => capture(&url_pattern(), &content)
+                .map(|url| url.replace(&old.to_string(), &new.to_string())),
+            (None, Kind::Pkgbuild) => None,
+        };
+        let sha256 = if self.checksum {
+            let url = url.as_deref().ok_or(anyhow::anyhow!(
+                "{}: `checksum` needs a `url` to download",
+                self.path.display()
+            ))?;
+            Some(sha256_of(url)?)
+        } else {
+            None
+        };
+
+        let content = rewrite(self.kind, &content, new, url.as_deref(), sha256.as_deref())
+            .map_err(|e| anyhow::anyhow!("{}: {e}", self.path.display()))?;
+        crate::cli::files::write(&path, content)?;
+        Ok(path)
+    }
+}
+
+fn url_pattern() -> Regex {
+    Regex::new(r#"(?m)^\s*url\s+"([^"]*)""#).expect("valid regex")
+}
+
+fn capture(pattern: &Regex, content: &str) -> Option<String> {
+    Some(pattern.captures(content)?.get(1)?.as_str().to_string())
+}
+
+/// `pkgver` can't hold `-`, and `1.3.0rc.1` sorts before `1.3.0` in `vercmp`
+fn pkgver(version: &Version) -> String {
+    let mut pkgver = format!("{}.{}.{}", version.major, version.minor, version.patch);
+    if !version.pre.is_empty() {
+        pkgver.push_str(version.pre.as_str());
+    }
+    pkgver
+}
+
+/// Apply the new version (and url/checksum, when known) to a package definition
+fn rewrite(
+    kind: Kind,
+    content: &str,
+    new: &Version,
+    url: Option<&str>,
+    sha256: Option<&str>,
+) -> anyhow::Result<String> {
+    let mut content = content.to_string();
+    let mut set = |pattern: &str, value: &str, required: bool| -> anyhow::Result<()> {
+        let regex = Regex::new(pattern)?;
+        let Some(group) = regex.captures(&content).and_then(|caps| caps.get(1)) else {
+            if required {
+                anyhow::bail!("couldn't find `{pattern}`");
+            }
+            return Ok(());
+        };
+        content.replace_range(group.range(), value);
+        Ok(())
+    };
+
+    match kind {
+        Kind::Homebrew => {
+            if let Some(url) = url {
+                set(r#"(?m)^\s*url\s+"([^"]*)""#, url, true)?;
+            }
+            set(r#"(?m)^\s*version\s+"([^"]*)""#, &new.to_string(), false)?;
+            if let Some(sha256) = sha256 {
+                set(r#"(?m)^\s*sha256\s+"([0-9a-fA-F]*)""#, sha256, true)?;
+            }
+        }
+        Kind::Pkgbuild => {
+            set(r"(?m)^pkgver=(\S*)", &pkgver(new), true)?;
+            set(r"(?m)^pkgrel=(\S*)", "1", false)?;
+            if let Some(sha256) = sha256 {
+                set(
+                    r#"(?m)^sha256sums=\(\s*['"]([0-9a-fA-F]+|SKIP)['"]"#,
+                    sha256,
+                    true,
+                )?;
+            }
+        }
+    }
+
+    Ok(content)
+}
+
+fn sha256_of(url: &str) -> anyhow::Result<String> {
+    let body = http::get(url, &[])
+        .and_then(|response| http::success(url, response))
+        .map_err(|e| anyhow::anyhow!("Failed to download {url}: {e}"))?
+        .body_mut()
+        .with_config()
+        .limit(1024 * 1024 * 1024)
+        .read_to_vec()?;
+
+    let mut hex = String::new();
+    for byte in Sha256::digest(&body) {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    Ok(hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_homebrew() {
+        let formula = "class App < Formula\n  desc \"App\"\n  \
+                       url \"https://example.com/app/archive/v1.2.3.tar.gz\"\n  \
+                       sha256 \"abc123\"\n  license \"MIT\"\nend\n";
+        let url = capture(&url_pattern(), formula)
+            .unwrap()
+            .replace("1.2.3", "1.3.0");
+        let updated = rewrite(
+            Kind::Homebrew,
+            formula,
+            &Version::new(1, 3, 0),
+            Some(&url),
+            Some("def456"),
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            formula
+                .replace("v1.2.3.tar.gz", "v1.3.0.tar.gz")
+                .replace("abc123", "def456")
+        );
+    }
+
+    #[test]
+    fn test_pkgbuild() {
+        let pkgbuild = "pkgname=app\npkgver=1.2.3\npkgrel=3\n\
+                        source=(\"$pkgname-$pkgver.tar.gz::https://example.com/v$pkgver.tar.gz\")\n\
+                        sha256sums=('abc123')\n";
+        let updated = rewrite(
+            Kind::Pkgbuild,
+            pkgbuild,
+            &Version::parse("1.3.0-rc.1").unwrap(),
+            None,
+            Some("def456"),
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            "pkgname=app\npkgver=1.3.0rc.1\npkgrel=1\n\
+             source=(\"$pkgname-$pkgver.tar.gz::https://example.com/v$pkgver.tar.gz\")\n\
+             sha256sums=('def456')\n"
+        );
+        assert!(
+            rewrite(
+                Kind::Pkgbuild,
+                "pkgname=app\n",
+                &Version::new(1, 0, 0),
+                None,
+                None
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use cargo_manifest::Manifest;
+use colored::Colorize;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::{
+    Args, VersionBump, cap_for_tier, check_assert_bump, check_release, choose_bump, files, git,
+    hooks, lineage_tags, lockfile, manifest, manifest_dir, output, past_lineage, plugin,
+    print_output, release, release_members, report_committed, report_updated, rewrite_span,
+    tag_message, tier, verify_build, with_freeze_override, workspace,
+};
+
+/// `--paths`: bump the workspace members whose directories match, each from its own version,
+/// leaving every other member (and the workspace version) alone
+pub fn bump_paths(args: &Args, manifest: &Manifest, config: &Config) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let patterns = args
+        .paths
+        .iter()
+        .map(|pattern| glob::Pattern::new(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+    let members = release_members(args, manifest, config)?
+        .into_iter()
+        .filter(|member| {
+            let dir = member.dir.strip_prefix(root).unwrap_or(&member.dir);
+            patterns.iter().any(|pattern| pattern.matches_path(dir))
+        })
+        .collect::<Vec<_>>();
+    if members.is_empty() {
+        anyhow::bail!(
+            "No workspace members match --paths {}",
+            args.paths.join(", ")
+        );
+    }
+    let (crate_tags, umbrella) = plan_tags(args, config)?;
+
+    let (crates, moving, freeze_override) = plan_members(args, config, members)?;
+    let result = output::Output::new(crates);
+    plugin::run(&config.plugins, root, &result, args.is_quiet())?;
+
+    // For the build check, and to put everything back if a hook aborts the release
+    let backup = if args.verify_build || !config.hooks.is_empty() {
+        let mut paths = moving
+            .iter()
+            .map(|member| member.path.clone())
+            .collect::<Vec<_>>();
+        paths.push(lockfile(root));
+        Some(files::Backup::new(paths)?)
+    } else {
+        None
+    };
+    let mut written = write_members(args, &moving)?;
+    let mut tags = Vec::new();
+    if crate_tags {
+        for member in &moving {
+            let tag = config.tag(&member.name, &member.new);
+            let message = tag_message(args, config, &tag, &member.name, &member.new)?;
+            tags.push((tag, message));
+        }
+    }
+    written.extend(update_lockfile(args, root, &result.crates)?);
+
+    // The build check runs once for the whole release, seeing the first member's versions
+    if args.verify_build
+        && let (Some(backup), Some(first)) = (&backup, moving.first())
+    {
+        verify_build(args, config, backup, &first.old, &first.new)?;
+        let lockfile = lockfile(root);
+        if lockfile.is_file() && !written.contains(&lockfile) {
+            written.push(lockfile);
+        }
+    }
+    for member in &moving {
+        let hooked = hooks::run(
+            &config.hooks,
+            root,
+            &member.old,
+            &member.new,
+            args.is_quiet(),
+        );
+        if let Err(err) = hooked {
+            if let Some(backup) = &backup {
+                backup.restore()?;
+            }
+            anyhow::bail!("{}: {err}; rolled back the version bump", member.name);
+        }
+    }
+
+    if args.commit && !written.is_empty() {
+        let released = result
+            .crates
+            .iter()
+            .filter(|result| result.old_version != result.new_version)
+            .map(|result| format!("{} {}", result.name, result.new_version))
+            .collect::<Vec<_>>();
+        let message =
+            with_freeze_override(format!("Release {}", released.join(", ")), freeze_override);
+        if let Some(tag) = umbrella {
+            tags.push((tag, umbrella_message(&result.crates)));
+        }
+        release::commit_and_tag(root, &written, &message, &tags, None, args.push)?;
+        if !args.is_quiet() {
+            report_committed(&message, &tags);
+        }
+    }
+
+    print_output(args, &result)?;
+    Ok(())
+}
+
+/// Each matched member's bump, the members whose versions move, having passed the release
+/// gates, and the `--override-freeze` reason if any of them is released during a freeze
+fn plan_members<'a>(
+    args: &'a Args,
+    config: &Config,
+    members: Vec<workspace::Member>,
+) -> anyhow::Result<(Vec<output::CrateResult>, Vec<Moving>, Option<&'a str>)> {
+    let options = args.bump_options(config)?;
+    let lineage = lineage_tags(args, config)?;
+    let mut crates = Vec::new();
+    let mut moving = Vec::new();
+    let mut freeze_override = None;
+    for member in members {
+        let old = member.version.as_deref().ok_or(anyhow::anyhow!(
+            "{} inherits the workspace version, so it can't be bumped on its own",
+            member.name
+        ))?;
+        let old = Version::parse(old)
+            .map_err(|e| anyhow::anyhow!("{}: invalid version: {e}", member.name))?;
+        let (bump, reason) = choose_bump(args, config, &old);
+        let tier = tier::resolve(config, &member.name, member.tier);
+        let bump = cap_for_tier(args, &[(member.name.clone(), tier)], bump, &reason);
+        check_assert_bump(args, bump, &reason)
+            .map_err(|e| anyhow::anyhow!("{}: {e}", member.name))?;
+        let mut new = bump.apply(old.clone(), &options)?;
+        if let Some(tags) = &lineage {
+            new = past_lineage(args, config, tags, &member.name, &new)?;
+        }
+
+        if !matches!(bump, VersionBump::Skip) {
+            let overridden = check_release(args, config, &member.name, &old, &new)
+                .map_err(|e| anyhow::anyhow!("{}: {e}", member.name))?;
+            freeze_override = freeze_override.or(overridden);
+            moving.push(Moving {
+                name: member.name.clone(),
+                path: member.dir.join("Cargo.toml"),
+                old: old.clone(),
+                new: new.clone(),
+                bump,
+                tier,
+            });
+        }
+
+        crates.push(output::CrateResult {
+            tier,
+            ..output::CrateResult::new(
+                member.name,
+                old.to_string(),
+                new.to_string(),
+                bump.name(),
+                vec![reason],
+            )
+        });
+    }
+    Ok((crates, moving, freeze_override))
+}
+
+/// Rewrite each moving member's manifest, returning the files written
+fn write_members(args: &Args, moving: &[Moving]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for member in moving {
+        if !args.is_quiet() {
+            print_member_bump(member);
+        }
+        let text = files::read(&member.path)?;
+        let span = manifest::table_span(&text.content, "package").unwrap_or(0..text.content.len());
+        let content = rewrite_span(
+            &text.content,
+            span,
+            "version",
+            &member.old.to_string(),
+            &member.new.to_string(),
+        )?;
+        files::write(&member.path, text.encode(&content)?)?;
+        report_updated(args, &member.path);
+        written.push(member.path.clone());
+    }
+    Ok(written)
+}
+
+/// A matched member whose version moves, worked out (and through the release gates) before
+/// anything is written
+struct Moving {
+    name: String,
+    /// Its `Cargo.toml`
+    path: PathBuf,
+    old: Version,
+    new: Version,
+    bump: VersionBump,
+    tier: Option<tier::Tier>,
+}
+
+/// Whether each crate gets its own tag, and the umbrella tag if there's one to create
+fn plan_tags(args: &Args, config: &Config) -> anyhow::Result<(bool, Option<String>)> {
+    let crate_tags = args.tag
+        && !config
+            .umbrella
+            .as_ref()
+            .is_some_and(|umbrella| umbrella.only);
+    if crate_tags && !config.tag_format().contains("{name}") {
+        anyhow::bail!(
+            "--paths releases crates separately, so --tag needs `{{name}}` in tag_format"
+        );
+    }
+    let umbrella = match &config.umbrella {
+        Some(umbrella) if args.tag => {
+            let tag = umbrella.tag.replace("{date}", &args.clock().today());
+            if git::tag_exists(manifest_dir(&args.path), &tag) {
+                anyhow::bail!("The umbrella tag {tag} already exists");
+            }
+            Some(tag)
+        }
+        _ => None,
+    };
+
+    Ok((crate_tags, umbrella))
+}
+
+/// The umbrella tag's annotation: one line per crate bumped, with its old and new version
+fn umbrella_message(crates: &[output::CrateResult]) -> String {
+    let bumped = crates
+        .iter()
+        .filter(|result| result.old_version != result.new_version)
+        .collect::<Vec<_>>();
+    let plural = if bumped.len() == 1 { "" } else { "s" };
+    let mut message = format!("Release of {} crate{plural}\n", bumped.len());
+    for result in bumped {
+        let _ = write!(
+            message,
+            "\n- {} {} → {}",
+            result.name, result.old_version, result.new_version
+        );
+    }
+    message
+}
+
+/// A member's "name old → new (description)" line, labelled with its tier
+fn print_member_bump(member: &Moving) {
+    let tier = member
+        .tier
+        .map_or_else(String::new, |tier| format!(" [{}]", tier.name()));
+    eprintln!(
+        "{} {}{} {} {} {} ({})",
+        member.bump.emoji(),
+        member.name.bold(),
+        tier.dimmed(),
+        member.old.to_string().cyan(),
+        "→".bright_white(),
+        member.new.to_string().bright_green().bold(),
+        member.bump.description().color(member.bump.color())
+    );
+}
+
+/// Move the bumped members' `Cargo.lock` entries, returning the lockfile if it changed
+fn update_lockfile(
+    args: &Args,
+    root: &Path,
+    crates: &[output::CrateResult],
+) -> anyhow::Result<Option<PathBuf>> {
+    let bumps = crates
+        .iter()
+        .filter(|result| result.old_version != result.new_version)
+        .map(|result| lockfile::Bump {
+            name: result.name.clone(),
+            old: result.old_version.clone(),
+            new: result.new_version.clone(),
+        })
+        .collect::<Vec<_>>();
+    let path = lockfile(root);
+    if !lockfile::write(&path, &bumps)? {
+        return Ok(None);
+    }
+    report_updated(args, &path);
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_umbrella_message() {
+        let result = |name: &str, old: &str, new: &str, bump: &str| {
+            output::CrateResult::new(
+                name.to_string(),
+                old.to_string(),
+                new.to_string(),
+                bump.to_string(),
+                Vec::new(),
+            )
+        };
+        let crates = [
+            result("net-client", "0.3.1", "0.3.2", "patch"),
+            result("net-server", "0.3.4", "0.3.4", "skip"),
+            result("net-proto", "1.0.0", "1.1.0", "minor"),
+        ];
+        assert_eq!(
+            umbrella_message(&crates),
+            "Release of 2 crates\n\n- net-client 0.3.1 → 0.3.2\n- net-proto 1.0.0 → 1.1.0"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use colored::Colorize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::output::Output;
+
+/// An external policy check run before anything is written
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Plugin {
+    /// Run through `sh -c` in the manifest's directory
+    pub command: String,
+}
+
+/// What a plugin prints on stdout
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Verdict {
+    /// Whether the bump may go ahead
+    pub allow: bool,
+    /// Shown to the user as warnings when allowed, errors when denied
+    #[serde(default)]
+    pub messages: Vec<String>,
+}
+
+/// JSON Schema for a plugin's verdict
+pub fn schema() -> schemars::Schema {
+    schemars::schema_for!(Verdict)
+}
+
+/// Pass the planned bump (the `--json` result) to each plugin on stdin and stop at the first
+/// that denies it. A plugin that fails or prints anything but a verdict denies it too
+pub fn run(plugins: &[Plugin], dir: &Path, plan: &Output, quiet: bool) -> anyhow::Result<()> {
+    let plan = serde_json::to_vec(plan)?;
+    for plugin in plugins {
+        let verdict = ask(plugin, dir, &plan)
+            .map_err(|e| anyhow::anyhow!("Plugin `{}` failed: {e}", plugin.command))?;
+
+        if verdict.allow {
+            if !quiet {
+                for message in &verdict.messages {
+                    eprintln!("{} {message}", "⚠".yellow().bold());
+                }
+            }
+            continue;
+        }
+        for message in &verdict.messages {
+            eprintln!("{} {message}", "✗".red().bold());
+        }
+        anyhow::bail!("Plugin `{}` denied the bump", plugin.command);
+    }
+    Ok(())
+}
+
+fn ask(plugin: &Plugin, dir: &Path, plan: &[u8]) -> anyhow::Result<Verdict> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(&plugin.command)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    // A plugin that exits without reading the plan closes the pipe; its verdict still counts
+    if let Some(mut stdin) = child.stdin.take()
+        && let Err(err) = stdin.write_all(plan)
+        && err.kind() != std::io::ErrorKind::BrokenPipe
+    {
+        return Err(err.into());
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("exited with {}", output.status);
+    }
+    parse(&output.stdout)
+}
+
+fn parse(stdout: &[u8]) -> anyhow::Result<Verdict> {
+    serde_json::from_slice(stdout).map_err(|e| {
+        anyhow::anyhow!("expected {{\"allow\": true|false, \"messages\": [...]}} on stdout ({e})")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let verdict = parse(br#"{"allow": false, "messages": ["frozen until Monday"]}"#).unwrap();
+        assert!(!verdict.allow);
+        assert_eq!(verdict.messages, ["frozen until Monday"]);
+        assert!(parse(br#"{"allow": true}"#).unwrap().messages.is_empty());
+        assert!(parse(b"ok").is_err());
+        assert!(parse(br#"{"allowed": true}"#).is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let plan = Output::new(Vec::new());
+        let dir = Path::new(".");
+        let plugin = |command: &str| Plugin {
+            command: command.to_string(),
+        };
+        let allow = plugin(r#"grep -q schema_version && echo '{"allow": true}'"#);
+        assert!(run(&[allow], dir, &plan, true).is_ok());
+        let deny = plugin(r#"echo '{"allow": false, "messages": ["no"]}'"#);
+        assert!(run(&[deny], dir, &plan, true).is_err());
+        assert!(run(&[plugin("exit 1")], dir, &plan, true).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// How often a non-TTY stderr gets a "label: n/total" line
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Progress for a long loop: a bar on a TTY, periodic stderr lines otherwise, nothing when quiet
+pub struct Progress {
+    label: String,
+    total: usize,
+    done: usize,
+    mode: Mode,
+}
+
+enum Mode {
+    Hidden,
+    Bar(ProgressBar),
+    Log(Instant),
+}
+
+impl Progress {
+    pub fn new(label: &str, total: usize, quiet: bool) -> Self {
+        let mode = if quiet {
+            Mode::Hidden
+        } else if std::io::stderr().is_terminal() {
+            let bar = ProgressBar::new(total as u64).with_style(
+                ProgressStyle::with_template("{prefix} [{bar:30}] {pos}/{len} {msg}")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar())
+                    .progress_chars("=> "),
+            );
+            bar.set_prefix(label.to_string());
+            Mode::Bar(bar)
+        } else {
+            Mode::Log(Instant::now())
+        };
+
+        Self {
+            label: label.to_string(),
+            total,
+            done: 0,
+            mode,
+        }
+    }
+
+    /// Mark one item (named `item`) as done
+    pub fn inc(&mut self, item: &str) {
+        self.done += 1;
+        match &mut self.mode {
+            Mode::Hidden => {}
+            Mode::Bar(bar) => {
+                bar.set_message(item.to_string());
+                bar.inc(1);
+            }
+            Mode::Log(last) => {
+                if last.elapsed() >= LOG_INTERVAL {
+                    eprintln!("{}: {}/{} ({item})", self.label, self.done, self.total);
+                    *last = Instant::now();
+                }
+            }
+        }
+    }
+
+    /// Print a row of the command's report to stdout without tearing the bar, which is
+    /// on stderr
+    pub fn println(&self, line: &str) {
+        match &self.mode {
+            Mode::Bar(bar) => bar.suspend(|| println!("{line}")),
+            Mode::Hidden | Mode::Log(_) => println!("{line}"),
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if let Mode::Bar(bar) = &self.mode {
+            bar.finish_and_clear();
+        }
+    }
+}
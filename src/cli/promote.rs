@@ -0,0 +1,149 @@
+//! `version promote`: move a prerelease along the release channels (alpha → beta → rc →
+//! the release) without touching its major, minor, or patch. It runs the normal bump to
+//! the promoted version, the way `--to` would
+
+use semver::{Prerelease, Version};
+
+use crate::cli::config::{self, Config};
+use crate::cli::{Args, Command};
+
+/// The channels when the config has no `channels`
+const CHANNELS: [&str; 3] = ["alpha", "beta", "rc"];
+
+/// What `promote` takes to leave the channels for the stable release
+pub const RELEASE: &str = "release";
+
+/// Whether this run is a `promote`
+pub fn active(args: &Args) -> bool {
+    matches!(args.command, Some(Command::Promote { .. }))
+}
+
+/// The config's `channels` (or [`CHANNELS`]), checked to be prerelease identifiers that
+/// semver sorts in the same order, so each promotion is a newer version to cargo too
+pub fn channels(config: &Config) -> anyhow::Result<Vec<&str>> {
+    let channels = if config.channels.is_empty() {
+        CHANNELS.to_vec()
+    } else {
+        config.channels.iter().map(String::as_str).collect()
+    };
+    for channel in &channels {
+        config::valid_pre_id(channel)?;
+        if channel.contains('.') {
+            anyhow::bail!("`{channel}` has a `.`, but a channel is a single identifier");
+        }
+        if *channel == RELEASE {
+            anyhow::bail!("`{RELEASE}` is where the channels lead, so it can't be one of them");
+        }
+    }
+    for pair in channels.windows(2) {
+        let [earlier, later] = pair else {
+            continue;
+        };
+        if Prerelease::new(&format!("{earlier}.0"))? >= Prerelease::new(&format!("{later}.0"))? {
+            anyhow::bail!(
+                "`{later}` comes after `{earlier}` in the channels, but semver sorts it before (or with) it, so {later} versions would look older to cargo"
+            );
+        }
+    }
+    Ok(channels)
+}
+
+/// The version `version` is promoted to: `channel`'s first prerelease, by default the next
+/// channel's, and the release after the last channel
+pub fn target(
+    version: &Version,
+    channels: &[&str],
+    channel: Option<&str>,
+) -> anyhow::Result<Version> {
+    let listed = channels.join(", ");
+    let current = version.pre.split('.').next().unwrap_or_default();
+    if version.pre.is_empty() {
+        anyhow::bail!("{version} isn't a prerelease, so there's no channel to promote it from");
+    }
+    let Some(from) = channels.iter().position(|name| *name == current) else {
+        anyhow::bail!("{version} is on `{current}`, which isn't one of the channels ({listed})");
+    };
+    let to = match channel {
+        None => from + 1,
+        Some(RELEASE) => channels.len(),
+        Some(channel) => channels
+            .iter()
+            .position(|name| *name == channel)
+            .ok_or_else(|| {
+                anyhow::anyhow!("`{channel}` isn't one of the channels ({listed}) or `{RELEASE}`")
+            })?,
+    };
+    if to == from {
+        anyhow::bail!("{version} is already on `{current}`");
+    }
+    if to < from {
+        anyhow::bail!(
+            "{version} is on `{current}`, past `{}`; promote only moves forward",
+            channels[to]
+        );
+    }
+
+    let mut promoted = Version::new(version.major, version.minor, version.patch);
+    if let Some(channel) = channels.get(to) {
+        promoted.pre = Prerelease::new(&format!("{channel}.0"))?;
+    }
+    Ok(promoted)
+}
+
+/// Turn `args` into the bump to the promoted version
+pub fn prepare(args: &mut Args, config: &Config, version: &Version) -> anyhow::Result<()> {
+    let Some(Command::Promote { channel }) = &args.command else {
+        return Ok(());
+    };
+    if args.version_bump.is_some() || args.to.is_some() || args.from_git {
+        anyhow::bail!("promote picks the version itself, so it takes no bump, --to, or --from-git");
+    }
+    if !args.paths.is_empty() {
+        anyhow::bail!("promote releases the package or the whole workspace, not --paths");
+    }
+    args.to = Some(target(version, &channels(config)?, channel.as_deref())?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target() {
+        let version = |text: &str| Version::parse(text).unwrap();
+        let promote = |from: &str, channel: Option<&str>| {
+            target(&version(from), &CHANNELS, channel).map(|promoted| promoted.to_string())
+        };
+        assert_eq!(promote("1.3.0-alpha.4", None).unwrap(), "1.3.0-beta.0");
+        assert_eq!(promote("1.3.0-beta.2", None).unwrap(), "1.3.0-rc.0");
+        assert_eq!(promote("1.3.0-rc.1", None).unwrap(), "1.3.0");
+        assert_eq!(promote("1.3.0-alpha.4", Some("rc")).unwrap(), "1.3.0-rc.0");
+        assert_eq!(promote("2.0.0-alpha.0", Some("release")).unwrap(), "2.0.0");
+        // Downgrades, and versions off the channels
+        assert!(promote("1.3.0-rc.1", Some("beta")).is_err());
+        assert!(promote("1.3.0-beta.1", Some("beta")).is_err());
+        assert!(promote("1.3.0-dev.0", None).is_err());
+        assert!(promote("1.3.0", None).is_err());
+        assert!(promote("1.3.0-alpha.0", Some("gamma")).is_err());
+    }
+
+    #[test]
+    fn test_channels() {
+        assert_eq!(channels(&Config::default()).unwrap(), CHANNELS);
+        let config = |channels: &[&str]| Config {
+            channels: channels.iter().map(ToString::to_string).collect(),
+            ..Config::default()
+        };
+        assert_eq!(
+            channels(&config(&["beta", "canary"])).unwrap(),
+            ["beta", "canary"]
+        );
+        // `nightly` sorts after `beta`, so it can't come first
+        assert!(channels(&config(&["nightly", "beta"])).is_err());
+        assert!(channels(&config(&["alpha", "alpha"])).is_err());
+        assert!(channels(&config(&["alpha", "release"])).is_err());
+        assert!(channels(&config(&["alpha beta"])).is_err());
+        assert!(channels(&config(&["alpha.1"])).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+
+use crate::cli::{files, git, manifest};
+
+/// The table release traceability is recorded in
+const TABLE: &str = "package.metadata.release";
+
+/// The URL of the CI run this is part of, on GitHub Actions, GitLab CI, Buildkite, or
+/// `CircleCI`
+fn ci_run_url(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    if let (Some(server), Some(repository), Some(run)) = (
+        var("GITHUB_SERVER_URL"),
+        var("GITHUB_REPOSITORY"),
+        var("GITHUB_RUN_ID"),
+    ) {
+        let attempt = var("GITHUB_RUN_ATTEMPT")
+            .map(|attempt| format!("/attempts/{attempt}"))
+            .unwrap_or_default();
+        return Some(format!("{server}/{repository}/actions/runs/{run}{attempt}"));
+    }
+    ["CI_JOB_URL", "BUILDKITE_BUILD_URL", "CIRCLE_BUILD_URL"]
+        .into_iter()
+        .find_map(var)
+        .filter(|url| !url.is_empty())
+}
+
+/// `content` with `commit` and `ci_run` set under `[package.metadata.release]`, replacing
+/// what an earlier release recorded (a `ci_run` from outside CI is dropped, not kept stale)
+fn record(content: &str, commit: Option<&str>, ci_run: Option<&str>) -> String {
+    let entries = [("commit", commit), ("ci_run", ci_run)];
+    let lines = entries
+        .iter()
+        .filter_map(|(key, value)| {
+            value.map(|value| format!("{key} = {}\n", toml::Value::from(value)))
+        })
+        .collect::<String>();
+
+    let Some(span) = manifest::table_span(content, TABLE) else {
+        if lines.is_empty() {
+            return content.to_string();
+        }
+        let separator = match content {
+            "" => "",
+            _ if content.ends_with("\n\n") => "",
+            _ if content.ends_with('\n') => "\n",
+            _ => "\n\n",
+        };
+        return format!("{content}{separator}[{TABLE}]\n{lines}");
+    };
+
+    let table = &content[span.clone()];
+    let kept = table
+        .split_inclusive('\n')
+        .filter(|line| !entries.iter().any(|(key, _)| manifest::is_key(line, key)))
+        .collect::<String>();
+    // New keys go after the table's last entry, before the blank lines ahead of the next
+    let body = kept.trim_end_matches('\n');
+    let trailing = &kept[body.len()..];
+    format!(
+        "{}{body}\n{lines}{}{}",
+        &content[..span.start],
+        &trailing[trailing.len().min(1)..],
+        &content[span.end..]
+    )
+}
+
+/// Record the commit the release was cut from, and the CI run making it, in each of
+/// `manifests` that has a `[package]`, returning the ones written
+pub fn write(root: &Path, manifests: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let commit = git::git(root, &["rev-parse", "HEAD"]).ok();
+    let ci_run = ci_run_url(|name| std::env::var(name).ok());
+    let mut written = Vec::new();
+    for path in manifests {
+        let text = files::read(path)?;
+        if manifest::table_span(&text.content, "package").is_none() {
+            continue;
+        }
+        let content = record(&text.content, commit.as_deref(), ci_run.as_deref());
+        if content != text.content {
+            files::write(path, text.encode(&content)?)?;
+            written.push(path.clone());
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ci_run_url() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_string())
+            }
+        };
+        assert_eq!(
+            ci_run_url(env(&[
+                ("GITHUB_SERVER_URL", "https://github.com"),
+                ("GITHUB_REPOSITORY", "o/r"),
+                ("GITHUB_RUN_ID", "42"),
+                ("GITHUB_RUN_ATTEMPT", "2"),
+            ]))
+            .as_deref(),
+            Some("https://github.com/o/r/actions/runs/42/attempts/2")
+        );
+        assert_eq!(
+            ci_run_url(env(&[("CI_JOB_URL", "https://gitlab.com/o/r/-/jobs/7")])).as_deref(),
+            Some("https://gitlab.com/o/r/-/jobs/7")
+        );
+        assert_eq!(ci_run_url(env(&[])), None);
+    }
+
+    #[test]
+    fn test_record() {
+        let manifest = "[package]\nname = \"app\"\nversion = \"1.3.0\"\n";
+        assert_eq!(
+            record(manifest, Some("abc123"), Some("https://ci/1")),
+            "[package]\nname = \"app\"\nversion = \"1.3.0\"\n\n[package.metadata.release]\ncommit = \"abc123\"\nci_run = \"https://ci/1\"\n"
+        );
+
+        let recorded = "[package]\nname = \"app\"\n\n[package.metadata.release]\nci_run = \"https://ci/1\"\ncommit = \"abc123\"\nnotes = true\n\n[dependencies]\n";
+        assert_eq!(
+            record(recorded, Some("def456"), None),
+            "[package]\nname = \"app\"\n\n[package.metadata.release]\nnotes = true\ncommit = \"def456\"\n\n[dependencies]\n"
+        );
+        assert_eq!(record(manifest, None, None), manifest);
+    }
+}
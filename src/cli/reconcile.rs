@@ -0,0 +1,299 @@
+use cargo_manifest::Manifest;
+use colored::Colorize;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::files::Text;
+use crate::cli::{
+    Args, crate_names, git, manifest_dir, registry, release, report_committed, tag_message,
+    write_version,
+};
+
+/// Where the version was found, and what's known about the other two sources
+#[derive(Debug)]
+struct Sources {
+    manifest: Version,
+    /// The highest release tag merged into HEAD
+    latest_tag: Option<Version>,
+    /// Whether the manifest's own version has a tag
+    manifest_tagged: bool,
+    /// The highest unyanked version on the registry, `None` if unpublished or unknown
+    published: Option<Version>,
+}
+
+/// The fix for a drift, applied by `reconcile --apply`
+#[derive(Debug, PartialEq)]
+enum Action {
+    /// Tag the commit that set the manifest's version
+    Retag(Version),
+    /// Rewrite the manifest to a version that was already released, and commit it
+    SetManifest(Version),
+    /// Release a version past everything published, so the repo and the registry agree again
+    CatchUp(Version),
+}
+
+/// `version reconcile`: compare the manifest, the tags, and the registry, explain how they
+/// disagree, and (with `apply`) make them agree
+pub fn reconcile(
+    args: &Args,
+    text: &Text,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    offline: bool,
+    apply: bool,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let names = crate_names(args, manifest, config)?;
+    let name = names.first().map_or("", String::as_str);
+
+    let latest_tag = git::merged_tags(root)?
+        .iter()
+        .filter_map(|tag| config.parse_tag(tag))
+        .filter(|(tag_name, _)| tag_name.is_empty() || tag_name == name)
+        .map(|(_, version)| version)
+        .max();
+    let published = if offline || name.is_empty() {
+        None
+    } else {
+        match registry::versions(&args.registry_index, name) {
+            Ok(versions) => versions.into_iter().max(),
+            Err(e) => {
+                eprintln!(
+                    "{} Couldn't look {name} up in {}, comparing with the tags only: {e}",
+                    "⚠".yellow().bold(),
+                    args.registry_index
+                );
+                None
+            }
+        }
+    };
+    let sources = Sources {
+        manifest: version.clone(),
+        manifest_tagged: git::tag_exists(root, &config.tag(name, version)),
+        latest_tag,
+        published,
+    };
+
+    println!("Manifest:   {}", sources.manifest);
+    println!("Latest tag: {}", describe(sources.latest_tag.as_ref()));
+    println!("Registry:   {}", describe(sources.published.as_ref()));
+
+    // The three sources are the report; what to make of them is decoration
+    let Some((explanation, action)) = plan(&sources) else {
+        if !args.is_quiet() {
+            eprintln!(
+                "{} The manifest, tags, and registry agree",
+                "✓".green().bold()
+            );
+        }
+        return Ok(());
+    };
+    if !args.is_quiet() {
+        eprintln!("\n{} {explanation}", "⚠".yellow().bold());
+        eprintln!(
+            "Proposed: {}",
+            propose(&action, &config.tag(name, version_of(&action)))
+        );
+    }
+
+    if apply {
+        apply_action(
+            args,
+            text,
+            manifest,
+            config,
+            name,
+            &sources.manifest,
+            &action,
+        )?;
+    } else if !args.is_quiet() {
+        eprintln!("Run `version reconcile --apply` to do it");
+    }
+    Ok(())
+}
+
+fn describe(version: Option<&Version>) -> String {
+    version.map_or_else(|| "none".to_string(), Version::to_string)
+}
+
+fn version_of(action: &Action) -> &Version {
+    match action {
+        Action::Retag(version) | Action::SetManifest(version) | Action::CatchUp(version) => version,
+    }
+}
+
+fn propose(action: &Action, tag: &str) -> String {
+    match action {
+        Action::Retag(_) => format!("tag the commit that set the manifest's version as {tag}"),
+        Action::SetManifest(version) => {
+            format!("set the manifest back to {version} and commit it")
+        }
+        Action::CatchUp(version) => {
+            format!("release {version}: set the manifest, commit, and tag it as {tag}")
+        }
+    }
+}
+
+/// How the sources disagree and what fixes it, or `None` when they agree. A newer registry
+/// release wins over the tags, and an existing tag wins over a hand-edited manifest
+fn plan(sources: &Sources) -> Option<(String, Action)> {
+    let Sources {
+        manifest,
+        latest_tag,
+        manifest_tagged,
+        published,
+    } = sources;
+    let released = latest_tag.iter().chain(published).max();
+
+    if let Some(published) = published
+        && published > manifest
+        && latest_tag.as_ref().is_none_or(|tag| published > tag)
+    {
+        let mut next = published.clone();
+        next.patch += 1;
+        next.pre = semver::Prerelease::EMPTY;
+        next.build = semver::BuildMetadata::EMPTY;
+        return Some((
+            format!(
+                "{published} is on the registry but not in this history (published from another branch or machine?); its number can't be reused"
+            ),
+            Action::CatchUp(next),
+        ));
+    }
+
+    if let Some(tag) = latest_tag
+        && tag > manifest
+    {
+        return Some((
+            format!(
+                "The manifest is at {manifest}, behind the latest release tag {tag} (hand-edited or reverted?)"
+            ),
+            Action::SetManifest(tag.clone()),
+        ));
+    }
+
+    if !manifest_tagged && released.is_some_and(|released| manifest >= released) {
+        let why = if published.as_ref() == Some(manifest) {
+            "is published but was never tagged"
+        } else {
+            "is ahead of the latest tag but was never tagged (hand-edited, or released without --tag?)"
+        };
+        return Some((
+            format!("The manifest's {manifest} {why}"),
+            Action::Retag(manifest.clone()),
+        ));
+    }
+
+    None
+}
+
+fn apply_action(
+    args: &Args,
+    text: &Text,
+    manifest: &Manifest,
+    config: &Config,
+    name: &str,
+    current: &Version,
+    action: &Action,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let version = version_of(action);
+    let tag = config.tag(name, version);
+
+    match action {
+        Action::Retag(_) => {
+            // `root` is the manifest's directory, so its file name is the path from there
+            let path = args.path.file_name().unwrap_or_default().to_string_lossy();
+            let commit = git::git(root, &["log", "-1", "--format=%H", "--", &path])?;
+            if commit.is_empty() {
+                anyhow::bail!("{path} isn't committed yet; commit it before tagging");
+            }
+            let message = tag_message(args, config, &tag, name, version)?;
+            git::git(
+                root,
+                &[
+                    "tag",
+                    "-a",
+                    "--cleanup=verbatim",
+                    &tag,
+                    "-m",
+                    &message,
+                    &commit,
+                ],
+            )?;
+            eprintln!(
+                "{} Tagged {} at {}",
+                "✓".green().bold(),
+                tag.bold(),
+                &commit[..commit.len().min(7)]
+            );
+        }
+        Action::SetManifest(_) | Action::CatchUp(_) => {
+            let written =
+                write_version(args, text, manifest, config, &current.to_string(), version)?;
+            let message = if matches!(action, Action::SetManifest(_)) {
+                format!("Reconcile version with {tag}")
+            } else {
+                format!("Release {version}")
+            };
+            let tags = if matches!(action, Action::CatchUp(_)) {
+                vec![(tag.clone(), tag_message(args, config, &tag, name, version)?)]
+            } else {
+                Vec::new()
+            };
+            release::commit_and_tag(root, &written, &message, &tags, None, false)?;
+            report_committed(&message, &tags);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(
+        manifest: &str,
+        latest_tag: Option<&str>,
+        manifest_tagged: bool,
+        published: Option<&str>,
+    ) -> Sources {
+        let v = |s: &str| Version::parse(s).unwrap();
+        Sources {
+            manifest: v(manifest),
+            latest_tag: latest_tag.map(v),
+            manifest_tagged,
+            published: published.map(v),
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let action = |sources| plan(&sources).map(|(_, action)| action);
+        let v = |s| Version::parse(s).unwrap();
+
+        assert_eq!(
+            action(sources("1.2.3", Some("1.2.3"), true, Some("1.2.3"))),
+            None
+        );
+        // Nothing released to compare with
+        assert_eq!(action(sources("1.2.3", None, false, None)), None);
+        assert_eq!(
+            action(sources("1.2.3", Some("1.2.3"), true, Some("1.4.0"))),
+            Some(Action::CatchUp(v("1.4.1")))
+        );
+        assert_eq!(
+            action(sources("1.0.0", Some("1.2.3"), false, Some("1.2.3"))),
+            Some(Action::SetManifest(v("1.2.3")))
+        );
+        assert_eq!(
+            action(sources("1.3.0", Some("1.2.3"), false, Some("1.3.0"))),
+            Some(Action::Retag(v("1.3.0")))
+        );
+        assert_eq!(
+            action(sources("2.0.0", Some("1.2.3"), false, None)),
+            Some(Action::Retag(v("2.0.0")))
+        );
+    }
+}
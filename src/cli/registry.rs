@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use semver::Version;
+use serde::Deserialize;
+
+use crate::cli::http;
+
+pub const CRATES_IO_INDEX: &str = "https://index.crates.io";
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    vers: String,
+    yanked: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Published {
+    Yes,
+    Yanked,
+    Never,
+}
+
+/// The directories a crate's file sits under in an index (`se/rd`, `3/s`, ...)
+fn prefix(name: &str) -> String {
+    match name.len() {
+        1 => "1".to_string(),
+        2 => "2".to_string(),
+        3 => format!("3/{}", &name[..1]),
+        _ => format!("{}/{}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Path of a crate's file in a sparse index (`se/rd/serde`, `3/s/syn`, ...)
+fn index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    format!("{}/{name}", prefix(&name))
+}
+
+fn parse_index(body: &str, version: &Version) -> anyhow::Result<Published> {
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: IndexEntry = serde_json::from_str(line)?;
+        if Version::parse(&entry.vers).is_ok_and(|v| v == *version) {
+            return Ok(if entry.yanked {
+                Published::Yanked
+            } else {
+                Published::Yes
+            });
+        }
+    }
+
+    Ok(Published::Never)
+}
+
+/// Every version of `name` published and not yanked, in index order (oldest first)
+fn parse_versions(body: &str) -> anyhow::Result<Vec<Version>> {
+    let mut versions = Vec::new();
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: IndexEntry = serde_json::from_str(line)?;
+        if !entry.yanked
+            && let Ok(version) = Version::parse(&entry.vers)
+        {
+            versions.push(version);
+        }
+    }
+    Ok(versions)
+}
+
+/// Index responses kept by a long-running process like `version serve`, by URL
+type Cache = Mutex<BTreeMap<String, (Instant, Option<String>)>>;
+
+static CACHE_FOR: OnceLock<Duration> = OnceLock::new();
+static CACHE: Cache = Mutex::new(BTreeMap::new());
+
+/// Reuse each index response for `ttl` instead of fetching it again
+pub fn cache_for(ttl: Duration) {
+    let _ = CACHE_FOR.set(ttl);
+}
+
+/// A crate's file in the sparse index at `index`, `None` if the index has never seen it
+fn fetch(index: &str, name: &str) -> anyhow::Result<Option<String>> {
+    let url = format!("{}/{}", index.trim_end_matches('/'), index_path(name));
+    let Some(ttl) = CACHE_FOR.get() else {
+        return fetch_url(&url);
+    };
+
+    let mut cache = CACHE
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some((fetched, body)) = cache.get(&url)
+        && fetched.elapsed() < *ttl
+    {
+        return Ok(body.clone());
+    }
+    let body = fetch_url(&url)?;
+    cache.insert(url, (Instant::now(), body.clone()));
+    Ok(body)
+}
+
+fn fetch_url(url: &str) -> anyhow::Result<Option<String>> {
+    let response = http::get(url, &[])?;
+    match response.status().as_u16() {
+        // The index serves 404 (or 403 on some mirrors) for crates it has never seen
+        404 | 403 => Ok(None),
+        _ => Ok(Some(
+            http::success(url, response)?.into_body().read_to_string()?,
+        )),
+    }
+}
+
+/// Look `name@version` up in the sparse index at `index`
+pub fn published(index: &str, name: &str, version: &Version) -> anyhow::Result<Published> {
+    match fetch(index, name)? {
+        Some(body) => parse_index(&body, version),
+        None => Ok(Published::Never),
+    }
+}
+
+/// The unyanked versions of `name` in the sparse index at `index`
+pub fn versions(index: &str, name: &str) -> anyhow::Result<Vec<Version>> {
+    match fetch(index, name)? {
+        Some(body) => parse_versions(&body),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[derive(Deserialize)]
+struct IndexConfig {
+    dl: String,
+}
+
+/// Where the index's `dl` template says `name@version` is downloaded from. A template
+/// without markers is a prefix for `/{crate}/{version}/download`
+fn download_url(dl: &str, name: &str, version: &Version) -> String {
+    let markers = ["{crate}", "{version}", "{prefix}", "{lowerprefix}"];
+    if !markers.iter().any(|marker| dl.contains(marker)) {
+        return format!("{}/{name}/{version}/download", dl.trim_end_matches('/'));
+    }
+    dl.replace("{crate}", name)
+        .replace("{version}", &version.to_string())
+        .replace("{lowerprefix}", &prefix(&name.to_lowercase()))
+        .replace("{prefix}", &prefix(name))
+}
+
+/// Download the `.crate` file of `name@version` from the registry behind `index`
+pub fn download(index: &str, name: &str, version: &Version) -> anyhow::Result<Vec<u8>> {
+    let config_url = format!("{}/config.json", index.trim_end_matches('/'));
+    let config: IndexConfig = serde_json::from_str(
+        &http::success(&config_url, http::get(&config_url, &[])?)?
+            .into_body()
+            .read_to_string()?,
+    )?;
+    let url = download_url(&config.dl, name, version);
+    let response = http::get(&url, &[])?;
+    if response.status().as_u16() == 404 {
+        anyhow::bail!("{name} {version} isn't published ({url} is missing)");
+    }
+    Ok(http::success(&url, response)?
+        .body_mut()
+        .with_config()
+        .limit(1024 * 1024 * 1024)
+        .read_to_vec()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_path() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("ab"), "2/ab");
+        assert_eq!(index_path("syn"), "3/s/syn");
+        assert_eq!(index_path("Serde"), "se/rd/serde");
+    }
+
+    #[test]
+    fn test_download_url() {
+        let version = Version::new(1, 0, 0);
+        assert_eq!(
+            download_url("https://static.crates.io/crates", "serde", &version),
+            "https://static.crates.io/crates/serde/1.0.0/download"
+        );
+        assert_eq!(
+            download_url(
+                "https://dl.example.com/{prefix}/{lowerprefix}/{crate}-{version}.crate",
+                "Serde",
+                &version
+            ),
+            "https://dl.example.com/Se/rd/se/rd/Serde-1.0.0.crate"
+        );
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let body = concat!(
+            r#"{"name":"a","vers":"1.0.0","yanked":false}"#,
+            "\n",
+            r#"{"name":"a","vers":"1.0.1","yanked":true}"#,
+            "\n"
+        );
+        let check = |v: &str| parse_index(body, &Version::parse(v).unwrap()).unwrap();
+
+        assert_eq!(check("1.0.0"), Published::Yes);
+        assert_eq!(check("1.0.1"), Published::Yanked);
+        assert_eq!(check("1.0.2"), Published::Never);
+        assert_eq!(parse_versions(body).unwrap(), [Version::new(1, 0, 0)]);
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::cli::git;
+
+/// Where `--notes` keeps the release metadata, off the default `refs/notes/commits`
+pub const NOTES_REF: &str = "refs/notes/version";
+
+/// Commit the bumped files, tag the commit (each tag with its own message), note it, and
+/// optionally push all of it
+pub fn commit_and_tag(
+    root: &Path,
+    files: &[PathBuf],
+    message: &str,
+    tags: &[(String, String)],
+    note: Option<&str>,
+    push: bool,
+) -> anyhow::Result<()> {
+    let mut add = vec!["add", "--"];
+    let files = files
+        .iter()
+        .map(|file| file.to_string_lossy())
+        .collect::<Vec<_>>();
+    add.extend(files.iter().map(AsRef::as_ref));
+    git::git(root, &add)?;
+    git::git(root, &["commit", "-m", message])?;
+
+    for (tag, tag_message) in tags {
+        // Verbatim, so a changelog's `## ` heading isn't stripped as a comment
+        git::git(
+            root,
+            &["tag", "-a", "--cleanup=verbatim", tag, "-m", tag_message],
+        )?;
+    }
+
+    if let Some(note) = note {
+        git::git(
+            root,
+            &["notes", "--ref", NOTES_REF, "add", "-f", "-m", note, "HEAD"],
+        )?;
+    }
+
+    if push {
+        let mut refs = vec!["push".to_string(), "--atomic".to_string()];
+        refs.extend(["origin".to_string(), "HEAD".to_string()]);
+        refs.extend(tags.iter().map(|(tag, _)| format!("refs/tags/{tag}")));
+        if note.is_some() {
+            refs.push(NOTES_REF.to_string());
+        }
+        git::git(root, &refs.iter().map(String::as_str).collect::<Vec<_>>())?;
+    }
+
+    Ok(())
+}
+
+/// `--artifact` files as `sha256sum` prints them: the hex digest, two spaces, and the file name
+pub fn checksums(paths: &[PathBuf]) -> anyhow::Result<String> {
+    let mut out = String::new();
+    for path in paths {
+        let bytes = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+        let name = path
+            .file_name()
+            .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+        let digest = Sha256::digest(&bytes)
+            .iter()
+            .fold(String::new(), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            });
+        let _ = writeln!(out, "{digest}  {name}");
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("app.tar.gz");
+        std::fs::write(&path, "abc").unwrap();
+
+        assert_eq!(
+            checksums(&[path]).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  app.tar.gz\n"
+        );
+        assert!(checksums(&[dir.join("missing")]).is_err());
+    }
+}
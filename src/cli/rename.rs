@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use cargo_manifest::Manifest;
+
+use crate::cli::config::Config;
+use crate::cli::{Args, git, manifest_dir, previous_tag, release_members};
+
+/// A crate released under another name at the last release tag
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+/// The crates being released whose manifest named them differently at the last release tag
+pub fn renames(args: &Args, manifest: &Manifest, config: &Config) -> anyhow::Result<Vec<Rename>> {
+    let Some(tag) = previous_tag(args) else {
+        return Ok(Vec::new());
+    };
+    let crates = if args.workspace {
+        release_members(args, manifest, config)?
+            .into_iter()
+            .map(|member| (member.name, member.dir))
+            .collect()
+    } else {
+        manifest
+            .package
+            .iter()
+            .map(|package| (package.name.clone(), manifest_dir(&args.path).to_path_buf()))
+            .collect::<Vec<_>>()
+    };
+
+    Ok(crates
+        .into_iter()
+        .filter_map(|(name, dir)| {
+            let from = name_at(&dir, &tag)?;
+            (from != name).then_some(Rename { from, to: name })
+        })
+        .collect())
+}
+
+/// The `[package]` name of the `Cargo.toml` in `dir` as of `tag`, if it existed then
+fn name_at(dir: &Path, tag: &str) -> Option<String> {
+    let content = git::git(dir, &["show", &format!("{tag}:./Cargo.toml")]).ok()?;
+    let table = content.parse::<toml::Table>().ok()?;
+    Some(table.get("package")?.get("name")?.as_str()?.to_string())
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use cargo_manifest::Manifest;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::{
+    Args, VersionBump, changelog, crate_names, files, git, infer, manifest_dir, previous_tag,
+    report_updated,
+};
+
+/// A crate's row in the bump table
+struct Row {
+    name: String,
+    old_version: String,
+    new_version: String,
+    bump: String,
+    reason: String,
+}
+
+/// Everything the page shows
+struct Page {
+    title: String,
+    generated: String,
+    rows: Vec<Row>,
+    /// `(text, short sha)` changelog entries
+    changes: Vec<(String, String)>,
+    /// Authors by number of commits, most first
+    contributors: Vec<(String, usize)>,
+    timing: Vec<(&'static str, String)>,
+}
+
+/// `report --html <DIR>`: write `index.html`, a self-contained page of the pending release
+/// (or of a saved `--json` result), for attaching to a release review
+pub fn report(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    dir: &Path,
+    result: Option<&Path>,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let tag = previous_tag(args);
+    let commits = git::commits(root, tag.as_deref(), &[])?;
+    let authorship = git::authorship(root, tag.as_deref())?;
+
+    let rows = match result {
+        Some(path) => saved_rows(path)?,
+        None => planned_rows(args, manifest, config, version, &commits)?,
+    };
+
+    let mut contributors = BTreeMap::<String, usize>::new();
+    for (author, _) in &authorship {
+        *contributors.entry(author.clone()).or_default() += 1;
+    }
+    let mut contributors = contributors.into_iter().collect::<Vec<_>>();
+    contributors.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+
+    let now = args.clock().now();
+    let mut timing = Vec::new();
+    if let Some(tag) = &tag {
+        let timestamp = git::commit_timestamp(root, tag)?;
+        timing.push((
+            "Last release",
+            format!(
+                "{tag}, {} ({} days ago)",
+                changelog::format_date(timestamp),
+                now.saturating_sub(timestamp) / changelog::SECONDS_PER_DAY
+            ),
+        ));
+    }
+    timing.push(("Commits since", commits.len().to_string()));
+    let dates = authorship.iter().map(|(_, timestamp)| *timestamp);
+    if let (Some(first), Some(last)) = (dates.clone().min(), dates.max()) {
+        timing.push((
+            "Changes from",
+            format!(
+                "{} to {}",
+                changelog::format_date(first),
+                changelog::format_date(last)
+            ),
+        ));
+    }
+
+    let page = Page {
+        title: match rows.first() {
+            Some(row) if rows.len() == 1 => format!("{} {}", row.name, row.new_version),
+            Some(row) => format!("Release {}", row.new_version),
+            None => "Release".to_string(),
+        },
+        generated: changelog::format_date(now),
+        rows,
+        changes: commits
+            .iter()
+            .filter_map(|commit| {
+                Some((
+                    changelog::entry_text(commit)?.to_string(),
+                    commit.short_sha().to_string(),
+                ))
+            })
+            .collect(),
+        contributors,
+        timing,
+    };
+
+    std::fs::create_dir_all(dir)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {e}", dir.display()))?;
+    let path: PathBuf = dir.join("index.html");
+    files::write(&path, render(&page))?;
+    report_updated(args, &path);
+    Ok(())
+}
+
+/// The bump the commits since the last release ask for, for each crate being released
+fn planned_rows(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    commits: &[git::Commit],
+) -> anyhow::Result<Vec<Row>> {
+    let (bump, reason) = match infer::bump_from_commits(args, config, commits) {
+        Some((bump, reason)) => (bump, reason.to_string()),
+        None => (
+            VersionBump::Skip,
+            "no markers since the last release".to_string(),
+        ),
+    };
+    let new_version = bump.apply(version.clone(), &args.bump_options(config)?)?;
+    Ok(crate_names(args, manifest, config)?
+        .into_iter()
+        .map(|name| Row {
+            name,
+            old_version: version.to_string(),
+            new_version: new_version.to_string(),
+            bump: bump.name(),
+            reason: reason.clone(),
+        })
+        .collect())
+}
+
+/// The crates of a result saved from `--json`
+fn saved_rows(path: &Path) -> anyhow::Result<Vec<Row>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+    let result = serde_json::from_str::<serde_json::Value>(&content)
+        .map_err(|e| anyhow::anyhow!("{} isn't a `--json` result: {e}", path.display()))?;
+    let crates = result["crates"].as_array().ok_or(anyhow::anyhow!(
+        "{} isn't a `--json` result: it has no crates",
+        path.display()
+    ))?;
+    let text = |value: &serde_json::Value| value.as_str().unwrap_or_default().to_string();
+    Ok(crates
+        .iter()
+        .map(|entry| Row {
+            name: text(&entry["name"]),
+            old_version: text(&entry["old_version"]),
+            new_version: text(&entry["new_version"]),
+            bump: text(&entry["bump"]),
+            reason: entry["reasons"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|reason| text(&reason["kind"]).replace('_', " "))
+                .collect::<Vec<_>>()
+                .join(", "),
+        })
+        .collect())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:56rem;margin:2rem auto;padding:0 1rem;color:#1f2328}\
+table{border-collapse:collapse;width:100%}th,td{text-align:left;padding:.4rem .6rem;border-bottom:1px solid #d0d7de}\
+code{font-family:ui-monospace,monospace;color:#57606a}.muted{color:#57606a}";
+
+fn render(page: &Page) -> String {
+    let mut html = String::new();
+    let title = escape(&page.title);
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p class=\"muted\">Generated {}</p>\n",
+        page.generated
+    );
+
+    html.push_str("<h2>Bumps</h2>\n<table>\n<tr><th>Crate</th><th>From</th><th>To</th><th>Bump</th><th>Why</th></tr>\n");
+    for row in &page.rows {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&row.name),
+            escape(&row.old_version),
+            escape(&row.new_version),
+            escape(&row.bump),
+            escape(&row.reason)
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Changelog</h2>\n<ul>\n");
+    if page.changes.is_empty() {
+        html.push_str("<li class=\"muted\">No changes</li>\n");
+    }
+    for (text, sha) in &page.changes {
+        let _ = writeln!(
+            html,
+            "<li>{} <code>{}</code></li>",
+            escape(text),
+            escape(sha)
+        );
+    }
+    html.push_str("</ul>\n");
+
+    html.push_str("<h2>Contributors</h2>\n<ul>\n");
+    for (author, commits) in &page.contributors {
+        let plural = if *commits == 1 { "" } else { "s" };
+        let _ = writeln!(
+            html,
+            "<li>{} <span class=\"muted\">({commits} commit{plural})</span></li>",
+            escape(author)
+        );
+    }
+    html.push_str("</ul>\n");
+
+    html.push_str("<h2>Timing</h2>\n<table>\n");
+    for (label, value) in &page.timing {
+        let _ = writeln!(html, "<tr><th>{label}</th><td>{}</td></tr>", escape(value));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let page = Page {
+            title: "app 1.3.0".to_string(),
+            generated: "2024-06-01".to_string(),
+            rows: vec![Row {
+                name: "app".to_string(),
+                old_version: "1.2.3".to_string(),
+                new_version: "1.3.0".to_string(),
+                bump: "minor".to_string(),
+                reason: "[minor] in the subject of abc1234".to_string(),
+            }],
+            changes: vec![("Support <T> & friends".to_string(), "abc1234".to_string())],
+            contributors: vec![("Ada".to_string(), 2), ("Linus".to_string(), 1)],
+            timing: vec![("Commits since", "3".to_string())],
+        };
+        let html = render(&page);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>app 1.3.0</title>"));
+        assert!(html.contains(
+            "<tr><td>app</td><td>1.2.3</td><td>1.3.0</td><td>minor</td><td>[minor] in the subject of abc1234</td></tr>"
+        ));
+        assert!(html.contains("<li>Support &lt;T&gt; &amp; friends <code>abc1234</code></li>"));
+        assert!(html.contains("Ada <span class=\"muted\">(2 commits)</span>"));
+        assert!(html.contains("Linus <span class=\"muted\">(1 commit)</span>"));
+        assert!(html.contains("<tr><th>Commits since</th><td>3</td></tr>"));
+        // Self-contained: nothing is loaded from elsewhere
+        assert!(!html.contains("http"));
+    }
+}
@@ -0,0 +1,104 @@
+use schemars::JsonSchema;
+use semver::Version;
+use serde::Serialize;
+
+use crate::bump::BumpOptions;
+use crate::cli::{VersionBump, output};
+
+#[derive(Serialize, JsonSchema)]
+struct Roadmap {
+    schema_version: u32,
+    current: String,
+    steps: Vec<Step>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Step {
+    bump: String,
+    version: String,
+}
+
+/// JSON Schema for `roadmap --json`
+pub fn schema() -> schemars::Schema {
+    schemars::schema_for!(Roadmap)
+}
+
+/// Print the versions `steps` would lead to from `version`, one bump after another
+pub fn roadmap(
+    version: &Version,
+    steps: &[VersionBump],
+    options: &BumpOptions,
+    json: bool,
+) -> anyhow::Result<()> {
+    let chain = chain(version, steps, options)?;
+    if json {
+        let roadmap = Roadmap {
+            schema_version: output::SCHEMA_VERSION,
+            current: version.to_string(),
+            steps: steps
+                .iter()
+                .zip(&chain)
+                .map(|(bump, version)| Step {
+                    bump: bump.name(),
+                    version: version.to_string(),
+                })
+                .collect(),
+        };
+        println!("{}", serde_json::to_string_pretty(&roadmap)?);
+    } else {
+        let width = steps
+            .iter()
+            .map(|bump| bump.name().len())
+            .fold("current".len(), usize::max);
+        println!("{:width$}  {version}", "current");
+        for (bump, version) in steps.iter().zip(&chain) {
+            println!("{:width$}  {version}", bump.name());
+        }
+    }
+    Ok(())
+}
+
+fn chain(
+    version: &Version,
+    steps: &[VersionBump],
+    options: &BumpOptions,
+) -> anyhow::Result<Vec<Version>> {
+    let mut current = version.clone();
+    steps
+        .iter()
+        .map(|bump| {
+            current = bump.apply(current.clone(), options)?;
+            Ok(current.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain() {
+        let steps = [
+            VersionBump::Minor,
+            VersionBump::Patch,
+            VersionBump::Preminor,
+            VersionBump::Preminor,
+            VersionBump::Minor,
+            VersionBump::Major,
+        ];
+        let chain = chain(&Version::new(1, 2, 3), &steps, &BumpOptions::default()).unwrap();
+        let chain = chain.iter().map(Version::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            chain,
+            [
+                "1.3.0",
+                "1.3.1",
+                "1.4.0-alpha.0",
+                "1.4.0-alpha.1",
+                "1.4.0",
+                "2.0.0"
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+
+/// Directories that hold build output or other people's code
+const SKIPPED_DIRS: [&str; 5] = ["target", "vendor", "third_party", "node_modules", ".git"];
+
+/// Whether `path` (relative to the manifest's directory) or a directory above it matches
+/// one of the config's `ignore` globs
+pub fn ignored(ignore: &[String], path: &Path) -> bool {
+    ignore
+        .iter()
+        .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+        .any(|pattern| {
+            path.ancestors()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .any(|dir| pattern.matches_path(dir))
+        })
+}
+
+/// Every `Cargo.toml` under `root`, skipping build output, vendored crates, and `ignore`d
+/// paths
+pub fn find_manifests(root: &Path, ignore: &[String]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut manifests = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        // `cargo vendor` leaves a checksum file in every crate it copies
+        if dir.join(".cargo-checksum.json").exists() {
+            continue;
+        }
+
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                let relative = path.strip_prefix(root).unwrap_or(&path);
+                if !SKIPPED_DIRS.iter().any(|skipped| name == *skipped)
+                    && !ignored(ignore, relative)
+                {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() && entry.file_name() == "Cargo.toml" {
+                manifests.push(path);
+            }
+        }
+    }
+
+    manifests.sort();
+    Ok(manifests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_manifests() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        for dir in ["a", "b/c", "target/package/x", "vendor/y", "d", "gen/e/f"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join("Cargo.toml"), "").unwrap();
+        }
+        std::fs::write(root.join("d/.cargo-checksum.json"), "{}").unwrap();
+
+        let found = find_manifests(root, &["gen/*".to_string()]).unwrap();
+
+        assert_eq!(
+            found,
+            [root.join("a/Cargo.toml"), root.join("b/c/Cargo.toml")]
+        );
+    }
+
+    #[test]
+    fn test_ignored() {
+        let ignore = ["vendor/**".to_string(), "third_party".to_string()];
+        assert!(ignored(&ignore, Path::new("vendor/serde")));
+        assert!(ignored(&ignore, Path::new("third_party/proto/codegen")));
+        assert!(!ignored(&ignore, Path::new("crates/vendor")));
+        assert!(!ignored(&ignore, Path::new("third_party_tools")));
+        assert!(!ignored(&[], Path::new("vendor/serde")));
+    }
+}
@@ -0,0 +1,92 @@
+use clap::ValueEnum;
+
+use crate::cli::{check, log, output, plugin, roadmap, snapshot, status};
+
+/// A machine-readable output with a published schema
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Document {
+    /// `--json` from a bump: the versions written (or, when skipped, left alone)
+    Result,
+    /// `status --json`: the bump planned for the next release
+    Status,
+    /// `check --json`
+    Check,
+    /// `log --json`
+    Log,
+    /// `snapshot --json`
+    Snapshot,
+    /// `roadmap --json`
+    Roadmap,
+    /// What a `[[plugins]]` command answers on stdout (it's given a `result` on stdin)
+    PluginVerdict,
+}
+
+impl Document {
+    fn schema(self) -> schemars::Schema {
+        match self {
+            Document::Result => output::Output::schema(),
+            Document::Status => status::schema(),
+            Document::Check => check::schema(),
+            Document::Log => log::schema(),
+            Document::Snapshot => snapshot::schema(),
+            Document::Roadmap => roadmap::schema(),
+            Document::PluginVerdict => plugin::schema(),
+        }
+    }
+}
+
+/// Print the JSON Schema for `document`, or an object holding every schema by name
+pub fn print(document: Option<Document>) -> anyhow::Result<()> {
+    let json = if let Some(document) = document {
+        serde_json::to_string_pretty(&document.schema())?
+    } else {
+        let schemas = Document::value_variants()
+            .iter()
+            .filter_map(|document| {
+                let name = document.to_possible_value()?.get_name().to_string();
+                Some((name, document.schema().to_value()))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::to_string_pretty(&schemas)?
+    };
+    println!("{json}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The committed schemas are the output contract: if this fails, the JSON changed shape.
+    /// Bump `output::SCHEMA_VERSION` if the change breaks consumers, then regenerate with
+    /// `version schema <document> > schemas/<document>.json`
+    #[test]
+    fn test_schemas_match_snapshots() {
+        let snapshots = [
+            (Document::Result, include_str!("../../schemas/result.json")),
+            (Document::Status, include_str!("../../schemas/status.json")),
+            (Document::Check, include_str!("../../schemas/check.json")),
+            (Document::Log, include_str!("../../schemas/log.json")),
+            (
+                Document::Snapshot,
+                include_str!("../../schemas/snapshot.json"),
+            ),
+            (
+                Document::Roadmap,
+                include_str!("../../schemas/roadmap.json"),
+            ),
+            (
+                Document::PluginVerdict,
+                include_str!("../../schemas/plugin-verdict.json"),
+            ),
+        ];
+        for (document, snapshot) in snapshots {
+            let snapshot: serde_json::Value = serde_json::from_str(snapshot).unwrap();
+            assert_eq!(
+                document.schema().to_value(),
+                snapshot,
+                "schemas/{document:?}.json is out of date"
+            );
+        }
+    }
+}
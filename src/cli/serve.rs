@@ -0,0 +1,321 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use clap::Parser;
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::cli::{Args, output, registry};
+
+/// Biggest request body accepted, far more than any bump request needs
+const MAX_BODY: usize = 64 * 1024;
+
+/// Options a request can't pass: the server picks the manifest (under its root) and the
+/// config beside it, since a config names hooks and plugins that run through the shell
+const REFUSED: [&str; 2] = ["--path", "--config"];
+
+/// Options that are set once for the whole process, so they're given to `serve` itself
+const SERVER_WIDE: [&str; 3] = ["--lang", "--http-retries", "--http-timeout"];
+
+/// Held while a bump runs, so two bots can't release the same repo at once
+static BUMPING: Mutex<()> = Mutex::new(());
+
+/// How long a sparse index lookup is reused across requests
+const REGISTRY_CACHE: Duration = Duration::from_mins(1);
+
+/// `POST /bump`: the manifest to bump and the command-line arguments to bump it with
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct BumpRequest {
+    path: PathBuf,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    target: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+/// `version serve`: answer bump requests over HTTP from one long-lived process. Each
+/// connection is read on its own thread, so a slow client holds up no one, but bumps run one
+/// at a time. Only manifests under `root` are bumped, and without a `token` only loopback
+/// connections are accepted
+pub fn serve(listen: &str, token: Option<&str>, root: &Path) -> anyhow::Result<()> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("--root {}: {e}", root.display()))?;
+    let listener = TcpListener::bind(listen)
+        .map_err(|e| anyhow::anyhow!("Couldn't listen on {listen}: {e}"))?;
+    if token.is_none() && !listener.local_addr()?.ip().is_loopback() {
+        anyhow::bail!(
+            "Refusing to listen on {listen} without --token, since anyone who can reach it could release"
+        );
+    }
+    registry::cache_for(REGISTRY_CACHE);
+    eprintln!(
+        "{} Listening on http://{}",
+        "✓".green().bold(),
+        listener.local_addr()?
+    );
+
+    let token = Arc::new(token.map(str::to_string));
+    let root = Arc::new(root);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{} {e}", "⚠".yellow().bold());
+                continue;
+            }
+        };
+        let (token, root) = (Arc::clone(&token), Arc::clone(&root));
+        std::thread::spawn(move || handle(stream, token.as_deref(), &root));
+    }
+    Ok(())
+}
+
+/// Read one request from `stream` and answer it
+fn handle(mut stream: TcpStream, token: Option<&str>, root: &Path) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(30)));
+    let (status, body) = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => respond(&request, token, root),
+        Err(e) => (400, error_body(&e.to_string())),
+    };
+    if let Err(e) = write_response(&mut stream, status, &body) {
+        eprintln!("{} Couldn't answer a request: {e}", "⚠".yellow().bold());
+    }
+}
+
+fn respond(request: &Request, token: Option<&str>, root: &Path) -> (u16, String) {
+    if let Some(token) = token
+        && !request
+            .authorization
+            .as_deref()
+            .is_some_and(|authorization| {
+                same(
+                    authorization.as_bytes(),
+                    format!("Bearer {token}").as_bytes(),
+                )
+            })
+    {
+        return (401, error_body("missing or wrong bearer token"));
+    }
+
+    match (request.method.as_str(), request.target.as_str()) {
+        ("GET", "/health") => (200, "{\"ok\":true}\n".to_string()),
+        ("POST", "/bump") => match serde_json::from_slice::<BumpRequest>(&request.body) {
+            Ok(bump) => run_bump(&bump, root),
+            Err(e) => (400, error_body(&format!("invalid request body: {e}"))),
+        },
+        (_, "/health" | "/bump") => (405, error_body("method not allowed")),
+        _ => (404, error_body("not found")),
+    }
+}
+
+/// Whether `a` and `b` are equal, taking as long for any `b` of the same length, so a
+/// token can't be guessed a byte at a time from response times
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// The request's manifest, if it's under `root` (relative paths are taken from `root`)
+fn manifest_under(root: &Path, path: &Path) -> Option<PathBuf> {
+    root.join(path)
+        .canonicalize()
+        .ok()
+        .filter(|path| path.starts_with(root))
+}
+
+/// The first of `flags` in `args`, as `--flag value` or `--flag=value`
+fn passed(args: &[String], flags: &[&'static str]) -> Option<&'static str> {
+    args.iter().find_map(|arg| {
+        flags
+            .iter()
+            .copied()
+            .find(|flag| arg == flag || arg.starts_with(&format!("{flag}=")))
+    })
+}
+
+/// Run the bump in this process and answer with its `--json` result
+fn run_bump(request: &BumpRequest, root: &Path) -> (u16, String) {
+    if let Some(flag) = passed(&request.args, &REFUSED) {
+        return (
+            400,
+            error_body(&format!("{flag} can't be passed in a request")),
+        );
+    }
+    if let Some(flag) = passed(&request.args, &SERVER_WIDE) {
+        return (
+            400,
+            error_body(&format!(
+                "{flag} applies to the whole server; pass it to `version serve` instead"
+            )),
+        );
+    }
+    let Some(path) = manifest_under(root, &request.path) else {
+        return (
+            403,
+            error_body(&format!(
+                "{} isn't a manifest under {}",
+                request.path.display(),
+                root.display()
+            )),
+        );
+    };
+    let command_line = ["version".into(), "--path".into(), path.into()]
+        .into_iter()
+        .chain(request.args.iter().map(Into::into))
+        .chain(["--json".into()])
+        .collect::<Vec<std::ffi::OsString>>();
+    let args = match Args::try_parse_from(command_line) {
+        Ok(args) if args.command.is_some() => {
+            return (
+                400,
+                error_body("only bumps can be requested, not subcommands"),
+            );
+        }
+        Ok(args) => args,
+        Err(e) => return (400, error_body(&e.to_string())),
+    };
+
+    let _bumping = BUMPING.lock().unwrap_or_else(PoisonError::into_inner);
+    match output::capture(|| crate::cli::execute(args)) {
+        (Ok(()), Some(json)) => (200, json),
+        (Ok(()), None) => (200, "{}\n".to_string()),
+        (Err(e), _) => (422, error_body(&format!("{e:#}"))),
+    }
+}
+
+fn error_body(message: &str) -> String {
+    format!("{}\n", serde_json::json!({ "error": message }))
+}
+
+/// Read one HTTP/1.1 request: the request line, the headers this needs, and the body
+fn read_request(reader: &mut impl BufRead) -> anyhow::Result<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("malformed request line");
+    };
+    let (method, target) = (method.to_string(), target.to_string());
+
+    let mut length = 0;
+    let mut authorization = None;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            anyhow::bail!("malformed header");
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value.parse()?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        }
+    }
+    if length > MAX_BODY {
+        anyhow::bail!("request body over {MAX_BODY} bytes");
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method,
+        target,
+        authorization,
+        body,
+    })
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &str) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Unprocessable Entity",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /bump HTTP/1.1\r\nHost: x\r\nContent-Length: 26\r\nAuthorization: Bearer s3cret\r\n\r\n{\"path\":\"a\",\"args\":[\"-q\"]}";
+        let request = read_request(&mut raw.as_bytes()).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.target, "/bump");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer s3cret"));
+        assert_eq!(
+            serde_json::from_slice::<BumpRequest>(&request.body).unwrap(),
+            BumpRequest {
+                path: PathBuf::from("a"),
+                args: vec!["-q".to_string()],
+            }
+        );
+
+        let root = Path::new(".");
+        assert_eq!(respond(&request, Some("other"), root).0, 401);
+        assert_eq!(respond(&request, Some("s3cret!"), root).0, 401);
+        let health = Request {
+            method: "GET".to_string(),
+            target: "/health".to_string(),
+            ..request
+        };
+        assert_eq!(respond(&health, Some("s3cret"), root).0, 200);
+        assert!(read_request(&mut "\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_run_bump_refuses() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::write(root.join("app/Cargo.toml"), "").unwrap();
+        let bump = |path: &str, args: &[&str]| BumpRequest {
+            path: PathBuf::from(path),
+            args: args.iter().map(ToString::to_string).collect(),
+        };
+
+        assert_eq!(
+            manifest_under(&root, Path::new("app/Cargo.toml")),
+            Some(root.join("app/Cargo.toml"))
+        );
+        assert_eq!(manifest_under(&root, Path::new("../Cargo.toml")), None);
+        assert_eq!(manifest_under(&root, Path::new("/etc/passwd")), None);
+        assert_eq!(run_bump(&bump("/etc/passwd", &[]), &root).0, 403);
+        assert_eq!(
+            run_bump(&bump("app/Cargo.toml", &["--config", "/tmp/x.toml"]), &root).0,
+            400
+        );
+        assert_eq!(
+            run_bump(&bump("app/Cargo.toml", &["--path=/tmp/Cargo.toml"]), &root).0,
+            400
+        );
+        assert_eq!(
+            run_bump(&bump("app/Cargo.toml", &["patch", "--lang", "de"]), &root).0,
+            400
+        );
+    }
+}
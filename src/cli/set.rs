@@ -0,0 +1,137 @@
+//! `version set <VERSION>`: write a version worked out elsewhere (say, by another CI step).
+//! Only the manifests, the sync targets, and the lockfile are written, and the result printed
+//! as for a bump; the release policy (freezes, approvals, jump limits, tag and registry
+//! checks, plugins, and next-dev) belongs to whatever chose the version. It may move backwards
+
+use cargo_manifest::Manifest;
+use colored::Colorize;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::files::Text;
+use crate::cli::output::Reason;
+use crate::cli::{Args, Command, VersionBump, bump_result, print_result, write_version};
+
+/// Turn `args` into the bump to the version being set
+pub fn prepare(args: &mut Args) -> anyhow::Result<()> {
+    let Some(Command::Set { version }) = &args.command else {
+        return Ok(());
+    };
+    if args.version_bump.is_some() || args.to.is_some() || args.from_git {
+        anyhow::bail!(
+            "set writes the version it's given, so it takes no bump, --to, or --from-git"
+        );
+    }
+    if !args.paths.is_empty() {
+        anyhow::bail!("set writes the package or the whole workspace, not --paths");
+    }
+    if args.commit || args.changelog {
+        anyhow::bail!("set only writes the version, so it takes no --commit or --changelog");
+    }
+    args.to = Some(version.clone());
+    Ok(())
+}
+
+/// Write the version being set over `version`, unless it's already the version
+pub fn write(
+    args: &Args,
+    text: &Text,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+) -> anyhow::Result<()> {
+    let Some(target) = &args.to else {
+        return Ok(());
+    };
+    if target == version {
+        if !args.is_quiet() {
+            eprintln!("{} {version} is already the version", "ℹ".blue().bold());
+        }
+        let result = bump_result(
+            args,
+            manifest,
+            config,
+            version,
+            version,
+            VersionBump::Skip,
+            &Reason::Argument,
+        )?;
+        return print_result(args, &result, version);
+    }
+
+    let (name, description) = direction(version, target);
+    if !args.is_quiet() {
+        eprintln!(
+            "{} {} {} {} {} {}",
+            "✓".green().bold(),
+            "Set version:".bold().blue(),
+            version.to_string().cyan(),
+            "→".bright_white(),
+            target.to_string().bright_green().bold(),
+            format!("({description})").dimmed()
+        );
+    }
+    write_version(args, text, manifest, config, &version.to_string(), target)?;
+
+    let mut result = bump_result(
+        args,
+        manifest,
+        config,
+        version,
+        target,
+        VersionBump::between(version, target),
+        &Reason::Argument,
+    )?;
+    for krate in &mut result.crates {
+        krate.bump.clone_from(&name);
+    }
+    print_result(args, &result, target)
+}
+
+/// The bump name and description for going from `old` to `new`: the bump that describes
+/// it, or a downgrade
+fn direction(old: &Version, new: &Version) -> (String, String) {
+    if new < old {
+        return ("downgrade".to_string(), "downgrade".to_string());
+    }
+    let bump = VersionBump::between(old, new);
+    (bump.name(), bump.description().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_prepare() {
+        let mut args = Args::parse_from(["version", "set", "2.1.0"]);
+        prepare(&mut args).unwrap();
+        assert_eq!(args.to, Some(Version::new(2, 1, 0)));
+
+        assert!(Args::try_parse_from(["version", "set", "2.1"]).is_err());
+        let mut args = Args::parse_from([
+            "version",
+            "--workspace",
+            "--paths",
+            "crates/app",
+            "set",
+            "2.1.0",
+        ]);
+        assert!(prepare(&mut args).is_err());
+        let mut args = Args::parse_from(["version", "--commit", "set", "2.1.0"]);
+        assert!(prepare(&mut args).is_err());
+    }
+
+    #[test]
+    fn test_direction() {
+        let direction = |old: &str, new: &str| {
+            direction(&Version::parse(old).unwrap(), &Version::parse(new).unwrap()).0
+        };
+        assert_eq!(direction("1.2.3", "2.1.0"), "major");
+        assert_eq!(direction("1.2.3", "1.2.4-rc.0"), "prepatch");
+        assert_eq!(direction("2.0.0", "1.0.0"), "downgrade");
+        assert_eq!(direction("1.0.0", "1.0.0-rc.1"), "downgrade");
+    }
+}
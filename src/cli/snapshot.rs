@@ -0,0 +1,129 @@
+use schemars::JsonSchema;
+use semver::{BuildMetadata, Prerelease, Version};
+use serde::Serialize;
+
+use crate::cli::config::Config;
+use crate::cli::{
+    Args, VersionBump, files, git, infer, manifest_dir, output, previous_tag, report_updated,
+    rewrite_manifest,
+};
+
+#[derive(Serialize, JsonSchema)]
+struct Snapshot {
+    schema_version: u32,
+    version: String,
+    /// The release the snapshot leads up to
+    base: String,
+    commit: String,
+    /// Commits since the last release tag
+    distance: usize,
+}
+
+/// JSON Schema for `snapshot --json`
+pub fn schema() -> schemars::Schema {
+    schemars::schema_for!(Snapshot)
+}
+
+/// Print a unique version for HEAD, `<base>-<id>.<n>+g<sha>`, which sorts after every
+/// earlier snapshot of the same base and before the base itself
+pub fn snapshot(
+    args: &Args,
+    config: &Config,
+    version: &Version,
+    id: &str,
+    timestamp: bool,
+    base: Option<&Version>,
+    write: bool,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let commits = git::commits(root, previous_tag(args).as_deref(), &[])?;
+    let commit = git::git(root, &["rev-parse", "--short", "HEAD"])?;
+
+    let base = match base {
+        Some(base) => base.clone(),
+        None => next_release(args, config, version, &commits)?,
+    };
+    let counter = if timestamp {
+        git::commit_timestamp(root, "HEAD")?
+    } else {
+        commits.len() as u64
+    };
+    let snapshot = snapshot_version(&base, id, counter, &commit)?;
+
+    if write {
+        let text = files::read(&args.path)?;
+        let content = rewrite_manifest(args, &text.content, &version.to_string(), &snapshot)?;
+        files::write(&args.path, text.encode(&content)?)?;
+        report_updated(args, &args.path);
+    }
+
+    if args.json {
+        let output = Snapshot {
+            schema_version: output::SCHEMA_VERSION,
+            version: snapshot.to_string(),
+            base: base.to_string(),
+            commit,
+            distance: commits.len(),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        println!("{snapshot}");
+    }
+
+    Ok(())
+}
+
+/// The release the pending commits would produce: the current prerelease's target, or the
+/// bump their markers ask for (a patch without any)
+fn next_release(
+    args: &Args,
+    config: &Config,
+    version: &Version,
+    commits: &[git::Commit],
+) -> anyhow::Result<Version> {
+    if !version.pre.is_empty() {
+        return Ok(Version::new(version.major, version.minor, version.patch));
+    }
+    let bump = match infer::bump_from_commits(args, config, commits) {
+        Some((VersionBump::Skip, _)) | None => VersionBump::Patch,
+        Some((bump, _)) => bump,
+    };
+    let mut next = bump.apply(version.clone(), &args.bump_options(config)?)?;
+    next.pre = Prerelease::EMPTY;
+    next.build = BuildMetadata::EMPTY;
+    Ok(next)
+}
+
+fn snapshot_version(
+    base: &Version,
+    id: &str,
+    counter: u64,
+    commit: &str,
+) -> anyhow::Result<Version> {
+    let mut version = base.clone();
+    version.pre = Prerelease::new(&format!("{id}.{counter}"))
+        .map_err(|e| anyhow::anyhow!("Invalid prerelease identifier {id:?}: {e}"))?;
+    version.build = BuildMetadata::new(&format!("g{commit}"))?;
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_version() {
+        let base = Version::new(1, 3, 0);
+        let first = snapshot_version(&base, "snapshot", 2, "abc1234").unwrap();
+        let second = snapshot_version(&base, "snapshot", 10, "def5678").unwrap();
+        assert_eq!(first.to_string(), "1.3.0-snapshot.2+gabc1234");
+        assert!(first < second && second < base);
+
+        let canary = snapshot_version(&Version::new(0, 0, 0), "canary", 1_760_000_000, "abc1234");
+        assert_eq!(
+            canary.unwrap().to_string(),
+            "0.0.0-canary.1760000000+gabc1234"
+        );
+        assert!(snapshot_version(&base, "not valid", 1, "abc1234").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use colored::Colorize;
+use schemars::JsonSchema;
+use semver::Version;
+use serde::Serialize;
+
+use crate::cli::config::Config;
+use crate::cli::i18n::{self, Message};
+use crate::cli::output::Reason;
+use crate::cli::{Args, VersionBump, changelog, git, infer, manifest_dir, output, previous_tag};
+
+#[derive(Serialize, JsonSchema)]
+struct Status {
+    schema_version: u32,
+    version: String,
+    last_tag: Option<LastTag>,
+    commits_since: usize,
+    pending_bump: Option<PendingBump>,
+    stale: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct LastTag {
+    name: String,
+    date: String,
+    age_days: u64,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct PendingBump {
+    bump: String,
+    next_version: String,
+    reason: Reason,
+}
+
+/// JSON Schema for `status --json`
+pub fn schema() -> schemars::Schema {
+    schemars::schema_for!(Status)
+}
+
+/// Summarize where the project stands relative to its last release
+pub fn status(
+    args: &Args,
+    config: &Config,
+    version: &Version,
+    stale_after_days: Option<u64>,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let tag = previous_tag(args);
+    let commits = git::commits(root, tag.as_deref(), &[])?;
+
+    let last_tag = match &tag {
+        Some(name) => {
+            let timestamp = git::commit_timestamp(root, name)?;
+            Some(LastTag {
+                name: name.clone(),
+                date: changelog::format_date(timestamp),
+                age_days: args.clock().now().saturating_sub(timestamp) / changelog::SECONDS_PER_DAY,
+            })
+        }
+        None => None,
+    };
+
+    let pending_bump = match infer::bump_from_commits(args, config, &commits) {
+        Some((VersionBump::Skip, _)) | None => None,
+        Some((bump, reason)) => Some(PendingBump {
+            bump: bump.name(),
+            next_version: bump
+                .apply(version.clone(), &args.bump_options(config)?)?
+                .to_string(),
+            reason,
+        }),
+    };
+
+    let threshold = match stale_after_days {
+        Some(days) => Some(days),
+        None => config.stale_after_days()?,
+    };
+    let stale = match (&last_tag, threshold) {
+        (Some(last_tag), Some(threshold)) => last_tag.age_days > threshold,
+        _ => false,
+    };
+
+    let status = Status {
+        schema_version: output::SCHEMA_VERSION,
+        version: version.to_string(),
+        last_tag,
+        commits_since: commits.len(),
+        pending_bump,
+        stale,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else if !args.is_quiet() {
+        print_status(&status, threshold);
+    }
+
+    Ok(())
+}
+
+fn print_status(status: &Status, threshold: Option<u64>) {
+    println!(
+        "📦 {} {}",
+        i18n::tr(Message::StatusVersion).bold().blue(),
+        status.version.cyan()
+    );
+
+    let last_tag = i18n::tr(Message::StatusLastTag).bold().blue();
+    match &status.last_tag {
+        Some(tag) => println!(
+            "🏷️  {last_tag} {} ({}, {})",
+            tag.name.bold(),
+            tag.date,
+            i18n::format(
+                Message::StatusDaysAgo,
+                &[("days", &tag.age_days.to_string())]
+            )
+        ),
+        None => println!("🏷️  {last_tag} {}", i18n::tr(Message::StatusNone).dimmed()),
+    }
+
+    println!(
+        "📝 {} {}",
+        i18n::tr(Message::StatusCommitsSince).bold().blue(),
+        status.commits_since
+    );
+
+    let pending_bump = i18n::tr(Message::StatusPendingBump).bold().blue();
+    match &status.pending_bump {
+        Some(pending) => println!(
+            "🔮 {pending_bump} {} {} {}",
+            pending.bump,
+            "→".bright_white(),
+            pending.next_version.bright_green().bold()
+        ),
+        None => println!(
+            "🔮 {pending_bump} {}",
+            i18n::tr(Message::StatusNoMarkers).dimmed()
+        ),
+    }
+
+    if let (true, Some(tag), Some(threshold)) = (status.stale, &status.last_tag, threshold) {
+        eprintln!(
+            "{} {}",
+            "⚠".yellow().bold(),
+            i18n::format(
+                Message::StatusStale,
+                &[
+                    ("days", &tag.age_days.to_string()),
+                    ("threshold", &threshold.to_string())
+                ]
+            )
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::git;
+
+/// `[[submodules]]`: a submodule whose pinned release is recorded in the changelog
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Submodule {
+    /// Where the submodule is checked out, relative to the manifest's directory
+    pub path: PathBuf,
+    /// Stable bumps fail unless the submodule is pinned at a tagged commit
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_tagged: bool,
+}
+
+/// The commit a submodule is pinned at, and its tag if it has one
+#[derive(Debug, PartialEq)]
+struct Pin {
+    sha: String,
+    tag: Option<String>,
+}
+
+/// The commit the superproject's index records for `submodule` (what the release commit
+/// will pin), named by its tag when the submodule is checked out to look it up in
+fn pin(root: &Path, submodule: &Submodule) -> anyhow::Result<Pin> {
+    let path = submodule.path.to_string_lossy();
+    let entry = git::git(root, &["ls-files", "--stage", "--", &path])?;
+    let sha = match entry.split_whitespace().collect::<Vec<_>>()[..] {
+        ["160000", sha, ..] => sha.to_string(),
+        _ => anyhow::bail!("{path} (under [[submodules]]) isn't a git submodule"),
+    };
+
+    let checkout = root.join(&submodule.path);
+    if git::git(&checkout, &["cat-file", "-e", &format!("{sha}^{{commit}}")]).is_err() {
+        anyhow::bail!(
+            "Submodule {path} isn't checked out; run `git submodule update --init {path}`"
+        );
+    }
+    let tag = git::git(&checkout, &["describe", "--tags", "--exact-match", &sha])
+        .ok()
+        .filter(|tag| !tag.is_empty());
+    Ok(Pin { sha, tag })
+}
+
+/// Every `require_tagged` submodule must be pinned at a tag for a stable `new_version`
+pub fn check(root: &Path, submodules: &[Submodule], new_version: &Version) -> anyhow::Result<()> {
+    if !new_version.pre.is_empty() {
+        return Ok(());
+    }
+    for submodule in submodules
+        .iter()
+        .filter(|submodule| submodule.require_tagged)
+    {
+        let pin = pin(root, submodule)?;
+        if pin.tag.is_none() {
+            anyhow::bail!(
+                "Submodule {} is at {}, which isn't a tagged release; {new_version} is stable, so point it at a tag first",
+                submodule.path.display(),
+                short(&pin.sha)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The changelog's "Submodules" subsection, listing where each one is pinned, or nothing
+/// without `[[submodules]]`
+pub fn changelog(root: &Path, submodules: &[Submodule]) -> anyhow::Result<String> {
+    if submodules.is_empty() {
+        return Ok(String::new());
+    }
+    let mut section = "\n### Submodules\n\n".to_string();
+    for submodule in submodules {
+        let _ = writeln!(
+            section,
+            "{}",
+            entry(&submodule.path, &pin(root, submodule)?)
+        );
+    }
+    Ok(section)
+}
+
+fn entry(path: &Path, pin: &Pin) -> String {
+    match &pin.tag {
+        Some(tag) => format!("- `{}` at {tag} ({})", path.display(), short(&pin.sha)),
+        None => format!("- `{}` at {} (untagged)", path.display(), short(&pin.sha)),
+    }
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry() {
+        let path = Path::new("proto");
+        let tagged = Pin {
+            sha: "0123456789abcdef".to_string(),
+            tag: Some("v2.3.0".to_string()),
+        };
+        assert_eq!(entry(path, &tagged), "- `proto` at v2.3.0 (0123456)");
+        let untagged = Pin {
+            tag: None,
+            ..tagged
+        };
+        assert_eq!(entry(path, &untagged), "- `proto` at 0123456 (untagged)");
+    }
+}
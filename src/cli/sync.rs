@@ -0,0 +1,593 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::rename::Rename;
+
+/// Another file that declares the version and is rewritten alongside the manifest
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    /// Relative to the manifest
+    pub path: PathBuf,
+    /// [default: from the file name]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Format>,
+    /// Variable holding the version, for `starlark` [default: VERSION] and `go` [default: Version]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    /// Regex whose first capture group is the version, overriding the format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    /// The crate whose requirements a `dependency` target rewrites
+    #[serde(rename = "crate", skip_serializing_if = "Option::is_none")]
+    pub crate_name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    /// `module(version = "…")` in `MODULE.bazel`
+    BazelModule,
+    /// `VERSION = "…"` in a `.bzl` file
+    Starlark,
+    /// `project(… VERSION 1.2.3)` in `CMakeLists.txt` (numbers only, so prereleases are dropped)
+    Cmake,
+    /// `project(…, version: '…')` in `meson.build`
+    Meson,
+    /// `version=…` in `gradle.properties`
+    GradleProperties,
+    /// The project's own `<version>` in `pom.xml` (not its parent's or a dependency's)
+    Maven,
+    /// `const Version = "…"` in a `.go` file, with or without a leading `v`
+    Go,
+    /// Every requirement on `crate` (`name = "1.2"` or `name = { version = "1.2", … }`) in an
+    /// example manifest, or in the ```` ```toml ```` blocks of a Markdown file
+    Dependency,
+}
+
+impl Format {
+    /// The format for a well-known file name
+    fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?;
+        if name == "MODULE.bazel" {
+            Some(Format::BazelModule)
+        } else if name == "CMakeLists.txt" {
+            Some(Format::Cmake)
+        } else if name == "meson.build" {
+            Some(Format::Meson)
+        } else if name == "gradle.properties" {
+            Some(Format::GradleProperties)
+        } else if name == "pom.xml" {
+            Some(Format::Maven)
+        } else if path.extension()? == "bzl" {
+            Some(Format::Starlark)
+        } else if path.extension()? == "go" {
+            Some(Format::Go)
+        } else {
+            None
+        }
+    }
+
+    /// How the format spells `version`
+    fn render(self, version: &str) -> &str {
+        match self {
+            Format::Cmake => version.split(['-', '+']).next().unwrap_or(version),
+            Format::BazelModule
+            | Format::Starlark
+            | Format::Meson
+            | Format::GradleProperties
+            | Format::Maven
+            | Format::Go
+            | Format::Dependency => version,
+        }
+    }
+}
+
+impl Target {
+    pub fn format(&self) -> Option<Format> {
+        self.format.or_else(|| Format::detect(&self.path))
+    }
+
+    /// The regex locating the version in this target
+    fn pattern(&self) -> anyhow::Result<Regex> {
+        if let Some(anchor) = &self.anchor {
+            return Ok(Regex::new(anchor)?);
+        }
+
+        let format = self.format().ok_or(anyhow::anyhow!(
+            "Can't tell the format of {}; set `format` or `anchor`",
+            self.path.display()
+        ))?;
+        let pattern = match format {
+            Format::BazelModule => {
+                r#"\bmodule\s*\([^)]*?\bversion\s*=\s*["']([^"']*)["']"#.to_string()
+            }
+            Format::Starlark => format!(
+                r#"(?m)^\s*{}\s*=\s*["']([^"']*)["']"#,
+                regex::escape(self.variable.as_deref().unwrap_or("VERSION"))
+            ),
+            Format::Cmake => r#"\b(?i:project)\s*\([^)]*?\bVERSION\s+"?([^\s")]+)"?"#.to_string(),
+            Format::Meson => r"\bproject\s*\([^)]*?\bversion\s*:\s*'([^']*)'".to_string(),
+            Format::Go => format!(
+                r#"(?m)^\s*(?:const\s+)?{}\s*(?:string\s*)?=\s*"v?([^"]*)""#,
+                regex::escape(self.variable.as_deref().unwrap_or("Version"))
+            ),
+            Format::GradleProperties => r"(?m)^\s*version\s*[=:]\s*([^\s#!]+)\s*$".to_string(),
+            // Matched against `mask_nested`, which leaves only the project's own elements
+            Format::Maven => r"<version>\s*([^<\s]+)\s*</version>".to_string(),
+            Format::Dependency => anyhow::bail!(
+                "{} is a `dependency` target, which has no single version to match",
+                self.path.display()
+            ),
+        };
+        Ok(Regex::new(&pattern)?)
+    }
+
+    /// Rewrite `old` to `new` in the target file under `root`, returning its path. A
+    /// `dependency` target on a crate in `renames` also rewrites requirements on its old name
+    pub fn apply(
+        &self,
+        root: &Path,
+        old: &str,
+        new: &str,
+        renames: &[Rename],
+    ) -> anyhow::Result<PathBuf> {
+        let path = root.join(&self.path);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+        if let (Some(Format::Dependency), None) = (self.format(), &self.anchor) {
+            let name = self.crate_name.as_deref().ok_or(anyhow::anyhow!(
+                "{}: a `dependency` target needs `crate`",
+                self.path.display()
+            ))?;
+            let renamed_from = renames
+                .iter()
+                .find(|rename| rename.to == name)
+                .map(|rename| rename.from.as_str());
+            let Some(content) =
+                rewrite_requirements(&content, &path, name, renamed_from, &new.parse()?)?
+            else {
+                match renamed_from {
+                    Some(old) => anyhow::bail!(
+                        "{}: no requirement on {name} (or {old}, its old name) found",
+                        self.path.display()
+                    ),
+                    None => {
+                        anyhow::bail!("{}: no requirement on {name} found", self.path.display())
+                    }
+                }
+            };
+            crate::cli::files::write(&path, content)?;
+            return Ok(path);
+        }
+        let (old, new) = match (self.format(), &self.anchor) {
+            (Some(format), None) => (format.render(old), format.render(new)),
+            _ => (old, new),
+        };
+        let masked = match (self.format(), &self.anchor) {
+            (Some(Format::Maven), None) => mask_nested(&content),
+            _ => content.clone(),
+        };
+        let content = replace_masked(&content, &masked, &self.pattern()?, old, new)
+            .map_err(|e| anyhow::anyhow!("{}: {e}", self.path.display()))?;
+        crate::cli::files::write(&path, content)?;
+        Ok(path)
+    }
+}
+
+/// Replace the first capture group of the single `pattern` match, which must be `old`
+pub fn replace_capture(
+    content: &str,
+    pattern: &Regex,
+    old: &str,
+    new: &str,
+) -> anyhow::Result<String> {
+    replace_masked(content, content, pattern, old, new)
+}
+
+/// [`replace_capture`], matching against `masked`: `content` with the parts that mustn't
+/// match blanked out (so its offsets are still `content`'s)
+fn replace_masked(
+    content: &str,
+    masked: &str,
+    pattern: &Regex,
+    old: &str,
+    new: &str,
+) -> anyhow::Result<String> {
+    let matches = pattern.captures_iter(masked).collect::<Vec<_>>();
+    let [captures] = matches.as_slice() else {
+        anyhow::bail!(
+            "`{pattern}` matched {} times, expected exactly once",
+            matches.len()
+        );
+    };
+    let group = captures.get(1).ok_or(anyhow::anyhow!(
+        "`{pattern}` needs a capture group around the version"
+    ))?;
+    let found = &content[group.range()];
+    if found != old {
+        anyhow::bail!("`{pattern}` captured `{found}`, not the current version {old}");
+    }
+
+    Ok(format!(
+        "{}{new}{}",
+        &content[..group.start()],
+        &content[group.end()..]
+    ))
+}
+
+/// Point every requirement on `name` at `new`, keeping the requirement's operator and
+/// precision (`"1.2"` becomes `"1.3"`, `"=1.2.3"` becomes `"=1.3.0"`), whether it's keyed by
+/// the crate's name or renamed with `package = "…"`. Requirements on `renamed_from`, the
+/// crate's name at its last release, are pointed at the new name with `package`, so the
+/// key code refers to it by stays. Only ```` ```toml ```` blocks count in Markdown.
+/// Prereleases leave requirements alone, since install instructions should point at
+/// stable releases. `None` when there's no requirement on the crate at all
+pub fn rewrite_requirements(
+    content: &str,
+    path: &Path,
+    name: &str,
+    renamed_from: Option<&str>,
+    new: &semver::Version,
+) -> anyhow::Result<Option<String>> {
+    let markdown = path
+        .extension()
+        .is_some_and(|extension| extension == "md" || extension == "markdown");
+    let masked = if markdown {
+        mask_outside_toml_fences(content)
+    } else {
+        content.to_string()
+    };
+    let entry = Regex::new(r#"(?m)^[ \t]*([A-Za-z0-9_-]+)[ \t]*=[ \t]*("[^"\n]*"|\{[^}\n]*\})"#)?;
+    let version_key = Regex::new(r#"\bversion[ \t]*=[ \t]*"([^"]*)""#)?;
+    let package_key = Regex::new(r#"\bpackage[ \t]*=[ \t]*"([^"]*)""#)?;
+
+    // (byte range, replacement), applied back to front
+    let mut edits = Vec::new();
+    let mut found = false;
+    for captures in entry.captures_iter(&masked) {
+        let (key, value) = (&captures[1], &captures[2]);
+        let start = captures.get(2).map_or(0, |value| value.start());
+        let package = package_key
+            .captures(value)
+            .and_then(|package| package.get(1));
+        let crate_name = package.map_or(key, |package| package.as_str());
+        let renamed = renamed_from == Some(crate_name);
+        if crate_name != name && !renamed {
+            continue;
+        }
+        let requirement = if value.starts_with('"') {
+            start + 1..start + value.len() - 1
+        } else if let Some(version) = version_key
+            .captures(value)
+            .and_then(|version| version.get(1))
+        {
+            start + version.start()..start + version.end()
+        } else {
+            continue;
+        };
+        found = true;
+        let Some(bumped) = bump_requirement(&content[requirement.clone()], new) else {
+            continue;
+        };
+
+        match (renamed, package) {
+            (true, None) if value.starts_with('"') => edits.push((
+                start..start + value.len(),
+                format!("{{ package = \"{name}\", version = \"{bumped}\" }}"),
+            )),
+            (true, None) => {
+                edits.push((requirement, bumped));
+                edits.push((start + 1..start + 1, format!(" package = \"{name}\",")));
+            }
+            (true, Some(package)) => {
+                edits.push((requirement, bumped));
+                edits.push((
+                    start + package.start()..start + package.end(),
+                    name.to_string(),
+                ));
+            }
+            (false, _) => edits.push((requirement, bumped)),
+        }
+    }
+    if !found {
+        return Ok(None);
+    }
+    if !new.pre.is_empty() {
+        return Ok(Some(content.to_string()));
+    }
+
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut rewritten = content.to_string();
+    for (range, replacement) in edits {
+        rewritten.replace_range(range, &replacement);
+    }
+    Ok(Some(rewritten))
+}
+
+/// `requirement` moved to `new` at the same precision, `None` for requirements that aren't
+/// a plain (optionally `^`, `~`, `=`, or `>=`) version
+fn bump_requirement(requirement: &str, new: &semver::Version) -> Option<String> {
+    let version = requirement.trim_start_matches(['^', '~', '=', '>']);
+    let operator = &requirement[..requirement.len() - version.len()];
+    let parts = version.trim().split('.').collect::<Vec<_>>();
+    if !["", "^", "~", "=", ">="].contains(&operator)
+        || parts.len() > 3
+        || !parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return None;
+    }
+    let new = [new.major, new.minor, new.patch].map(|part| part.to_string());
+    Some(format!("{operator}{}", new[..parts.len()].join(".")))
+}
+
+/// Blank out everything but the contents of ```` ```toml ```` fenced blocks
+fn mask_outside_toml_fences(content: &str) -> String {
+    // Same byte length, so offsets still line up with `content`
+    let blank = |line: &str| {
+        line.chars()
+            .map(|c| {
+                if c == '\n' {
+                    "\n".to_string()
+                } else {
+                    " ".repeat(c.len_utf8())
+                }
+            })
+            .collect::<String>()
+    };
+    let mut masked = String::with_capacity(content.len());
+    let mut in_toml = false;
+    for line in content.split_inclusive('\n') {
+        match line.trim_start().strip_prefix("```") {
+            Some(info) => {
+                in_toml = !in_toml && info.trim().split([' ', ',']).next() == Some("toml");
+                masked.push_str(&blank(line));
+            }
+            None if in_toml => masked.push_str(line),
+            None => masked.push_str(&blank(line)),
+        }
+    }
+    masked
+}
+
+/// Blank out XML comments and everything below the root element's children, so only the
+/// `<project>`'s own `<version>` is left to match
+fn mask_nested(content: &str) -> String {
+    let tokens = Regex::new(r"(?s)<!--.*?-->|<[^>]*>").expect("valid regex");
+    let mut masked = content.as_bytes().to_vec();
+    let mut blank = |range: std::ops::Range<usize>| masked[range].fill(b' ');
+
+    let mut depth = 0usize;
+    let mut offset = 0;
+    for token in tokens.find_iter(content) {
+        if depth > 2 {
+            blank(offset..token.start());
+        }
+        let tag = token.as_str();
+        if tag.starts_with("<!--") {
+            blank(token.range());
+        } else if tag.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            if depth >= 2 {
+                blank(token.range());
+            }
+        } else if !tag.starts_with("<?") && !tag.starts_with("<!") {
+            if depth >= 2 {
+                blank(token.range());
+            }
+            if !tag.ends_with("/>") {
+                depth += 1;
+            }
+        }
+        offset = token.end();
+    }
+
+    String::from_utf8(masked).expect("only ASCII-delimited ranges are blanked")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(path: &str) -> Target {
+        Target {
+            path: PathBuf::from(path),
+            format: None,
+            variable: None,
+            anchor: None,
+            crate_name: None,
+        }
+    }
+
+    #[test]
+    fn test_replace_capture() {
+        let content = "VERSION = \"1.0.0\"\nOTHER_VERSION = \"1.0.0\"\n";
+        let anchor = Regex::new(r#"(?m)^VERSION = "([^"]+)""#).unwrap();
+        assert_eq!(
+            replace_capture(content, &anchor, "1.0.0", "1.1.0").unwrap(),
+            "VERSION = \"1.1.0\"\nOTHER_VERSION = \"1.0.0\"\n"
+        );
+
+        let loose = Regex::new(r#"VERSION = "([^"]+)""#).unwrap();
+        assert!(replace_capture(content, &loose, "1.0.0", "1.1.0").is_err());
+        let no_group = Regex::new(r"(?m)^VERSION").unwrap();
+        assert!(replace_capture(content, &no_group, "1.0.0", "1.1.0").is_err());
+        assert!(replace_capture(content, &anchor, "0.9.0", "1.1.0").is_err());
+    }
+
+    #[test]
+    fn test_bazel_module() {
+        let content = "module(\n    name = \"app\",\n    version = \"1.2.3\",\n)\n\n\
+                       bazel_dep(name = \"rules_rust\", version = \"0.40.0\")\n";
+        let pattern = target("MODULE.bazel").pattern().unwrap();
+        assert_eq!(
+            replace_capture(content, &pattern, "1.2.3", "1.3.0").unwrap(),
+            content.replace("\"1.2.3\"", "\"1.3.0\"")
+        );
+    }
+
+    #[test]
+    fn test_cmake() {
+        let content = "cmake_minimum_required(VERSION 3.20)\n\
+                       project(app_ffi\n  VERSION 1.2.3\n  LANGUAGES C)\n";
+        let target = target("capi/CMakeLists.txt");
+        let format = target.format().unwrap();
+        assert_eq!(format.render("1.3.0-rc.1+meta"), "1.3.0");
+        assert_eq!(
+            replace_capture(content, &target.pattern().unwrap(), "1.2.3", "1.3.0").unwrap(),
+            content.replace("VERSION 1.2.3", "VERSION 1.3.0")
+        );
+    }
+
+    #[test]
+    fn test_meson() {
+        let content = "project('app', 'c',\n  meson_version : '>=0.60',\n  version : '1.2.3')\n";
+        let pattern = target("meson.build").pattern().unwrap();
+        assert_eq!(
+            replace_capture(content, &pattern, "1.2.3", "1.3.0-rc.1").unwrap(),
+            content.replace("'1.2.3'", "'1.3.0-rc.1'")
+        );
+    }
+
+    #[test]
+    fn test_gradle_properties() {
+        let content = "org.gradle.jvmargs=-Xmx2g\nversion=1.2.3\nkotlinVersion=1.9.0\n";
+        let pattern = target("services/api/gradle.properties").pattern().unwrap();
+        assert_eq!(
+            replace_capture(content, &pattern, "1.2.3", "1.3.0").unwrap(),
+            content.replace("version=1.2.3", "version=1.3.0")
+        );
+    }
+
+    #[test]
+    fn test_maven() {
+        let content = "<?xml version=\"1.0\"?>\n<project>\n  <parent>\n    \
+                       <artifactId>base</artifactId>\n    <version>1.2.3</version>\n  \
+                       </parent>\n  <!-- <version>0.0.0</version> -->\n  \
+                       <artifactId>api</artifactId>\n  <version>1.2.3</version>\n  \
+                       <dependencies>\n    <dependency>\n      <artifactId>lib</artifactId>\n      \
+                       <version>1.2.3</version>\n    </dependency>\n  </dependencies>\n</project>\n";
+        let target = target("pom.xml");
+        let updated = replace_masked(
+            content,
+            &mask_nested(content),
+            &target.pattern().unwrap(),
+            "1.2.3",
+            "1.3.0",
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            content.replace(
+                "<artifactId>api</artifactId>\n  <version>1.2.3",
+                "<artifactId>api</artifactId>\n  <version>1.3.0"
+            )
+        );
+
+        let inherited =
+            "<project>\n  <parent>\n    <version>1.2.3</version>\n  </parent>\n</project>\n";
+        let masked = mask_nested(inherited);
+        assert_eq!(masked.len(), inherited.len());
+        assert!(
+            replace_masked(
+                inherited,
+                &masked,
+                &target.pattern().unwrap(),
+                "1.2.3",
+                "1.3.0"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_go() {
+        let content = "package version\n\nconst (\n\tVersion = \"v1.2.3\"\n\tProtocol = \"2\"\n)\n";
+        let pattern = target("internal/version/version.go").pattern().unwrap();
+        assert_eq!(
+            replace_capture(content, &pattern, "1.2.3", "1.3.0").unwrap(),
+            content.replace("v1.2.3", "v1.3.0")
+        );
+        let typed = "const Version string = \"1.2.3\"\n";
+        assert!(replace_capture(typed, &pattern, "1.2.3", "1.3.0").is_ok());
+    }
+
+    #[test]
+    fn test_dependency_requirements() {
+        let readme = "# mycrate\n\n```toml\n[dependencies]\nmycrate = \"1.2\"\n```\n\n\
+                      Or `mycrate = \"1.2\"` out of a block, left alone.\n\n\
+                      ```toml\nmycrate = { version = \"=1.2.3\", features = [\"derive\"] }\n\
+                      mycrate-macros = \"1.2\"\n```\n";
+        let new = semver::Version::new(1, 3, 0);
+        assert_eq!(
+            rewrite_requirements(readme, Path::new("README.md"), "mycrate", None, &new).unwrap(),
+            Some(
+                readme
+                    .replacen("mycrate = \"1.2\"", "mycrate = \"1.3\"", 1)
+                    .replace("=1.2.3", "=1.3.0")
+            )
+        );
+
+        let example = "[dependencies]\nmycrate = \"^0.9\"\nother = \"1\"\n";
+        assert_eq!(
+            rewrite_requirements(
+                example,
+                Path::new("examples/Cargo.toml"),
+                "mycrate",
+                None,
+                &new
+            )
+            .unwrap()
+            .as_deref(),
+            Some("[dependencies]\nmycrate = \"^1.3\"\nother = \"1\"\n")
+        );
+        let rc = semver::Version::parse("1.3.0-rc.1").unwrap();
+        assert_eq!(
+            rewrite_requirements(example, Path::new("Cargo.toml"), "mycrate", None, &rc)
+                .unwrap()
+                .as_deref(),
+            Some(example)
+        );
+        assert_eq!(
+            rewrite_requirements(example, Path::new("Cargo.toml"), "nope", None, &new).unwrap(),
+            None
+        );
+
+        let renamed = "[dependencies]\nold-name = \"1.2\"\nalias = { package = \"old-name\", version = \"1.2\" }\n\
+                       tables = { version = \"~1.2\", features = [\"x\"] }\nplain = { package = \"mycrate\", version = \"1.2\" }\n";
+        let renamed = renamed.replace("tables", "old-name");
+        let path = Path::new("Cargo.toml");
+        assert_eq!(
+            rewrite_requirements(&renamed, path, "mycrate", Some("old-name"), &new)
+                .unwrap()
+                .as_deref(),
+            Some(
+                "[dependencies]\nold-name = { package = \"mycrate\", version = \"1.3\" }\n\
+                 alias = { package = \"mycrate\", version = \"1.3\" }\n\
+                 old-name = { package = \"mycrate\", version = \"~1.3\", features = [\"x\"] }\n\
+                 plain = { package = \"mycrate\", version = \"1.3\" }\n"
+            )
+        );
+        assert!(rewrite_requirements(&renamed, path, "mycrate", None, &new).is_ok());
+        assert!(rewrite_requirements(example, path, "mycrate", Some("gone"), &new).is_ok());
+
+        assert_eq!(bump_requirement(">=1.2", &new).as_deref(), Some(">=1.3"));
+        assert_eq!(bump_requirement("1.2, <2", &new), None);
+        assert_eq!(bump_requirement("*", &new), None);
+    }
+
+    #[test]
+    fn test_starlark() {
+        let content = "# Generated\nVERSION = \"1.2.3\"\nRULES_VERSION = \"0.40.0\"\n";
+        let pattern = target("tools/version.bzl").pattern().unwrap();
+        assert_eq!(
+            replace_capture(content, &pattern, "1.2.3", "1.3.0").unwrap(),
+            "# Generated\nVERSION = \"1.3.0\"\nRULES_VERSION = \"0.40.0\"\n"
+        );
+        assert!(target("VERSION.txt").pattern().is_err());
+    }
+}
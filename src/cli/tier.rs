@@ -0,0 +1,120 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use toml::Table;
+
+use crate::cli::VersionBump;
+use crate::cli::config::Config;
+
+/// How much of a crate's API is promised to its users, capping the bumps automation may
+/// give it. Ordered from least to most restricted
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    Stable,
+    /// At most minor bumps: breaking changes ship without a new major
+    Preview,
+    /// At most patch bumps: nothing outside the workspace depends on it
+    Internal,
+}
+
+impl Tier {
+    pub fn name(self) -> &'static str {
+        match self {
+            Tier::Stable => "stable",
+            Tier::Preview => "preview",
+            Tier::Internal => "internal",
+        }
+    }
+
+    /// The bump an inferred `bump` becomes for a crate in this tier
+    pub fn cap(self, bump: VersionBump) -> VersionBump {
+        match (self, bump) {
+            (Tier::Preview, VersionBump::Major) => VersionBump::Minor,
+            (Tier::Preview, VersionBump::Premajor) => VersionBump::Preminor,
+            (Tier::Internal, _) => bump.patch_only(),
+            _ => bump,
+        }
+    }
+}
+
+/// The `tier` a manifest declares under `[package.metadata.version]`
+pub fn declared(content: &str) -> anyhow::Result<Option<Tier>> {
+    let Ok(table) = toml::from_str::<Table>(content) else {
+        return Ok(None);
+    };
+    in_metadata(
+        table
+            .get("package")
+            .and_then(|package| package.get("metadata")),
+    )
+}
+
+/// The `tier` under `version` in a parsed `[package.metadata]`
+pub fn in_metadata(metadata: Option<&toml::Value>) -> anyhow::Result<Option<Tier>> {
+    let Some(tier) = metadata
+        .and_then(|metadata| metadata.get("version"))
+        .and_then(|version| version.get("tier"))
+    else {
+        return Ok(None);
+    };
+    Ok(Some(tier.clone().try_into().map_err(|_| {
+        anyhow::anyhow!(
+            "[package.metadata.version] tier = {tier} isn't stable, preview, or internal"
+        )
+    })?))
+}
+
+/// The tier of the crate `name`: the config's `tiers` entry, else what its manifest
+/// declares
+pub fn resolve(config: &Config, name: &str, declared: Option<Tier>) -> Option<Tier> {
+    config.tiers.get(name).copied().or(declared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap() {
+        assert_eq!(Tier::Stable.cap(VersionBump::Major), VersionBump::Major);
+        assert_eq!(Tier::Preview.cap(VersionBump::Major), VersionBump::Minor);
+        assert_eq!(
+            Tier::Preview.cap(VersionBump::Preminor),
+            VersionBump::Preminor
+        );
+        assert_eq!(
+            Tier::Preview.cap(VersionBump::Premajor),
+            VersionBump::Preminor
+        );
+        assert_eq!(Tier::Internal.cap(VersionBump::Minor), VersionBump::Patch);
+        assert_eq!(
+            Tier::Internal.cap(VersionBump::Preminor),
+            VersionBump::Prepatch
+        );
+        assert_eq!(Tier::Internal.cap(VersionBump::Skip), VersionBump::Skip);
+    }
+
+    #[test]
+    fn test_declared() {
+        let manifest =
+            "[package]\nname = \"api\"\n\n[package.metadata.version]\ntier = \"preview\"\n";
+        assert_eq!(declared(manifest).unwrap(), Some(Tier::Preview));
+        assert_eq!(declared("[package]\nname = \"api\"\n").unwrap(), None);
+        assert!(declared("[package.metadata.version]\ntier = \"beta\"\n").is_err());
+
+        let config = Config {
+            tiers: [("api".to_string(), Tier::Internal)].into(),
+            ..Config::default()
+        };
+        assert_eq!(
+            resolve(&config, "api", Some(Tier::Preview)),
+            Some(Tier::Internal)
+        );
+        assert_eq!(
+            resolve(&config, "cli", Some(Tier::Preview)),
+            Some(Tier::Preview)
+        );
+    }
+}
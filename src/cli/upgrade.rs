@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use crate::cli::manifest;
+
+/// The config schema this build reads. Every breaking change to the config bumps it and
+/// adds a migration from the version before
+pub const CONFIG_VERSION: u32 = 2;
+
+/// A rewrite taking a config written for `from` to `from + 1`, as text so comments and
+/// layout survive `--write-migrated`
+struct Migration {
+    from: u32,
+    change: &'static str,
+    rewrite: fn(&str) -> anyhow::Result<String>,
+}
+
+const MIGRATIONS: [Migration; 1] = [Migration {
+    from: 1,
+    change: "`stale_after_days = N` is now `stale_after = \"Nd\"`",
+    rewrite: stale_after,
+}];
+
+/// A config file's content migrated to [`CONFIG_VERSION`]
+#[derive(Debug, PartialEq)]
+pub struct Upgraded {
+    /// The `config_version` the file declares (1 without one)
+    pub from: u32,
+    pub content: String,
+    /// What the migrations changed, empty if the file reads the same either way
+    pub changes: Vec<&'static str>,
+}
+
+/// Migrate `content` to the current schema. Failing means a malformed `config_version`,
+/// or one newer than this build understands
+pub fn upgrade(content: &str) -> anyhow::Result<Upgraded> {
+    let from = match content
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|table| table.get("config_version").cloned())
+    {
+        None => 1,
+        Some(value) => value
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .ok_or(anyhow::anyhow!("config_version must be a positive number"))?,
+    };
+    if from > CONFIG_VERSION {
+        anyhow::bail!(
+            "config_version = {from} is newer than this version of the tool reads ({CONFIG_VERSION}); upgrade it"
+        );
+    }
+
+    let mut upgraded = content.to_string();
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.from >= from) {
+        let rewritten = (migration.rewrite)(&upgraded)?;
+        if rewritten != upgraded {
+            changes.push(migration.change);
+        }
+        upgraded = rewritten;
+    }
+    Ok(Upgraded {
+        from,
+        content: set_config_version(&upgraded),
+        changes,
+    })
+}
+
+/// `--write-migrated`: rewrite the config at `path` for the current schema, returning
+/// whether it changed
+pub fn write(path: &Path) -> anyhow::Result<bool> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+    let upgraded = upgrade(&content).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+    if upgraded.content == content {
+        return Ok(false);
+    }
+    crate::cli::files::write(path, upgraded.content)?;
+    Ok(true)
+}
+
+/// Rewrite the top-level `key` line of `content` with `rewrite(value, comment)`
+fn rewrite_key(
+    content: &str,
+    key: &str,
+    rewrite: impl Fn(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<Option<String>> {
+    let end = manifest::top_level(content);
+    let mut offset = 0;
+    for line in content[..end].split_inclusive('\n') {
+        if manifest::is_key(line, key) {
+            let newline = &line[line.trim_end_matches(['\r', '\n']).len()..];
+            let (_, value) = line.trim_end().split_once('=').unwrap_or_default();
+            let (value, comment) = match value.split_once('#') {
+                Some((value, comment)) => (value, format!(" #{comment}")),
+                None => (value, String::new()),
+            };
+            let indent = &line[..line.len() - line.trim_start().len()];
+            return Ok(Some(format!(
+                "{}{indent}{}{comment}{newline}{}",
+                &content[..offset],
+                rewrite(value.trim())?,
+                &content[offset + line.len()..]
+            )));
+        }
+        offset += line.len();
+    }
+    Ok(None)
+}
+
+/// `config_version = CONFIG_VERSION`, replacing the declared one or ahead of everything
+fn set_config_version(content: &str) -> String {
+    let current = format!("config_version = {CONFIG_VERSION}");
+    match rewrite_key(content, "config_version", |_| Ok(current.clone())) {
+        Ok(Some(content)) => content,
+        _ => format!("{current}\n\n{content}"),
+    }
+}
+
+/// 1 → 2: the staleness threshold became an interval, like `min_release_interval`
+fn stale_after(content: &str) -> anyhow::Result<String> {
+    let rewritten = rewrite_key(content, "stale_after_days", |value| {
+        let days = value
+            .parse::<u64>()
+            .map_err(|_| anyhow::anyhow!("stale_after_days = {value} isn't a number of days"))?;
+        Ok(format!("stale_after = \"{days}d\""))
+    })?;
+    Ok(rewritten.unwrap_or_else(|| content.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade() {
+        let v1 = "# Release settings\nstale_after_days = 90 # a quarter\npre_id = \"rc\"\n\n[umbrella]\nstale_after_days = 1\n";
+        assert_eq!(
+            upgrade(v1).unwrap(),
+            Upgraded {
+                from: 1,
+                content: "config_version = 2\n\n# Release settings\nstale_after = \"90d\" # a quarter\npre_id = \"rc\"\n\n[umbrella]\nstale_after_days = 1\n".to_string(),
+                changes: vec![MIGRATIONS[0].change],
+            }
+        );
+
+        // Nothing to migrate but the missing version
+        let unversioned = upgrade("pre_id = \"rc\"\n").unwrap();
+        assert_eq!(
+            unversioned.content,
+            "config_version = 2\n\npre_id = \"rc\"\n"
+        );
+        assert!(unversioned.changes.is_empty());
+
+        let current = "config_version = 2\nstale_after = \"1w\"\n";
+        assert_eq!(upgrade(current).unwrap().content, current);
+        assert_eq!(
+            upgrade("config_version = 1\nstale_after_days = 7\n")
+                .unwrap()
+                .content,
+            "config_version = 2\nstale_after = \"7d\"\n"
+        );
+
+        assert!(upgrade("config_version = 3\n").is_err());
+        assert!(upgrade("config_version = \"2\"\n").is_err());
+        assert!(upgrade("stale_after_days = \"soon\"\n").is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::registry;
+
+/// `[upstream]`: the crate this one is an API-compatible fork of, and how much of its
+/// version has to come from one of upstream's releases
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Upstream {
+    /// The upstream crate's name on the registry
+    #[serde(rename = "crate")]
+    pub name: String,
+
+    /// How much of the version is upstream's [default: minor]
+    #[serde(default)]
+    pub track: Track,
+
+    /// Sparse index to look upstream up in [default: --registry-index]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Track {
+    /// The major version is upstream's; minor and patch are ours
+    Major,
+    /// `major.minor` is upstream's; the patch is ours
+    #[default]
+    Minor,
+    /// `major.minor.patch` is upstream's; only prerelease and build metadata are ours
+    Patch,
+}
+
+impl Track {
+    fn name(self) -> &'static str {
+        match self {
+            Track::Major => "major",
+            Track::Minor => "minor",
+            Track::Patch => "patch",
+        }
+    }
+
+    /// The part of a version that has to match upstream
+    fn key(self, version: &Version) -> Vec<u64> {
+        let parts = [version.major, version.minor, version.patch];
+        let len = match self {
+            Track::Major => 1,
+            Track::Minor => 2,
+            Track::Patch => 3,
+        };
+        parts[..len].to_vec()
+    }
+}
+
+impl Upstream {
+    /// Fail unless `version` lines up with a stable, unyanked upstream release
+    pub fn check(&self, default_index: &str, version: &Version) -> anyhow::Result<()> {
+        let index = self.index.as_deref().unwrap_or(default_index);
+        let published = registry::versions(index, &self.name)?;
+        if published.is_empty() {
+            anyhow::bail!(
+                "upstream {} has no published releases in {index}",
+                self.name
+            );
+        }
+        match violation(self.track, &self.name, version, &published) {
+            Some(problem) => Err(anyhow::anyhow!(problem)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn violation(track: Track, name: &str, version: &Version, published: &[Version]) -> Option<String> {
+    let key = track.key(version);
+    let stable = published.iter().filter(|release| release.pre.is_empty());
+    if stable.clone().any(|release| track.key(release) == key) {
+        return None;
+    }
+
+    let mut expected = key.iter().map(u64::to_string).collect::<Vec<_>>();
+    expected.resize(3, "x".to_string());
+    let latest = stable
+        .max()
+        .map_or_else(|| "none".to_string(), Version::to_string);
+    Some(format!(
+        "{version} doesn't follow upstream {name}: with track = \"{}\" it needs a published {name} {} (latest stable: {latest})",
+        track.name(),
+        expected.join(".")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violation() {
+        let v = |s| Version::parse(s).unwrap();
+        let published = [v("1.4.2"), v("1.5.0"), v("2.0.0-rc.1")];
+        let check = |track, version| violation(track, "serde", &v(version), &published);
+
+        assert_eq!(check(Track::Minor, "1.5.7"), None);
+        assert_eq!(check(Track::Minor, "1.4.0-fork.1"), None);
+        assert_eq!(
+            check(Track::Minor, "1.6.0").as_deref(),
+            Some(
+                "1.6.0 doesn't follow upstream serde: with track = \"minor\" it needs a published serde 1.6.x (latest stable: 1.5.0)"
+            )
+        );
+        // Upstream prereleases don't count
+        assert!(check(Track::Major, "2.0.0").is_some());
+        assert_eq!(check(Track::Major, "1.9.0"), None);
+        assert_eq!(check(Track::Patch, "1.4.2+fork.3"), None);
+        assert!(check(Track::Patch, "1.4.3").is_some());
+    }
+}
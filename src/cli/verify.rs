@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use cargo_manifest::Manifest;
+use colored::Colorize;
+use semver::Version;
+
+use crate::cli::config::Config;
+use crate::cli::{Args, changelog, crate_file, git, manifest_dir, registry};
+
+/// Run every preflight check, reporting each, and fail if any of them failed
+pub fn verify_tag(
+    args: &Args,
+    config: &Config,
+    version: &Version,
+    tag: &str,
+    default_branch: Option<&str>,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+
+    let head = git::git(root, &["rev-parse", "HEAD"])?;
+    let tagged = git::git(root, &["rev-parse", &format!("{tag}^{{commit}}")])?;
+
+    let branch = default_branch.map_or_else(|| git::default_branch(root), str::to_string);
+
+    let checks = [
+        (
+            head == tagged,
+            format!("HEAD is {tag}"),
+            format!("HEAD ({}) is not {tag} ({})", &head[..7], &tagged[..7]),
+        ),
+        (
+            config.version_from_tag(tag).is_some_and(|v| v == *version),
+            format!("{tag} matches manifest version {version}"),
+            format!("{tag} does not match manifest version {version}"),
+        ),
+        (
+            git::is_ancestor(root, tag, &branch)?,
+            format!("{tag} is reachable from {branch}"),
+            format!("{tag} is not reachable from {branch}"),
+        ),
+    ];
+
+    report(args, &checks, "tag")
+}
+
+/// Print each `(passed, pass message, fail message)`, failing if any of them failed
+fn report(args: &Args, checks: &[(bool, String, String)], what: &str) -> anyhow::Result<()> {
+    let mut failed = 0;
+    for (ok, pass, fail) in checks {
+        if *ok {
+            if !args.is_quiet() {
+                eprintln!("{} {pass}", "✓".green().bold());
+            }
+        } else {
+            failed += 1;
+            eprintln!("{} {fail}", "✗".red().bold());
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} {what} checks failed", checks.len());
+    }
+
+    Ok(())
+}
+
+/// `verify-package`: check the published package (downloaded, or the local `.crate` file)
+/// against the release: its manifest version, that `Cargo.toml.orig` is the manifest at the
+/// release tag, that it was packaged from the tagged commit, and that its changelog has the
+/// release's section as tagged
+pub fn verify_package(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    version: &Version,
+    crate_file: Option<&Path>,
+) -> anyhow::Result<()> {
+    let root = manifest_dir(&args.path);
+    let name = &manifest
+        .package
+        .as_ref()
+        .ok_or(anyhow::anyhow!(
+            "verify-package checks a single package (pick a workspace member with --package)"
+        ))?
+        .name;
+    let bytes = match crate_file {
+        Some(path) => std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?,
+        None => registry::download(&args.registry_index, name, version)?,
+    };
+    let package = crate_file::read(&bytes)?;
+    let tag = config.tag(name, version);
+    let tagged = git::git(root, &["rev-parse", &format!("{tag}^{{commit}}")])?;
+    // `<tag>:./<file>` is relative to the manifest's directory
+    let at_tag = |file: &str| git::git(root, &["show", &format!("{tag}:./{file}")]).ok();
+
+    let packaged = package
+        .file("Cargo.toml")
+        .and_then(|content| toml::from_str::<toml::Table>(content).ok())
+        .and_then(|table| {
+            table
+                .get("package")?
+                .get("version")?
+                .as_str()
+                .map(str::to_string)
+        });
+    let mut checks = vec![(
+        packaged.as_deref() == Some(version.to_string().as_str()),
+        format!("The package is {name} {version}"),
+        format!(
+            "The package's manifest says {}, not {version}",
+            packaged.as_deref().unwrap_or("no version")
+        ),
+    )];
+
+    let file_name = args
+        .path
+        .file_name()
+        .map_or("Cargo.toml".into(), |file| file.to_string_lossy());
+    let original = package.file("Cargo.toml.orig").map(str::trim);
+    checks.push((
+        original.is_some() && original == at_tag(&file_name).as_deref(),
+        format!("Cargo.toml.orig is {file_name} at {tag}"),
+        match original {
+            Some(_) => format!("Cargo.toml.orig differs from {file_name} at {tag}"),
+            None => "The package has no Cargo.toml.orig".to_string(),
+        },
+    ));
+
+    let vcs = package.vcs_info()?;
+    checks.push(match &vcs {
+        Some(vcs) => (
+            vcs.git.sha1 == tagged && !vcs.git.dirty,
+            format!("Packaged from {tag} ({})", &tagged[..7]),
+            if vcs.git.dirty {
+                format!(
+                    "Packaged from uncommitted changes on {}",
+                    &vcs.git.sha1[..vcs.git.sha1.len().min(7)]
+                )
+            } else {
+                format!(
+                    "Packaged from {}, not {tag} ({})",
+                    &vcs.git.sha1[..vcs.git.sha1.len().min(7)],
+                    &tagged[..7]
+                )
+            },
+        ),
+        None => (
+            false,
+            String::new(),
+            "The package has no .cargo_vcs_info.json to say which commit it came from".to_string(),
+        ),
+    });
+
+    let changelog = args.changelog_path.as_deref().unwrap_or("CHANGELOG.md");
+    if let Some(tagged_changelog) = at_tag(changelog)
+        && let Some(expected) = changelog::excerpt(&tagged_changelog, &version.to_string())
+    {
+        let excerpt = package
+            .file(changelog)
+            .and_then(|content| changelog::excerpt(content, &version.to_string()));
+        checks.push((
+            excerpt == Some(expected),
+            format!("{changelog} has the {version} section as tagged"),
+            match (package.file(changelog), excerpt) {
+                (None, _) => format!("The package has no {changelog}"),
+                (Some(_), None) => format!("The package's {changelog} has no {version} section"),
+                (Some(_), Some(_)) => {
+                    format!("The package's {changelog} {version} section differs from {tag}'s")
+                }
+            },
+        ));
+    }
+
+    report(args, &checks, "package")
+}
@@ -0,0 +1,273 @@
+use cargo_manifest::Manifest;
+use clap::ValueEnum;
+use colored::Colorize;
+use regex::Regex;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::config::Config;
+use crate::cli::workspace::Member;
+use crate::cli::{Args, config, files, manifest, manifest_dir, release_members, report_updated};
+
+/// How a workspace's members are versioned
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Versioning {
+    /// Every member inherits the `[workspace.package]` version
+    Fixed,
+    /// Every member declares its own version
+    Independent,
+}
+
+impl Versioning {
+    pub fn name(self) -> &'static str {
+        match self {
+            Versioning::Fixed => "fixed",
+            Versioning::Independent => "independent",
+        }
+    }
+}
+
+/// Fail if any release member doesn't follow the declared `versioning`
+pub fn check(members: &[Member], versioning: Versioning) -> anyhow::Result<()> {
+    let fixed = versioning == Versioning::Fixed;
+    let names = members
+        .iter()
+        .filter(|member| member.inherits_version != fixed)
+        .map(|member| member.name.as_str())
+        .collect::<Vec<_>>();
+    let problem = if fixed {
+        "declare their own version"
+    } else {
+        "inherit the workspace version"
+    };
+    if !names.is_empty() {
+        anyhow::bail!(
+            "versioning = \"{}\", but {} {problem}; run `version --workspace migrate-mode {}` to convert",
+            versioning.name(),
+            names.join(", "),
+            versioning.name()
+        );
+    }
+    Ok(())
+}
+
+/// `version migrate-mode`: rewrite the member manifests for `to`, then record it in the config
+pub fn migrate(
+    args: &Args,
+    manifest: &Manifest,
+    config: &Config,
+    to: Versioning,
+) -> anyhow::Result<()> {
+    if !args.workspace {
+        anyhow::bail!("migrate-mode converts a workspace (use --workspace)");
+    }
+    let members = release_members(args, manifest, config)?;
+    let shared = manifest
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.package.as_ref()?.version.clone());
+
+    match to {
+        Versioning::Fixed => {
+            // The shared version can't go backwards for any member, or it'd publish a
+            // lower version than one already released
+            let highest = members
+                .iter()
+                .filter_map(|member| Version::parse(member.version.as_deref()?).ok())
+                .chain(
+                    shared
+                        .as_deref()
+                        .and_then(|shared| Version::parse(shared).ok()),
+                )
+                .max()
+                .ok_or(anyhow::anyhow!("No member declares a version to share"))?
+                .to_string();
+            if shared.as_deref() != Some(highest.as_str()) {
+                let text = files::read(&args.path)?;
+                let content = match &shared {
+                    Some(shared) => crate::cli::rewrite_span(
+                        &text.content,
+                        manifest::table_span(&text.content, "workspace.package")
+                            .unwrap_or(0..text.content.len()),
+                        "version",
+                        shared,
+                        &highest,
+                    )?,
+                    None => add_workspace_version(&text.content, &highest),
+                };
+                files::write(&args.path, text.encode(&content)?)?;
+                report_updated(args, &args.path);
+            }
+            let shared = highest;
+            for member in members.iter().filter(|member| !member.inherits_version) {
+                let own = member.version.as_deref().unwrap_or_default();
+                if own != shared && !args.is_quiet() {
+                    eprintln!(
+                        "{} {} moves from {own} to the workspace version {shared}",
+                        "⚠".yellow().bold(),
+                        member.name
+                    );
+                }
+                rewrite_member(args, member, to_inherited)?;
+            }
+        }
+        Versioning::Independent => {
+            let shared = shared.ok_or(anyhow::anyhow!(
+                "The workspace has no [workspace.package] version for members to start from"
+            ))?;
+            for member in members.iter().filter(|member| member.inherits_version) {
+                rewrite_member(args, member, |content| to_own(content, &shared))?;
+            }
+        }
+    }
+
+    let path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| config::default_path(manifest_dir(&args.path)));
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => anyhow::bail!("Failed to read {}: {e}", path.display()),
+    };
+    files::write(&path, set_versioning(&content, to))?;
+    report_updated(args, &path);
+
+    Ok(())
+}
+
+fn rewrite_member(
+    args: &Args,
+    member: &Member,
+    rewrite: impl Fn(&str) -> Option<String>,
+) -> anyhow::Result<()> {
+    let path = member.dir.join("Cargo.toml");
+    let text = files::read(&path)?;
+    let span = manifest::table_span(&text.content, "package")
+        .ok_or(anyhow::anyhow!("{} has no [package] table", path.display()))?;
+    let package = rewrite(&text.content[span.clone()]).ok_or(anyhow::anyhow!(
+        "Couldn't find the version key in {}",
+        path.display()
+    ))?;
+    let content = format!(
+        "{}{package}{}",
+        &text.content[..span.start],
+        &text.content[span.end..]
+    );
+    files::write(&path, text.encode(&content)?)?;
+    report_updated(args, &path);
+    Ok(())
+}
+
+/// `version = "…"` as `version.workspace = true`, in a `[package]` table
+fn to_inherited(package: &str) -> Option<String> {
+    let own = Regex::new(r#"(?m)^(\s*)version\s*=\s*(?:"[^"]*"|'[^']*')"#).expect("valid regex");
+    own.is_match(package).then(|| {
+        own.replace(package, "${1}version.workspace = true")
+            .into_owned()
+    })
+}
+
+/// `version.workspace = true` as `version = "<version>"`, in a `[package]` table
+fn to_own(package: &str, version: &str) -> Option<String> {
+    let inherited = Regex::new(
+        r"(?m)^(\s*)version\s*(?:\.\s*workspace\s*=\s*true|=\s*\{\s*workspace\s*=\s*true\s*\})",
+    )
+    .expect("valid regex");
+    inherited.is_match(package).then(|| {
+        inherited
+            .replace(package, |caps: &regex::Captures| {
+                format!("{}version = \"{version}\"", &caps[1])
+            })
+            .into_owned()
+    })
+}
+
+/// Give the root manifest a `[workspace.package]` version
+fn add_workspace_version(content: &str, version: &str) -> String {
+    match manifest::table_span(content, "workspace.package") {
+        Some(span) => {
+            let header_end = content[span.clone()]
+                .find('\n')
+                .map_or(span.end, |end| span.start + end + 1);
+            format!(
+                "{}version = \"{version}\"\n{}",
+                &content[..header_end],
+                &content[header_end..]
+            )
+        }
+        None => format!(
+            "{}\n\n[workspace.package]\nversion = \"{version}\"\n",
+            content.trim_end()
+        ),
+    }
+}
+
+/// Set the top-level `versioning` key of a config file, keeping the rest as written
+fn set_versioning(content: &str, versioning: Versioning) -> String {
+    let line = format!("versioning = \"{}\"", versioning.name());
+    let existing = Regex::new(r"(?m)^versioning\s*=.*$").expect("valid regex");
+    if existing.is_match(content) {
+        existing.replace(content, line.as_str()).into_owned()
+    } else if content.is_empty() {
+        format!("{line}\n")
+    } else {
+        format!("{line}\n\n{content}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_member_versions() {
+        let own = "[package]\nname = \"a\"\nversion = \"0.3.0\"\nedition = \"2024\"\n";
+        let inherited = to_inherited(own).unwrap();
+        assert_eq!(
+            inherited,
+            "[package]\nname = \"a\"\nversion.workspace = true\nedition = \"2024\"\n"
+        );
+        assert_eq!(
+            to_own(&inherited, "1.0.0").unwrap(),
+            own.replace("0.3.0", "1.0.0")
+        );
+        assert_eq!(
+            to_own("[package]\nversion = { workspace = true }\n", "1.0.0").as_deref(),
+            Some("[package]\nversion = \"1.0.0\"\n")
+        );
+        assert_eq!(to_inherited("[package]\nname = \"a\"\n"), None);
+    }
+
+    #[test]
+    fn test_add_workspace_version() {
+        assert_eq!(
+            add_workspace_version("[workspace]\nmembers = [\"crates/*\"]\n", "1.2.0"),
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nversion = \"1.2.0\"\n"
+        );
+        assert_eq!(
+            add_workspace_version("[workspace.package]\nedition = \"2024\"\n", "1.2.0"),
+            "[workspace.package]\nversion = \"1.2.0\"\nedition = \"2024\"\n"
+        );
+    }
+
+    #[test]
+    fn test_set_versioning() {
+        assert_eq!(
+            set_versioning("", Versioning::Fixed),
+            "versioning = \"fixed\"\n"
+        );
+        assert_eq!(
+            set_versioning(
+                "versioning = \"fixed\"\npre_id = \"rc\"\n",
+                Versioning::Independent
+            ),
+            "versioning = \"independent\"\npre_id = \"rc\"\n"
+        );
+        assert_eq!(
+            set_versioning("pre_id = \"rc\"\n", Versioning::Fixed),
+            "versioning = \"fixed\"\n\npre_id = \"rc\"\n"
+        );
+    }
+}
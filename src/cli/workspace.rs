@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+use cargo_manifest::{Manifest, MaybeInherited, Publish};
+
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub name: String,
+    pub dir: PathBuf,
+    /// False for `publish = false` (or an empty registry list)
+    pub publish: bool,
+    /// The freeze annotation, if the member opted out of automated bumps
+    pub frozen: Option<&'static str>,
+    /// `version.workspace = true`, so it moves with the workspace version
+    pub inherits_version: bool,
+    /// The member's own version, unless it inherits the workspace's
+    pub version: Option<String>,
+    /// The stability tier its manifest declares
+    pub tier: Option<crate::cli::tier::Tier>,
+}
+
+/// Whether the package can be published to a registry at all
+pub fn is_publishable(publish: Option<&MaybeInherited<Publish>>) -> bool {
+    !matches!(publish, Some(MaybeInherited::Local(Publish::Flag(false))))
+        && !matches!(publish, Some(MaybeInherited::Local(Publish::Registry(r))) if r.is_empty())
+}
+
+/// Expand the workspace `members` globs (minus `exclude`, and the config's `ignore`) into
+/// named member crates
+pub fn members(root: &Path, manifest: &Manifest, ignore: &[String]) -> anyhow::Result<Vec<Member>> {
+    let workspace = manifest
+        .workspace
+        .as_ref()
+        .ok_or(anyhow::anyhow!("Expected to find a workspace"))?;
+
+    let excluded = workspace
+        .exclude
+        .iter()
+        .flatten()
+        .map(|pattern| root.join(pattern))
+        .collect::<Vec<_>>();
+
+    let mut members = Vec::new();
+    for pattern in &workspace.members {
+        let pattern = root.join(pattern);
+        for dir in glob::glob(&pattern.to_string_lossy())? {
+            let dir = dir?;
+            let manifest_path = dir.join("Cargo.toml");
+            let relative = dir.strip_prefix(root).unwrap_or(&dir);
+            if excluded.contains(&dir)
+                || crate::cli::scan::ignored(ignore, relative)
+                || !manifest_path.is_file()
+            {
+                continue;
+            }
+
+            let Some(package) = crate::cli::manifest::load(&manifest_path)?.package else {
+                continue;
+            };
+            let content = crate::cli::files::read(&manifest_path)?.content;
+            members.push(Member {
+                publish: is_publishable(package.publish.as_ref()),
+                frozen: crate::cli::manifest::frozen(&content),
+                tier: crate::cli::tier::declared(&content)
+                    .map_err(|e| anyhow::anyhow!("{}: {e}", manifest_path.display()))?,
+                inherits_version: matches!(package.version, Some(MaybeInherited::Inherited { .. })),
+                version: package.version.and_then(MaybeInherited::as_local),
+                name: package.name,
+                dir,
+            });
+        }
+    }
+
+    Ok(members)
+}
@@ -0,0 +1,335 @@
+//! Inferring the bump from commit messages: `[minor]`-style markers, settled by a
+//! [`Precedence`] when they disagree, and `release:` directives in pull request
+//! descriptions. The repository is read through [`Repository`], so the same inference runs
+//! against git and a manifest on disk or against whatever a host has fetched
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::bump::{BumpOptions, VersionBump};
+
+pub const MARKERS: [(&str, VersionBump); 7] = [
+    ("major", VersionBump::Major),
+    ("minor", VersionBump::Minor),
+    ("patch", VersionBump::Patch),
+    ("premajor", VersionBump::Premajor),
+    ("preminor", VersionBump::Preminor),
+    ("prepatch", VersionBump::Prepatch),
+    ("no-version", VersionBump::Skip),
+];
+
+/// Which marker decides the bump when prerelease and stable markers are both found, e.g.
+/// `[preminor]` in one commit and `[minor]` in another
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precedence {
+    /// A stable marker wins, even a smaller one: `[patch]` over `[preminor]`
+    #[default]
+    Stable,
+    /// A prerelease marker keeps the release a prerelease, of the largest component any
+    /// marker asks for: `[minor]` and `[prepatch]` give preminor
+    Prerelease,
+    /// The largest component wins, and a stable marker breaks a tie: `[patch]` and
+    /// `[preminor]` give preminor, `[minor]` and `[preminor]` give minor
+    Largest,
+}
+
+impl Precedence {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Precedence::Stable => "stable",
+            Precedence::Prerelease => "prerelease",
+            Precedence::Largest => "largest",
+        }
+    }
+
+    /// The bump the markers `found` add up to. `[no-version]` only counts on its own
+    #[must_use]
+    pub fn resolve(self, found: &[VersionBump]) -> Option<VersionBump> {
+        let first = found
+            .iter()
+            .copied()
+            .min_by_key(|bump| significance(*bump))?;
+        let largest = found
+            .iter()
+            .copied()
+            .filter(|bump| *bump != VersionBump::Skip)
+            .max_by_key(|bump| (component(*bump), !bump.is_pre()));
+        Some(match (self, largest) {
+            (Precedence::Largest, Some(largest)) => largest,
+            (Precedence::Prerelease, Some(largest)) if found.iter().any(|bump| bump.is_pre()) => {
+                match component(largest) {
+                    2 => VersionBump::Premajor,
+                    1 => VersionBump::Preminor,
+                    _ => VersionBump::Prepatch,
+                }
+            }
+            _ => first,
+        })
+    }
+}
+
+/// A marker's place in [`MARKERS`], most significant first
+#[must_use]
+pub fn significance(bump: VersionBump) -> Option<usize> {
+    MARKERS.iter().position(|(_, marker)| *marker == bump)
+}
+
+/// The component a bump moves: 2 for major, 1 for minor, 0 for patch
+#[must_use]
+pub fn component(bump: VersionBump) -> u8 {
+    match bump {
+        VersionBump::Major | VersionBump::Premajor => 2,
+        VersionBump::Minor | VersionBump::Preminor => 1,
+        VersionBump::Patch | VersionBump::Prepatch | VersionBump::Skip => 0,
+    }
+}
+
+/// A message's subject line and the rest
+#[must_use]
+pub fn split_message(message: &str) -> (&str, &str) {
+    message.split_once('\n').unwrap_or((message, ""))
+}
+
+/// The name of every marker in `text`, with its bump, most significant first
+#[must_use]
+pub fn markers(text: &str) -> Vec<(&'static str, VersionBump)> {
+    let text = text.to_lowercase();
+    MARKERS
+        .into_iter()
+        .filter(|(name, _)| text.contains(&format!("[{name}]")))
+        .collect()
+}
+
+/// A marker found in one of several commit messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Found {
+    pub name: &'static str,
+    pub bump: VersionBump,
+    /// Which of the messages it's in
+    pub message: usize,
+    /// The subject had no marker, so it's the body's
+    pub in_body: bool,
+}
+
+/// Every bump the markers in `messages` ask for, most significant first, each from the first
+/// message asking for it. A message's markers are its subject's, or its body's if the
+/// subject has none
+pub fn found(messages: &[impl AsRef<str>]) -> Vec<Found> {
+    let mut found = messages
+        .iter()
+        .enumerate()
+        .flat_map(|(message, text)| {
+            let (subject, body) = split_message(text.as_ref());
+            let (markers, in_body) = match markers(subject) {
+                found if found.is_empty() => (markers(body), true),
+                found => (found, false),
+            };
+            markers.into_iter().map(move |(name, bump)| Found {
+                name,
+                bump,
+                message,
+                in_body,
+            })
+        })
+        .collect::<Vec<_>>();
+    // Stable, so each bump keeps the first message it's in
+    found.sort_by_key(|found| significance(found.bump));
+    found.dedup_by_key(|found| found.bump);
+    found
+}
+
+/// A `release:` directive in a pull request description, either YAML-style front-matter
+/// (`---` / `release: minor` / `---`) at the top or a fenced block (```` ```release: minor``` ````,
+/// or a `release` block holding `minor`)
+#[must_use]
+pub fn bump_from_description(description: &str) -> Option<(VersionBump, String)> {
+    let description = description.replace("\r\n", "\n");
+    let front_matter = description
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.split_once("\n---").map(|(front, _)| front));
+    let fences = description.split("```").skip(1).step_by(2);
+
+    let value = front_matter
+        .into_iter()
+        .flat_map(str::lines)
+        .chain(fences)
+        .find_map(|text| match text.trim().split_once('\n') {
+            // A ```release block holding just the bump
+            Some(("release", value)) => Some(value.trim()),
+            _ => text.trim().strip_prefix("release:").map(str::trim),
+        })?;
+
+    let value = value.to_lowercase();
+    MARKERS
+        .iter()
+        .find(|(name, _)| *name == value)
+        .map(|(name, bump)| (*bump, format!("release: {name}")))
+}
+
+/// The reads inference needs from a repository: the manifest's version, and the commits
+/// that haven't been released yet
+pub trait Repository {
+    /// The version the manifest is on
+    fn version(&self) -> anyhow::Result<Version>;
+
+    /// The messages of the commits since the last release, newest first
+    fn unreleased_messages(&self) -> anyhow::Result<Vec<String>>;
+}
+
+/// The bump the markers in `repository`'s unreleased commits ask for, settled by
+/// `precedence`. `None` when no commit has a marker
+pub fn infer(
+    repository: &impl Repository,
+    precedence: Precedence,
+) -> anyhow::Result<Option<VersionBump>> {
+    let found = found(&repository.unreleased_messages()?)
+        .into_iter()
+        .map(|found| found.bump)
+        .collect::<Vec<_>>();
+    Ok(precedence.resolve(&found))
+}
+
+/// The bump inferred for `repository` and the version it leads to, `None` when no commit
+/// has a marker
+pub fn next_version(
+    repository: &impl Repository,
+    precedence: Precedence,
+    options: &BumpOptions,
+) -> anyhow::Result<Option<(VersionBump, Version)>> {
+    let Some(bump) = infer(repository, precedence)? else {
+        return Ok(None);
+    };
+    Ok(Some((bump, bump.apply(repository.version()?, options)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fetched {
+        version: &'static str,
+        messages: &'static [&'static str],
+    }
+
+    impl Repository for Fetched {
+        fn version(&self) -> anyhow::Result<Version> {
+            Ok(Version::parse(self.version)?)
+        }
+
+        fn unreleased_messages(&self) -> anyhow::Result<Vec<String>> {
+            Ok(self.messages.iter().map(ToString::to_string).collect())
+        }
+    }
+
+    #[test]
+    fn test_markers() {
+        assert_eq!(
+            markers("Add thing [MINOR]"),
+            [("minor", VersionBump::Minor)]
+        );
+        assert_eq!(
+            markers("[patch] but also [major]"),
+            [("major", VersionBump::Major), ("patch", VersionBump::Patch)]
+        );
+        assert!(markers("Add thing (#123)").is_empty());
+    }
+
+    #[test]
+    fn test_found() {
+        let found = found(&[
+            "Add thing [minor]\n\n[major] in the body",
+            "Fix it\n\n[patch] [minor]",
+        ]);
+        assert_eq!(
+            found,
+            [
+                Found {
+                    name: "minor",
+                    bump: VersionBump::Minor,
+                    message: 0,
+                    in_body: false,
+                },
+                Found {
+                    name: "patch",
+                    bump: VersionBump::Patch,
+                    message: 1,
+                    in_body: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        use VersionBump::{Major, Minor, Patch, Preminor, Prepatch, Skip};
+
+        let cases = [
+            (&[Minor, Preminor][..], [Minor, Preminor, Minor]),
+            (&[Patch, Preminor], [Patch, Preminor, Preminor]),
+            (&[Minor, Prepatch], [Minor, Preminor, Minor]),
+            (&[Major, Patch], [Major, Major, Major]),
+            (&[Prepatch, Skip], [Prepatch, Prepatch, Prepatch]),
+            (&[Skip], [Skip, Skip, Skip]),
+        ];
+        for (found, [stable, prerelease, largest]) in cases {
+            assert_eq!(Precedence::Stable.resolve(found), Some(stable), "{found:?}");
+            assert_eq!(
+                Precedence::Prerelease.resolve(found),
+                Some(prerelease),
+                "{found:?}"
+            );
+            assert_eq!(
+                Precedence::Largest.resolve(found),
+                Some(largest),
+                "{found:?}"
+            );
+        }
+        assert_eq!(Precedence::Largest.resolve(&[]), None);
+    }
+
+    #[test]
+    fn test_bump_from_description() {
+        let front_matter = "---\nrelease: minor\n---\n\nAdds a thing.";
+        assert!(matches!(
+            bump_from_description(front_matter),
+            Some((VersionBump::Minor, _))
+        ));
+        let inline = "Adds a thing.\r\n\r\n```release: Major```\r\n";
+        assert!(matches!(
+            bump_from_description(inline),
+            Some((VersionBump::Major, _))
+        ));
+        let block = "Fixes it.\n\n```release\nno-version\n```\n";
+        assert_eq!(
+            bump_from_description(block).map(|(_, directive)| directive),
+            Some("release: no-version".to_string())
+        );
+        assert!(bump_from_description("```rust\nlet release: u8 = 1;\n```").is_none());
+        assert!(bump_from_description("Mentions release: minor in passing").is_none());
+    }
+
+    #[test]
+    fn test_next_version() {
+        let fetched = |messages| Fetched {
+            version: "1.2.3",
+            messages,
+        };
+        let next = |fetched: &Fetched, precedence| {
+            next_version(fetched, precedence, &BumpOptions::default())
+                .unwrap()
+                .map(|(bump, version)| (bump, version.to_string()))
+        };
+        let commits = fetched(&["Fix crash [patch]", "Start 1.3 [preminor]"]);
+        assert_eq!(
+            next(&commits, Precedence::Stable),
+            Some((VersionBump::Patch, "1.2.4".to_string()))
+        );
+        assert_eq!(
+            next(&commits, Precedence::Prerelease),
+            Some((VersionBump::Preminor, "1.3.0-alpha.0".to_string()))
+        );
+        assert_eq!(next(&fetched(&["Tidy up"]), Precedence::Stable), None);
+    }
+}
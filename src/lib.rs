@@ -0,0 +1,15 @@
+//! Bumping crate versions. The version arithmetic at the root needs nothing but the versions
+//! themselves, so it builds anywhere (wasm32-wasip1 included); the `cli` feature adds the
+//! `version` command line, which reads manifests, git, and registries around it, and the
+//! `testing` feature adds in-memory workspaces for testing release policies against
+
+pub mod bump;
+#[cfg(feature = "cli")]
+mod cli;
+pub mod inference;
+pub mod plan;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+#[cfg(feature = "cli")]
+pub use cli::main as run;
@@ -0,0 +1,266 @@
+//! Planning a workspace release without touching it: which members move to which version,
+//! and which requirements between members the new versions no longer meet. Only the bump
+//! arithmetic is modelled; the config's release policy (`exclude`, tiers, freezes,
+//! approvals) is the caller's to apply
+
+use semver::{Version, VersionReq};
+
+use crate::bump::{BumpOptions, VersionBump};
+
+/// A workspace: its `[workspace.package]` version and its members
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Workspace {
+    pub version: Option<Version>,
+    pub members: Vec<Member>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    /// The member's own version, `None` when it inherits the workspace's
+    pub version: Option<Version>,
+    /// The freeze annotation, if the member opted out of automated bumps
+    pub frozen: Option<String>,
+    /// Its requirements on other members
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    pub requirement: String,
+}
+
+/// The versions a release would write
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Plan {
+    /// The workspace version, when it moves
+    pub workspace: Option<(Version, Version)>,
+    /// Every member whose version moves
+    pub crates: Vec<Planned>,
+    /// Every requirement between members that a new version doesn't satisfy, as
+    /// `version check --workspace` would report after the release
+    pub unmet: Vec<Unmet>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Planned {
+    pub name: String,
+    pub old: Version,
+    pub new: Version,
+    pub bump: VersionBump,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unmet {
+    pub dependent: String,
+    pub dependency: String,
+    pub requirement: String,
+    /// The dependency's new version
+    pub version: Version,
+}
+
+impl Workspace {
+    /// Move the workspace version by `bump`, and every member inheriting it along with it.
+    /// Members with their own version stay put
+    pub fn release(&self, bump: VersionBump, options: &BumpOptions) -> anyhow::Result<Plan> {
+        let old = self
+            .version
+            .clone()
+            .ok_or(anyhow::anyhow!("The workspace has no version to bump"))?;
+        let new = bump.apply(old.clone(), options)?;
+        let mut plan = Plan::default();
+        if new == old {
+            return Ok(plan);
+        }
+        for member in self
+            .members
+            .iter()
+            .filter(|member| member.version.is_none())
+        {
+            if let Some(annotation) = &member.frozen {
+                anyhow::bail!(
+                    "{} is frozen ({annotation}) but inherits the workspace version; give it its own version or remove the annotation",
+                    member.name
+                );
+            }
+            plan.crates.push(Planned {
+                name: member.name.clone(),
+                old: old.clone(),
+                new: new.clone(),
+                bump,
+            });
+        }
+        plan.workspace = Some((old, new));
+        plan.unmet = self.unmet(&plan.crates);
+        Ok(plan)
+    }
+
+    /// Move each member with its own version by the bump `choose` picks for it, leaving
+    /// frozen members and the workspace version alone
+    pub fn release_members(
+        &self,
+        mut choose: impl FnMut(&Member, &Version) -> VersionBump,
+        options: &BumpOptions,
+    ) -> anyhow::Result<Plan> {
+        let mut plan = Plan::default();
+        for member in self.members.iter().filter(|member| member.frozen.is_none()) {
+            let old = member.version.clone().ok_or(anyhow::anyhow!(
+                "{} inherits the workspace version, so it can't be bumped on its own",
+                member.name
+            ))?;
+            let bump = choose(member, &old);
+            let new = bump
+                .apply(old.clone(), options)
+                .map_err(|e| anyhow::anyhow!("{}: {e}", member.name))?;
+            if new != old {
+                plan.crates.push(Planned {
+                    name: member.name.clone(),
+                    old,
+                    new,
+                    bump,
+                });
+            }
+        }
+        plan.unmet = self.unmet(&plan.crates);
+        Ok(plan)
+    }
+
+    /// The members' requirements on the `moved` crates that their new versions don't
+    /// satisfy. Requirements that don't parse are left to `version check`
+    fn unmet(&self, moved: &[Planned]) -> Vec<Unmet> {
+        let mut unmet = Vec::new();
+        for member in &self.members {
+            for dependency in &member.dependencies {
+                let Some(planned) = moved.iter().find(|planned| planned.name == dependency.name)
+                else {
+                    continue;
+                };
+                if VersionReq::parse(&dependency.requirement)
+                    .is_ok_and(|requirement| !requirement.matches(&planned.new))
+                {
+                    unmet.push(Unmet {
+                        dependent: member.name.clone(),
+                        dependency: dependency.name.clone(),
+                        requirement: dependency.requirement.clone(),
+                        version: planned.new.clone(),
+                    });
+                }
+            }
+        }
+        unmet
+    }
+}
+
+impl Plan {
+    /// The version `name` moves to, if it moves
+    #[must_use]
+    pub fn version(&self, name: &str) -> Option<&Version> {
+        self.crates
+            .iter()
+            .find(|planned| planned.name == name)
+            .map(|planned| &planned.new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, version: Option<&str>, dependencies: &[(&str, &str)]) -> Member {
+        Member {
+            name: name.to_string(),
+            version: version.map(|version| Version::parse(version).unwrap()),
+            frozen: None,
+            dependencies: dependencies
+                .iter()
+                .map(|(name, requirement)| Dependency {
+                    name: name.to_string(),
+                    requirement: requirement.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_release() {
+        let mut workspace = Workspace {
+            version: Some(Version::new(1, 2, 3)),
+            members: vec![
+                member("core", None, &[]),
+                member("cli", None, &[("core", "1.2")]),
+                member("tool", Some("0.4.0"), &[("core", "=1.2.3")]),
+            ],
+        };
+        let options = BumpOptions::default();
+        let plan = workspace.release(VersionBump::Minor, &options).unwrap();
+        assert_eq!(
+            plan.workspace,
+            Some((Version::new(1, 2, 3), Version::new(1, 3, 0)))
+        );
+        assert_eq!(plan.version("core"), Some(&Version::new(1, 3, 0)));
+        assert_eq!(plan.version("tool"), None);
+        assert_eq!(
+            plan.unmet,
+            [Unmet {
+                dependent: "tool".to_string(),
+                dependency: "core".to_string(),
+                requirement: "=1.2.3".to_string(),
+                version: Version::new(1, 3, 0),
+            }]
+        );
+
+        // A prerelease meets no plain requirement
+        let prerelease = workspace.release(VersionBump::Preminor, &options).unwrap();
+        assert_eq!(prerelease.crates.len(), 2);
+        assert_eq!(prerelease.unmet.len(), 2);
+        assert_eq!(
+            workspace.release(VersionBump::Skip, &options).unwrap(),
+            Plan::default()
+        );
+
+        workspace.members[0].frozen = Some("until 2.0".to_string());
+        let error = workspace.release(VersionBump::Minor, &options).unwrap_err();
+        assert!(error.to_string().contains("core is frozen (until 2.0)"));
+    }
+
+    #[test]
+    fn test_release_members() {
+        let mut workspace = Workspace {
+            version: None,
+            members: vec![
+                member("core", Some("0.4.2"), &[]),
+                member("cli", Some("1.0.0"), &[("core", "0.4")]),
+                member("docs", Some("0.1.0"), &[]),
+            ],
+        };
+        workspace.members[2].frozen = Some("archived".to_string());
+        let plan = workspace
+            .release_members(
+                |member, _| match member.name.as_str() {
+                    "core" => VersionBump::Minor,
+                    _ => VersionBump::Skip,
+                },
+                &BumpOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            plan.crates,
+            [Planned {
+                name: "core".to_string(),
+                old: Version::new(0, 4, 2),
+                new: Version::new(0, 5, 0),
+                bump: VersionBump::Minor,
+            }]
+        );
+        // A caret requirement below 1.0 stops at the next minor
+        assert_eq!(plan.unmet[0].dependent, "cli");
+
+        workspace.members.push(member("shared", None, &[]));
+        assert!(
+            workspace
+                .release_members(|_, _| VersionBump::Patch, &BumpOptions::default())
+                .is_err()
+        );
+    }
+}
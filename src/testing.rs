@@ -0,0 +1,292 @@
+//! In-memory workspaces for testing release policies built on this crate, without fixtures
+//! on disk. Build one with [`WorkspaceBuilder`], release it, and assert on the [`Outcome`]
+
+// Panicking on a mismatch is what the builders and assertions are for, and the assertions
+// return themselves only so they chain
+#![allow(clippy::missing_panics_doc, clippy::must_use_candidate)]
+
+use semver::Version;
+
+use crate::bump::{BumpOptions, VersionBump};
+use crate::inference::{self, Precedence, Repository};
+use crate::plan::{Dependency, Member, Plan, Workspace};
+
+/// Builds a [`Fixture`]. Versions and names are checked as they're added, panicking on
+/// anything a real workspace couldn't hold
+#[derive(Clone, Debug, Default)]
+pub struct WorkspaceBuilder {
+    workspace: Workspace,
+    commits: Vec<String>,
+}
+
+impl WorkspaceBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `[workspace.package]` version
+    #[must_use]
+    pub fn version(mut self, version: &str) -> Self {
+        self.workspace.version = Some(parse(version));
+        self
+    }
+
+    /// A member with its own version
+    #[must_use]
+    pub fn member(self, name: &str, version: &str) -> Self {
+        self.add(name, Some(parse(version)))
+    }
+
+    /// A member inheriting the workspace version
+    #[must_use]
+    pub fn inheriting(self, name: &str) -> Self {
+        self.add(name, None)
+    }
+
+    /// `count` more members on `version`, or inheriting the workspace's for `None`, each
+    /// named `member-{index}` for its place in the workspace
+    #[must_use]
+    pub fn members(mut self, count: usize, version: Option<&str>) -> Self {
+        let start = self.workspace.members.len();
+        for index in start..start + count {
+            self = self.add(&format!("member-{index}"), version.map(parse));
+        }
+        self
+    }
+
+    /// `dependent` requires `dependency` at `requirement`, e.g. `"1.2"` or `"=1.2.3"`
+    #[must_use]
+    pub fn depends(mut self, dependent: &str, dependency: &str, requirement: &str) -> Self {
+        assert!(
+            self.position(dependency).is_some(),
+            "No workspace member named {dependency}"
+        );
+        let index = self.expect(dependent);
+        self.workspace.members[index].dependencies.push(Dependency {
+            name: dependency.to_string(),
+            requirement: requirement.to_string(),
+        });
+        self
+    }
+
+    /// Freeze `name`, with the annotation's reason
+    #[must_use]
+    pub fn frozen(mut self, name: &str, annotation: &str) -> Self {
+        let index = self.expect(name);
+        self.workspace.members[index].frozen = Some(annotation.to_string());
+        self
+    }
+
+    /// An unreleased commit, after the ones already added
+    #[must_use]
+    pub fn commit(mut self, message: &str) -> Self {
+        self.commits.push(message.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Fixture {
+        Fixture {
+            workspace: self.workspace,
+            commits: self.commits,
+        }
+    }
+
+    fn add(mut self, name: &str, version: Option<Version>) -> Self {
+        assert!(
+            self.position(name).is_none(),
+            "There's already a workspace member named {name}"
+        );
+        self.workspace.members.push(Member {
+            name: name.to_string(),
+            version,
+            frozen: None,
+            dependencies: Vec::new(),
+        });
+        self
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.workspace
+            .members
+            .iter()
+            .position(|member| member.name == name)
+    }
+
+    fn expect(&self, name: &str) -> usize {
+        self.position(name)
+            .unwrap_or_else(|| panic!("No workspace member named {name}"))
+    }
+}
+
+/// A built workspace and its unreleased commits, which inference reads as a [`Repository`]
+#[derive(Clone, Debug)]
+pub struct Fixture {
+    pub workspace: Workspace,
+    /// Oldest first, as they were added
+    pub commits: Vec<String>,
+}
+
+impl Fixture {
+    /// Release the workspace version, and every member inheriting it, by `bump`
+    pub fn release(&self, bump: VersionBump, options: &BumpOptions) -> anyhow::Result<Outcome> {
+        Ok(Outcome(self.workspace.release(bump, options)?))
+    }
+
+    /// Release each member with its own version by the bump `choose` picks
+    pub fn release_members(
+        &self,
+        choose: impl FnMut(&Member, &Version) -> VersionBump,
+        options: &BumpOptions,
+    ) -> anyhow::Result<Outcome> {
+        Ok(Outcome(self.workspace.release_members(choose, options)?))
+    }
+
+    /// Release the workspace version by the bump its commits' markers ask for, settled by
+    /// `precedence`, or by prepatch when no commit has one
+    pub fn release_inferred(
+        &self,
+        precedence: Precedence,
+        options: &BumpOptions,
+    ) -> anyhow::Result<Outcome> {
+        let bump = inference::infer(self, precedence)?.unwrap_or(VersionBump::Prepatch);
+        self.release(bump, options)
+    }
+}
+
+impl Repository for Fixture {
+    fn version(&self) -> anyhow::Result<Version> {
+        self.workspace
+            .version
+            .clone()
+            .ok_or(anyhow::anyhow!("The workspace has no version"))
+    }
+
+    fn unreleased_messages(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self.commits.iter().rev().cloned().collect())
+    }
+}
+
+/// The plan a release came to, with assertions that panic saying what it holds instead
+#[derive(Clone, Debug)]
+pub struct Outcome(pub Plan);
+
+impl Outcome {
+    /// `name` moves to `version`
+    #[track_caller]
+    pub fn assert_version(&self, name: &str, version: &str) -> &Self {
+        match self.0.version(name) {
+            Some(new) => assert_eq!(new.to_string(), version, "{name}'s new version"),
+            None => panic!("{name} isn't released; expected it to move to {version}"),
+        }
+        self
+    }
+
+    /// `name` keeps its version
+    #[track_caller]
+    pub fn assert_unchanged(&self, name: &str) -> &Self {
+        if let Some(new) = self.0.version(name) {
+            panic!("{name} moves to {new}; expected it to keep its version");
+        }
+        self
+    }
+
+    /// `dependent`'s requirement on `dependency` no longer matches the version it moves to
+    #[track_caller]
+    pub fn assert_unmet(&self, dependent: &str, dependency: &str) -> &Self {
+        assert!(
+            self.0
+                .unmet
+                .iter()
+                .any(|unmet| unmet.dependent == dependent && unmet.dependency == dependency),
+            "{dependent}'s requirement on {dependency} is still met"
+        );
+        self
+    }
+
+    /// Every requirement between members still matches after the release
+    #[track_caller]
+    pub fn assert_all_met(&self) -> &Self {
+        if let Some(unmet) = self.0.unmet.first() {
+            panic!(
+                "{}'s requirement `{}` on {} doesn't match {}",
+                unmet.dependent, unmet.requirement, unmet.dependency, unmet.version
+            );
+        }
+        self
+    }
+
+    /// Exactly `names` are released, in any order
+    #[track_caller]
+    pub fn assert_released(&self, names: &[&str]) -> &Self {
+        let mut released = self
+            .0
+            .crates
+            .iter()
+            .map(|planned| planned.name.as_str())
+            .collect::<Vec<_>>();
+        let mut expected = names.to_vec();
+        released.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(released, expected, "the released crates");
+        self
+    }
+}
+
+#[track_caller]
+fn parse(version: &str) -> Version {
+    Version::parse(version).unwrap_or_else(|e| panic!("{version}: invalid version: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let fixture = WorkspaceBuilder::new()
+            .version("2.0.0")
+            .inheriting("core")
+            .members(2, Some("0.1.0"))
+            .depends("member-1", "core", "~2.0")
+            .frozen("member-2", "vendored")
+            .commit("Add a thing [minor]")
+            .commit("Fix the thing [patch]")
+            .build();
+        assert_eq!(
+            fixture
+                .workspace
+                .members
+                .iter()
+                .map(|member| member.name.as_str())
+                .collect::<Vec<_>>(),
+            ["core", "member-1", "member-2"]
+        );
+        assert_eq!(
+            fixture.unreleased_messages().unwrap()[0],
+            "Fix the thing [patch]"
+        );
+
+        fixture
+            .release_inferred(Precedence::Stable, &BumpOptions::default())
+            .unwrap()
+            .assert_released(&["core"])
+            .assert_version("core", "2.1.0")
+            .assert_unmet("member-1", "core");
+        fixture
+            .release_members(|_, _| VersionBump::Patch, &BumpOptions::default())
+            .unwrap_err();
+    }
+
+    #[test]
+    #[should_panic(expected = "member-0 isn't released")]
+    fn test_outcome_reports() {
+        WorkspaceBuilder::new()
+            .members(1, Some("1.0.0"))
+            .build()
+            .release_members(|_, _| VersionBump::Skip, &BumpOptions::default())
+            .unwrap()
+            .assert_version("member-0", "1.0.1");
+    }
+}